@@ -1,23 +1,54 @@
-use std::{path::Path, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    future::Future,
+    path::Path,
+    sync::Arc,
+    time::Duration,
+};
 
+use futures_util::TryStreamExt;
 use jaytripper_core::{
-    SignatureEventSource, SystemSignaturesObservedEvent,
+    CHARACTER_MOVED_EVENT_TYPE, MovementEvent, MovementEventSink, MovementEventSource,
+    MovementReason, ProjectedSignature, SignatureEntry, SignatureEventSource,
+    SystemSignaturesBaselinePayload, SystemSignaturesClearedEvent, SystemSignaturesObservedEvent,
+    character_stream_key,
     ids::{CharacterId, SolarSystemId},
-    parse_signature_snapshot,
-    time::Timestamp,
+    parse_signature_snapshot, parse_signature_snapshot_lenient,
+    time::{Clock, SystemClock, Timestamp},
+};
+use jaytripper_esi::{
+    EsiClient, LocationIngestor, LocationPollConfig, PollMetrics, PollOutcomeSummary,
+};
+use jaytripper_store::{EventLogStore, EventRecord, EventSource, GlobalSeq, StoreError};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    sync::{Mutex, broadcast, watch},
+    task::JoinSet,
 };
-use jaytripper_esi::{EsiClient, LocationIngestor, LocationPollConfig};
-use jaytripper_store::{EventLogStore, GlobalSeq};
-use tokio::sync::{Mutex, watch};
 use uuid::Uuid;
 
 use crate::{
     AppError,
-    projection_runtime::{ProjectionRuntimeState, project_records_with_monotonic_guard},
+    projection_runtime::{
+        ProjectionRuntimeState, project_record_with_monotonic_guard,
+        project_records_with_monotonic_guard,
+    },
     signature_resolution::{SignatureTargetSystemResolution, resolve_signature_target_system},
-    sink::AppMovementSink,
+    sink::{AppMovementSink, ObservingMovementSink},
+    state::{AppProjection, DecodedEvent, SystemSignaturesProjection, decode_event_record},
 };
 
+/// Identical pastes for one system closer together than this are treated as
+/// a double submit.
+const DUPLICATE_SNAPSHOT_WINDOW: Duration = Duration::from_secs(10);
+
+/// Reauth notices buffered per subscriber before the oldest are dropped.
+const REAUTH_CHANNEL_CAPACITY: usize = 16;
+
+/// Watched-system alerts buffered per subscriber before the oldest are
+/// dropped.
+const ALERT_CHANNEL_CAPACITY: usize = 64;
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum SignatureSnapshotRecordContext {
     Auto {
@@ -28,31 +59,176 @@ pub enum SignatureSnapshotRecordContext {
         system_id: SolarSystemId,
         attribution_character_id: Option<CharacterId>,
     },
+    /// Attributes the snapshot to the one tracked character currently in
+    /// `focused_system_id`.
+    AutoInferCharacter { focused_system_id: SolarSystemId },
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum SignatureSnapshotRecordOutcome {
     Recorded {
         system_id: SolarSystemId,
     },
-    NeedsConfirmation {
+    /// Recorded from a lenient parse that skipped some lines; each warning is
+    /// the 1-based line number and the parse error for that line.
+    RecordedWithWarnings {
+        system_id: SolarSystemId,
+        warnings: Vec<(usize, String)>,
+    },
+    /// The paste contained no signatures, so nothing was appended. Use
+    /// [`AppRuntime::clear_system_signatures`] to record a system as empty.
+    Empty,
+    /// An identical paste for the same system was recorded moments ago, so
+    /// nothing was appended.
+    AlreadyRecorded {
+        system_id: SolarSystemId,
+    },
+    /// The attributed character is elsewhere; pass the pending record to
+    /// [`AppRuntime::confirm_and_record`] to record it without re-parsing.
+    NeedsConfirmation(PendingSignatureRecord),
+    /// Attribution could not be inferred because zero or several tracked
+    /// characters are in the focused system; `candidates` lists the latter.
+    NeedsDisambiguation {
         focused_system_id: SolarSystemId,
-        character_system_id: SolarSystemId,
-        character_id: CharacterId,
+        candidates: Vec<CharacterId>,
     },
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// A parsed snapshot awaiting confirmation of which system it belongs to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingSignatureRecord {
+    pub focused_system_id: SolarSystemId,
+    pub character_system_id: SolarSystemId,
+    pub character_id: CharacterId,
+    /// Where [`AppRuntime::confirm_and_record`] records the snapshot; starts
+    /// as `focused_system_id`.
+    pub target_system_id: SolarSystemId,
+    /// When the snapshot was taken; kept so a confirmed backfill keeps its
+    /// historical date.
+    pub observed_at: Timestamp,
+    entries: Vec<SignatureEntry>,
+}
+
+impl PendingSignatureRecord {
+    pub fn entries(&self) -> &[SignatureEntry] {
+        &self.entries
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CharacterLocationView {
     pub character_id: CharacterId,
     pub current_system_id: SolarSystemId,
     pub last_movement_observed_at: Timestamp,
 }
 
+/// How long and how actively a character has been tracked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CharacterActivity {
+    pub first_movement_at: Timestamp,
+    pub last_movement_at: Timestamp,
+    pub total_movements: u32,
+}
+
+/// Exportable view of one system's projected signatures, sorted by
+/// signature id.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SystemSignaturesView {
+    pub system_id: SolarSystemId,
+    pub last_snapshot_id: Option<String>,
+    pub last_snapshot_at: Option<Timestamp>,
+    pub signatures: Vec<ProjectedSignature>,
+}
+
+/// The whole projection as one document, for sharing a chain map or
+/// attaching to a bug report.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StateExport {
+    /// Sequence number of the last event folded into the projection.
+    pub last_applied_seq: Option<i64>,
+    /// The system the exporting UI had focused, if any.
+    pub focused_system_id: Option<SolarSystemId>,
+    /// Sorted by character id.
+    pub characters: Vec<CharacterLocationView>,
+    /// Every system with signature state, sorted by system id.
+    pub systems: Vec<SystemSignaturesView>,
+}
+
+/// One hop from a character's movement stream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CharacterMovement {
+    pub occurred_at: Timestamp,
+    pub from_system_id: Option<SolarSystemId>,
+    pub to_system_id: SolarSystemId,
+    pub reason: Option<MovementReason>,
+}
+
+/// A tracked character arrived in a watched system.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SystemAlert {
+    pub character_id: CharacterId,
+    pub system_id: SolarSystemId,
+    pub observed_at: Timestamp,
+}
+
+/// A tracked character's ESI client can no longer refresh its session. The
+/// character stays listed until it logs in again; ingestion of other
+/// characters is unaffected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReauthNeeded {
+    pub character_id: CharacterId,
+    pub reason: String,
+}
+
+/// Latest poll health of one ingesting character.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IngestionStatus {
+    pub metrics: PollMetrics,
+    pub consecutive_failures: u32,
+    pub needs_reauth: bool,
+}
+
+impl IngestionStatus {
+    fn record_poll(&mut self, summary: &PollOutcomeSummary, now: Timestamp) {
+        if summary.success {
+            self.metrics.last_success_at = Some(now);
+            self.metrics.last_poll_latency = Some(summary.latency);
+        }
+        self.consecutive_failures = summary.consecutive_failures;
+        self.needs_reauth = summary.needs_reauth;
+    }
+}
+
+/// Size of the in-memory projection, for watching growth in long-running
+/// trackers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProjectionStats {
+    pub character_count: usize,
+    /// Systems with signature state.
+    pub system_count: usize,
+    pub total_signatures: usize,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KnownEntities {
+    pub characters: Vec<CharacterId>,
+    pub systems: Vec<SolarSystemId>,
+}
+
 #[derive(Clone)]
 pub struct AppRuntime {
     store: EventLogStore,
     state: Arc<Mutex<ProjectionRuntimeState>>,
+    clock: Arc<dyn Clock + Send + Sync>,
+    /// Shared by every clone, so subscribers see movements projected through
+    /// any handle (e.g. the one moved into an ingestion task).
+    movements: Arc<watch::Sender<Option<MovementEvent>>>,
+    watched_systems: Arc<Mutex<HashSet<SolarSystemId>>>,
+    alerts: broadcast::Sender<SystemAlert>,
+    ingestion_statuses: Arc<watch::Sender<HashMap<CharacterId, IngestionStatus>>>,
+    reauth_needed: broadcast::Sender<ReauthNeeded>,
+    atomic_movement_writes: bool,
+    reauth_retry_interval: Option<Duration>,
 }
 
 impl AppRuntime {
@@ -65,28 +241,104 @@ impl AppRuntime {
         let app = Self {
             store,
             state: Arc::new(Mutex::new(ProjectionRuntimeState::default())),
+            clock: Arc::new(SystemClock),
+            movements: Arc::new(watch::Sender::new(None)),
+            watched_systems: Arc::new(Mutex::new(HashSet::new())),
+            alerts: broadcast::Sender::new(ALERT_CHANNEL_CAPACITY),
+            ingestion_statuses: Arc::new(watch::Sender::new(HashMap::new())),
+            reauth_needed: broadcast::Sender::new(REAUTH_CHANNEL_CAPACITY),
+            atomic_movement_writes: false,
+            reauth_retry_interval: None,
         };
         app.initialize_from_event_log().await?;
         Ok(app)
     }
 
+    /// Replaces the clock used to stamp manually recorded events.
+    pub fn with_clock(mut self, clock: impl Clock + Send + Sync + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Holds the projection lock across each ingested movement's append and
+    /// catch-up, so no reader of this runtime sees an event in the store
+    /// before it is projected.
+    ///
+    /// Off by default: readers then wait behind the store write as well as
+    /// the projection, and concurrent ingestors serialize their appends.
+    pub fn with_atomic_movement_writes(mut self) -> Self {
+        self.atomic_movement_writes = true;
+        self
+    }
+
+    /// Keeps ingestors alive while their client needs reauth, checking every
+    /// `interval` and resuming once it recovers (see
+    /// [`LocationIngestor::with_reauth_retry`]). Reauth notices are still
+    /// published. Off by default: ingestion then ends with the reauth error.
+    pub fn with_reauth_retry(mut self, interval: Duration) -> Self {
+        self.reauth_retry_interval = Some(interval);
+        self
+    }
+
+    pub(crate) fn now(&self) -> Timestamp {
+        self.clock.now()
+    }
+
+    /// Subscribes to movements as they are persisted and projected.
+    ///
+    /// The feed only holds the latest movement, so a slow reader may miss
+    /// intermediate hops; use the event log when every hop matters.
+    pub fn subscribe_movements(&self) -> watch::Receiver<Option<MovementEvent>> {
+        self.movements.subscribe()
+    }
+
+    /// Raises a [`SystemAlert`] whenever a tracked character moves into
+    /// `system_id`, whether the movement is persisted or only observed (see
+    /// [`Self::observe_ingestion_until_shutdown`]).
+    pub async fn watch_system(&self, system_id: SolarSystemId) {
+        self.watched_systems.lock().await.insert(system_id);
+    }
+
+    pub async fn unwatch_system(&self, system_id: SolarSystemId) {
+        self.watched_systems.lock().await.remove(&system_id);
+    }
+
+    /// Subscribes to arrivals in watched systems. Like
+    /// [`Self::subscribe_reauth_needed`], every alert is queued, so arrivals
+    /// in quick succession are not collapsed.
+    pub fn subscribe_alerts(&self) -> broadcast::Receiver<SystemAlert> {
+        self.alerts.subscribe()
+    }
+
+    /// Poll health of every character ingested through this runtime, as of
+    /// each one's latest poll. Characters stay listed after their ingestor
+    /// stops.
+    pub fn ingestion_status(&self) -> HashMap<CharacterId, IngestionStatus> {
+        self.ingestion_statuses.borrow().clone()
+    }
+
+    /// Subscribes to characters whose client flags reauth while ingesting
+    /// through this runtime. Unlike the watch feeds, every notice is queued,
+    /// so a UI can keep one banner per character.
+    pub fn subscribe_reauth_needed(&self) -> broadcast::Receiver<ReauthNeeded> {
+        self.reauth_needed.subscribe()
+    }
+
+    fn publish_movement(&self, event: MovementEvent) {
+        self.movements.send_replace(Some(event));
+    }
+
     fn movement_sink(&self) -> AppMovementSink {
-        AppMovementSink::new(self.clone())
+        if self.atomic_movement_writes {
+            AppMovementSink::atomic(self.clone())
+        } else {
+            AppMovementSink::new(self.clone())
+        }
     }
 
+    /// Every tracked character's location, sorted by character id.
     pub async fn character_locations(&self) -> Vec<CharacterLocationView> {
-        self.state
-            .lock()
-            .await
-            .projection
-            .characters
-            .iter()
-            .map(|(character_id, status)| CharacterLocationView {
-                character_id: *character_id,
-                current_system_id: status.current_system_id,
-                last_movement_observed_at: status.last_movement_observed_at,
-            })
-            .collect()
+        character_location_views(&self.state.lock().await.projection)
     }
 
     pub async fn character_current_system(
@@ -102,14 +354,278 @@ impl AppRuntime {
             .map(|status| status.current_system_id)
     }
 
+    /// Returns the projected signatures for `system_id`, sorted by signature
+    /// id, or `None` if no snapshot has been recorded there.
+    pub async fn system_signatures(
+        &self,
+        system_id: SolarSystemId,
+    ) -> Option<SystemSignaturesView> {
+        let state = self.state.lock().await;
+        let system = state.projection.signatures_by_system.get(&system_id)?;
+        Some(system_signatures_view(system_id, system))
+    }
+
+    /// Snapshots every character and system under one lock, so the export is
+    /// consistent with `last_applied_seq`.
+    pub async fn export_state(&self, focused_system_id: Option<SolarSystemId>) -> StateExport {
+        let state = self.state.lock().await;
+
+        let characters = character_location_views(&state.projection);
+
+        let mut systems: Vec<SystemSignaturesView> = state
+            .projection
+            .signatures_by_system
+            .iter()
+            .map(|(system_id, system)| system_signatures_view(*system_id, system))
+            .collect();
+        systems.sort_by_key(|system| system.system_id.0);
+
+        StateExport {
+            last_applied_seq: state.last_projected_seq.map(|seq| seq.0),
+            focused_system_id,
+            characters,
+            systems,
+        }
+    }
+
+    /// [`Self::export_state`] as pretty-printed JSON.
+    pub async fn export_state_json(
+        &self,
+        focused_system_id: Option<SolarSystemId>,
+    ) -> Result<String, AppError> {
+        Ok(serde_json::to_string_pretty(
+            &self.export_state(focused_system_id).await,
+        )?)
+    }
+
+    /// Returns the entries of the most recent snapshot for `system_id` exactly
+    /// as recorded, unlike [`Self::system_signatures`], which accumulates
+    /// every snapshot.
+    pub async fn latest_snapshot_entries(
+        &self,
+        system_id: SolarSystemId,
+    ) -> Option<Vec<SignatureEntry>> {
+        let state = self.state.lock().await;
+        let system = state.projection.signatures_by_system.get(&system_id)?;
+        system.last_snapshot.as_ref()?;
+        Some(system.latest_entries.clone())
+    }
+
+    /// Lists every character and system the projection knows about, sorted by
+    /// id. Systems include both signature-tracked systems and systems a
+    /// character currently occupies.
+    pub async fn known_entities(&self) -> KnownEntities {
+        let state = self.state.lock().await;
+        let projection = &state.projection;
+
+        let characters: BTreeSet<u64> = projection.characters.keys().map(|id| id.0).collect();
+        let systems: BTreeSet<i32> = projection
+            .signatures_by_system
+            .keys()
+            .copied()
+            .chain(
+                projection
+                    .characters
+                    .values()
+                    .map(|status| status.current_system_id),
+            )
+            .map(|id| id.0)
+            .collect();
+
+        KnownEntities {
+            characters: characters.into_iter().map(CharacterId).collect(),
+            systems: systems.into_iter().map(SolarSystemId).collect(),
+        }
+    }
+
+    /// Summarizes a character's movement stream, or `None` if it has never
+    /// moved.
+    pub async fn character_activity(
+        &self,
+        character_id: CharacterId,
+    ) -> Result<Option<CharacterActivity>, AppError> {
+        let records = self
+            .store
+            .read_events_by_stream(&character_stream_key(character_id))
+            .await
+            .map_err(AppError::store_op("character_activity"))?;
+
+        let mut activity: Option<CharacterActivity> = None;
+        for record in records
+            .iter()
+            .filter(|record| record.envelope.event_type == CHARACTER_MOVED_EVENT_TYPE)
+        {
+            let occurred_at = record.envelope.occurred_at;
+            activity = Some(match activity {
+                None => CharacterActivity {
+                    first_movement_at: occurred_at,
+                    last_movement_at: occurred_at,
+                    total_movements: 1,
+                },
+                Some(activity) => CharacterActivity {
+                    first_movement_at: activity.first_movement_at.min(occurred_at),
+                    last_movement_at: activity.last_movement_at.max(occurred_at),
+                    total_movements: activity.total_movements + 1,
+                },
+            });
+        }
+
+        Ok(activity)
+    }
+
+    /// Reads a character's movements from the store in log order, without
+    /// touching ESI.
+    pub async fn character_movement_history(
+        &self,
+        character_id: CharacterId,
+    ) -> Result<Vec<CharacterMovement>, AppError> {
+        let records = self
+            .store
+            .read_events_by_stream(&character_stream_key(character_id))
+            .await
+            .map_err(AppError::store_op("character_movement_history"))?;
+
+        let mut movements = Vec::new();
+        for record in &records {
+            if let Some(DecodedEvent::CharacterMoved {
+                occurred_at,
+                payload,
+                ..
+            }) = decode_event_record(record)?
+            {
+                movements.push(CharacterMovement {
+                    occurred_at,
+                    from_system_id: payload.from_system_id,
+                    to_system_id: payload.to_system_id,
+                    reason: payload.reason,
+                });
+            }
+        }
+
+        Ok(movements)
+    }
+
+    /// Counts a character's moves per `bucket` (see [`Timestamp::truncate_to`]),
+    /// ordered by bucket start. The result is sparse: buckets without moves
+    /// are omitted, so callers drawing a sparkline fill the gaps themselves.
+    pub async fn movement_histogram(
+        &self,
+        character_id: CharacterId,
+        bucket: Duration,
+    ) -> Result<Vec<(Timestamp, u32)>, AppError> {
+        let records = self
+            .store
+            .read_events_by_stream(&character_stream_key(character_id))
+            .await
+            .map_err(AppError::store_op("movement_histogram"))?;
+
+        let mut counts: BTreeMap<Timestamp, u32> = BTreeMap::new();
+        for record in records
+            .iter()
+            .filter(|record| record.envelope.event_type == CHARACTER_MOVED_EVENT_TYPE)
+        {
+            *counts
+                .entry(record.envelope.occurred_at.truncate_to(bucket))
+                .or_default() += 1;
+        }
+
+        Ok(counts.into_iter().collect())
+    }
+
+    /// Returns the signature snapshots recorded for `system_id` together with
+    /// every movement that arrived in it, ordered by global sequence.
+    ///
+    /// Movement events live in per-character streams, so this streams the
+    /// full log rather than a single stream, keeping only matching events.
+    pub async fn events_affecting_system(
+        &self,
+        system_id: SolarSystemId,
+    ) -> Result<Vec<DecodedEvent>, AppError> {
+        let records = self.store.stream_ordered_events();
+        futures_util::pin_mut!(records);
+
+        let mut events = Vec::new();
+        while let Some(record) = records
+            .try_next()
+            .await
+            .map_err(AppError::store_op("events_affecting_system"))?
+        {
+            let Some(event) = decode_event_record(&record)? else {
+                continue;
+            };
+            let affects_system = match &event {
+                DecodedEvent::CharacterMoved { payload, .. } => payload.to_system_id == system_id,
+                DecodedEvent::SystemSignaturesObserved { payload, .. } => {
+                    payload.system_id == system_id
+                }
+                DecodedEvent::SystemSignaturesCleared { payload, .. } => {
+                    payload.system_id == system_id
+                }
+                DecodedEvent::SystemSignaturesBaseline { payload, .. } => {
+                    payload.system_id == system_id
+                }
+            };
+            if affects_system {
+                events.push(event);
+            }
+        }
+
+        Ok(events)
+    }
+
+    pub async fn projection_stats(&self) -> ProjectionStats {
+        let state = self.state.lock().await;
+        let projection = &state.projection;
+
+        ProjectionStats {
+            character_count: projection.characters.len(),
+            system_count: projection.signatures_by_system.len(),
+            total_signatures: projection
+                .signatures_by_system
+                .values()
+                .map(|system| system.signatures_by_id.len())
+                .sum(),
+        }
+    }
+
+    pub async fn last_projected_seq(&self) -> Option<GlobalSeq> {
+        self.state.lock().await.last_projected_seq
+    }
+
+    /// Number of stale records the projection has skipped since the last full
+    /// replay. Nonzero during normal operation means events were delivered
+    /// out of order.
+    pub async fn skipped_stale_record_count(&self) -> u64 {
+        self.state.lock().await.skipped_stale_records
+    }
+
+    /// Number of incremental catch-ups since the last full replay that found
+    /// sequence numbers missing after the last projected event.
+    pub async fn seq_gap_count(&self) -> u64 {
+        self.state.lock().await.seq_gaps
+    }
+
     pub async fn initialize_from_event_log(&self) -> Result<(), AppError> {
         let mut state = self.state.lock().await;
         *state = ProjectionRuntimeState::default();
 
-        let records = self.store.read_ordered_events().await?;
-        project_records_with_monotonic_guard(&mut state, &records)?;
+        state.projection.begin_replay();
+        let replayed = async {
+            let records = self.store.stream_ordered_events();
+            futures_util::pin_mut!(records);
+            while let Some(record) = records
+                .try_next()
+                .await
+                .map_err(AppError::store_op("initialize"))?
+            {
+                project_record_with_monotonic_guard(&mut state, &record)?;
+            }
+            Ok(())
+        }
+        .await;
+        state.projection.finish_replay();
 
-        Ok(())
+        replayed
     }
 
     pub async fn run_ingestion_until_shutdown<C>(
@@ -121,18 +637,186 @@ impl AppRuntime {
     where
         C: EsiClient + Send + Sync,
     {
+        let character_id = client.character_id();
+        self.ingestion_statuses.send_modify(|statuses| {
+            statuses.entry(character_id).or_default();
+        });
+        let statuses = Arc::clone(&self.ingestion_statuses);
+        let clock = Arc::clone(&self.clock);
+        // Ends on its own once the client, and with it the feed, is dropped.
+        if let Some(reauth_rx) = client.subscribe_reauth() {
+            tokio::spawn(forward_reauth_needed(
+                character_id,
+                reauth_rx,
+                self.reauth_needed.clone(),
+            ));
+        }
+
         let sink = self.movement_sink();
-        let mut ingestor = LocationIngestor::new(client, sink, config);
+        let ingestor = LocationIngestor::new(client, sink, config).with_on_poll(move |summary| {
+            let now = clock.now();
+            statuses.send_modify(|statuses| {
+                statuses
+                    .entry(character_id)
+                    .or_default()
+                    .record_poll(summary, now);
+            });
+        });
+        let mut ingestor = self.apply_reauth_retry(ingestor);
+        ingestor.run_until_shutdown(shutdown_rx).await?;
+        Ok(())
+    }
+
+    /// Polls like [`Self::run_ingestion_until_shutdown`] but hands detected
+    /// movements to `on_movement` instead of writing them to the store.
+    /// Arrivals in watched systems still raise alerts.
+    pub async fn observe_ingestion_until_shutdown<C>(
+        &self,
+        client: C,
+        config: LocationPollConfig,
+        shutdown_rx: watch::Receiver<bool>,
+        on_movement: impl Fn(&MovementEvent) + Send + Sync + 'static,
+    ) -> Result<(), AppError>
+    where
+        C: EsiClient + Send + Sync,
+    {
+        let sink = ObservingMovementSink::new(self.clone(), Box::new(on_movement));
+        let mut ingestor = self.apply_reauth_retry(LocationIngestor::new(client, sink, config));
         ingestor.run_until_shutdown(shutdown_rx).await?;
         Ok(())
     }
 
+    fn apply_reauth_retry<C, S>(&self, ingestor: LocationIngestor<C, S>) -> LocationIngestor<C, S>
+    where
+        C: EsiClient + Send + Sync,
+        S: MovementEventSink + Send + Sync,
+        <S as MovementEventSink>::Error: std::fmt::Display,
+    {
+        match self.reauth_retry_interval {
+            Some(interval) => ingestor.with_reauth_retry(interval),
+            None => ingestor,
+        }
+    }
+
+    /// Runs one ingestor per client, each with its own poll config, until
+    /// shutdown. The first ingestor failure stops the remaining ingestors.
+    pub async fn run_multi_ingestion_until_shutdown<C>(
+        &self,
+        clients: Vec<(C, LocationPollConfig)>,
+        shutdown_rx: watch::Receiver<bool>,
+    ) -> Result<(), AppError>
+    where
+        C: EsiClient + Send + Sync + 'static,
+    {
+        let mut tasks = JoinSet::new();
+        for (client, config) in clients {
+            let app = self.clone();
+            let shutdown_rx = shutdown_rx.clone();
+            tasks.spawn(async move {
+                app.run_ingestion_until_shutdown(client, config, shutdown_rx)
+                    .await
+            });
+        }
+
+        while let Some(joined) = tasks.join_next().await {
+            joined??;
+        }
+
+        Ok(())
+    }
+
+    pub async fn run_multi_ingestion_with_shared_config_until_shutdown<C>(
+        &self,
+        clients: Vec<C>,
+        config: LocationPollConfig,
+        shutdown_rx: watch::Receiver<bool>,
+    ) -> Result<(), AppError>
+    where
+        C: EsiClient + Send + Sync + 'static,
+    {
+        let clients = clients
+            .into_iter()
+            .map(|client| (client, config.clone()))
+            .collect();
+        self.run_multi_ingestion_until_shutdown(clients, shutdown_rx)
+            .await
+    }
+
     pub async fn record_signature_snapshot(
         &self,
         context: SignatureSnapshotRecordContext,
         snapshot_text: &str,
+    ) -> Result<SignatureSnapshotRecordOutcome, AppError> {
+        self.record_signature_snapshot_at(context, snapshot_text, self.clock.now())
+            .await
+    }
+
+    /// Like [`Self::record_signature_snapshot`], but dated `observed_at`
+    /// instead of now, for backfilling scan history.
+    pub async fn record_signature_snapshot_at(
+        &self,
+        context: SignatureSnapshotRecordContext,
+        snapshot_text: &str,
+        observed_at: Timestamp,
     ) -> Result<SignatureSnapshotRecordOutcome, AppError> {
         let entries = parse_signature_snapshot(snapshot_text)?;
+        self.record_signature_entries(context, entries, observed_at)
+            .await
+    }
+
+    /// Records a snapshot for `focused_system_id`, attributed to the one
+    /// tracked character currently in that system. Returns
+    /// `NeedsDisambiguation` instead of recording when there is not exactly one.
+    pub async fn record_signature_snapshot_with_inferred_attribution(
+        &self,
+        focused_system_id: SolarSystemId,
+        snapshot_text: &str,
+    ) -> Result<SignatureSnapshotRecordOutcome, AppError> {
+        self.record_signature_snapshot(
+            SignatureSnapshotRecordContext::AutoInferCharacter { focused_system_id },
+            snapshot_text,
+        )
+        .await
+    }
+
+    /// Like [`Self::record_signature_snapshot`], but records the well-formed
+    /// lines of a partially malformed paste and reports the skipped ones.
+    pub async fn record_signature_snapshot_lenient(
+        &self,
+        context: SignatureSnapshotRecordContext,
+        snapshot_text: &str,
+    ) -> Result<SignatureSnapshotRecordOutcome, AppError> {
+        let parsed = parse_signature_snapshot_lenient(snapshot_text);
+        let outcome = self
+            .record_signature_entries(context, parsed.entries, self.clock.now())
+            .await?;
+
+        Ok(match outcome {
+            SignatureSnapshotRecordOutcome::Recorded { system_id }
+                if !parsed.skipped.is_empty() =>
+            {
+                SignatureSnapshotRecordOutcome::RecordedWithWarnings {
+                    system_id,
+                    warnings: parsed
+                        .skipped
+                        .iter()
+                        .map(|err| (err.line(), err.to_string()))
+                        .collect(),
+                }
+            }
+            other => other,
+        })
+    }
+
+    async fn record_signature_entries(
+        &self,
+        context: SignatureSnapshotRecordContext,
+        entries: Vec<SignatureEntry>,
+        observed_at: Timestamp,
+    ) -> Result<SignatureSnapshotRecordOutcome, AppError> {
+        if entries.is_empty() {
+            return Ok(SignatureSnapshotRecordOutcome::Empty);
+        }
 
         let mut state = self.state.lock().await;
         let resolution = resolve_signature_target_system(&state.projection, context);
@@ -147,46 +831,521 @@ impl AppRuntime {
                 character_system_id,
                 character_id,
             } => {
-                return Ok(SignatureSnapshotRecordOutcome::NeedsConfirmation {
+                return Ok(SignatureSnapshotRecordOutcome::NeedsConfirmation(
+                    PendingSignatureRecord {
+                        focused_system_id,
+                        character_system_id,
+                        character_id,
+                        target_system_id: focused_system_id,
+                        observed_at,
+                        entries,
+                    },
+                ));
+            }
+            SignatureTargetSystemResolution::NeedsDisambiguation {
+                focused_system_id,
+                candidates,
+            } => {
+                return Ok(SignatureSnapshotRecordOutcome::NeedsDisambiguation {
                     focused_system_id,
-                    character_system_id,
-                    character_id,
+                    candidates,
                 });
             }
         };
 
+        if let Some(skipped) = skipped_snapshot_outcome(&state, system_id, &entries, observed_at) {
+            return Ok(skipped);
+        }
         self.store
-            .append_system_signatures_observed_event(&SystemSignaturesObservedEvent {
+            .append_system_signatures_observed_event_at(
+                &SystemSignaturesObservedEvent {
+                    system_id,
+                    snapshot_id: Uuid::now_v7().to_string(),
+                    entries,
+                    observed_at,
+                    attribution_character_id,
+                    source: SignatureEventSource::Manual,
+                },
+                self.clock.now(),
+            )
+            .await
+            .map_err(AppError::store_op("record_signature_snapshot"))?;
+
+        self.catch_up_projection_from_store_locked(&mut state)
+            .await?;
+
+        Ok(SignatureSnapshotRecordOutcome::Recorded { system_id })
+    }
+
+    /// Records that `system_id` was scanned and has no signatures, dropping
+    /// its projected signatures. Empty pastes are rejected by
+    /// [`Self::record_signature_snapshot`], so this is the way to say a
+    /// system was scanned down to nothing.
+    pub async fn clear_system_signatures(
+        &self,
+        system_id: SolarSystemId,
+        attribution_character_id: Option<CharacterId>,
+    ) -> Result<(), AppError> {
+        let mut state = self.state.lock().await;
+        let now = self.clock.now();
+        self.store
+            .append_system_signatures_cleared_event_at(
+                &SystemSignaturesClearedEvent {
+                    system_id,
+                    observed_at: now,
+                    attribution_character_id,
+                    source: SignatureEventSource::Manual,
+                },
+                now,
+            )
+            .await
+            .map_err(AppError::store_op("clear_system_signatures"))?;
+
+        self.catch_up_projection_from_store_locked(&mut state).await
+    }
+
+    /// Records a snapshot held back by `NeedsConfirmation` in its
+    /// `target_system_id`, attributed to the pending character.
+    pub async fn confirm_and_record(
+        &self,
+        pending: PendingSignatureRecord,
+    ) -> Result<SignatureSnapshotRecordOutcome, AppError> {
+        self.record_signature_entries(
+            SignatureSnapshotRecordContext::Explicit {
+                system_id: pending.target_system_id,
+                attribution_character_id: Some(pending.character_id),
+            },
+            pending.entries,
+            pending.observed_at,
+        )
+        .await
+    }
+
+    /// Replaces a system's snapshot history with one baseline event that
+    /// replays to the current projection, returning how many events were
+    /// pruned. Systems without a snapshot are left alone.
+    ///
+    /// Fails with [`StoreError::CompactionConflict`] if another process
+    /// appends to the system while this runs; nothing is pruned then.
+    pub async fn compact_system_signatures(
+        &self,
+        system_id: SolarSystemId,
+    ) -> Result<u64, AppError> {
+        let mut state = self.state.lock().await;
+        self.catch_up_projection_from_store_locked(&mut state)
+            .await?;
+
+        let Some(system) = state.projection.signatures_by_system.get(&system_id) else {
+            return Ok(0);
+        };
+        let Some(last_snapshot) = &system.last_snapshot else {
+            return Ok(0);
+        };
+        let mut baseline: Vec<ProjectedSignature> =
+            system.signatures_by_id.values().cloned().collect();
+        baseline.sort_by(|left, right| left.signature_id.cmp(&right.signature_id));
+        let payload = SystemSignaturesBaselinePayload {
+            system_id,
+            snapshot_id: last_snapshot.snapshot_id.clone(),
+            entries: system.latest_entries.clone(),
+            signatures: baseline,
+        };
+        let observed_at = last_snapshot.observed_at;
+        let Some(through_seq) = state.last_projected_seq else {
+            return Ok(0);
+        };
+
+        let pruned = self
+            .store
+            .compact_signature_history_at(&payload, through_seq, observed_at, self.clock.now())
+            .await
+            .map_err(AppError::store_op("compact_system_signatures"))?;
+        self.catch_up_projection_from_store_locked(&mut state)
+            .await?;
+
+        Ok(pruned)
+    }
+
+    /// Records one manual snapshot per system in a single store transaction,
+    /// then catches the projection up once.
+    ///
+    /// Empty pastes and repeats of a system's latest snapshot are skipped as
+    /// in [`Self::record_signature_snapshot`], so the returned sequences
+    /// cover only the snapshots that were appended.
+    pub async fn record_signature_snapshots(
+        &self,
+        entries: Vec<(SolarSystemId, Vec<SignatureEntry>)>,
+    ) -> Result<Vec<GlobalSeq>, AppError> {
+        let now = self.clock.now();
+        let mut state = self.state.lock().await;
+        let events: Vec<_> = entries
+            .into_iter()
+            .filter(|(system_id, entries)| {
+                skipped_snapshot_outcome(&state, *system_id, entries, now).is_none()
+            })
+            .map(|(system_id, entries)| SystemSignaturesObservedEvent {
                 system_id,
                 snapshot_id: Uuid::now_v7().to_string(),
                 entries,
-                observed_at: jaytripper_core::Timestamp::now(),
-                attribution_character_id,
+                observed_at: now,
+                attribution_character_id: None,
                 source: SignatureEventSource::Manual,
             })
+            .collect();
+        if events.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let seqs = self
+            .store
+            .append_system_signatures_observed_events_at(&events, now)
+            .await
+            .map_err(AppError::store_op("record_signature_snapshots"))?;
+        self.catch_up_projection_from_store_locked(&mut state)
             .await?;
 
+        Ok(seqs)
+    }
+
+    /// Parses a Tripwire-style chain export and records one snapshot event per
+    /// system in a single store transaction, returning the systems that were
+    /// updated.
+    pub async fn import_tripwire(&self, json: &str) -> Result<Vec<SolarSystemId>, AppError> {
+        let now = self.clock.now();
+        let events = crate::import::import_tripwire_at(json, now)?;
+
+        let mut state = self.state.lock().await;
+        self.store
+            .append_system_signatures_observed_events_at(&events, now)
+            .await
+            .map_err(AppError::store_op("import_tripwire"))?;
         self.catch_up_projection_from_store_locked(&mut state)
             .await?;
 
-        Ok(SignatureSnapshotRecordOutcome::Recorded { system_id })
+        Ok(events.iter().map(|event| event.system_id).collect())
     }
 
-    pub(crate) async fn catch_up_projection_from_store(&self) -> Result<(), AppError> {
+    /// Projects events appended to the store since `last_projected_seq`.
+    ///
+    /// For embedders that write to the shared database out-of-band. Only
+    /// events with a higher sequence than the last projected one are read, so
+    /// this cannot pick up rewritten history; reconnect for that. The newest
+    /// ESI movement among the new events is published to
+    /// [`Self::subscribe_movements`].
+    pub async fn catch_up(&self) -> Result<(), AppError> {
         let mut state = self.state.lock().await;
         self.catch_up_projection_from_store_locked(&mut state).await
     }
 
+    /// Runs `append` and the following catch-up under one hold of the
+    /// projection lock; a failed append is reported as store operation `op`.
+    pub(crate) async fn append_and_project(
+        &self,
+        op: &'static str,
+        append: impl Future<Output = Result<GlobalSeq, StoreError>>,
+    ) -> Result<(), AppError> {
+        let mut state = self.state.lock().await;
+        append.await.map_err(AppError::store_op(op))?;
+        self.catch_up_projection_from_store_locked(&mut state).await
+    }
+
+    /// Whether the store holds events the projection has not applied yet,
+    /// checked under the projection lock.
+    #[cfg(test)]
+    pub(crate) async fn projection_lags_store(&self) -> bool {
+        let state = self.state.lock().await;
+        let since_seq = state.last_projected_seq.unwrap_or(GlobalSeq(0));
+        !self
+            .store
+            .read_events_since(since_seq)
+            .await
+            .expect("read events since")
+            .is_empty()
+    }
+
+    pub(crate) async fn alert_if_watched(&self, event: &MovementEvent) {
+        if self
+            .watched_systems
+            .lock()
+            .await
+            .contains(&event.to_system_id)
+        {
+            // No subscribers is fine; alerts are only for live UIs.
+            let _ = self.alerts.send(SystemAlert {
+                character_id: event.character_id,
+                system_id: event.to_system_id,
+                observed_at: event.observed_at,
+            });
+        }
+    }
+
+    /// Projects every stored event past `last_projected_seq`, raising alerts
+    /// for arrivals in watched systems and publishing the newest ESI movement.
+    ///
+    /// Appends happen outside the state lock, so a concurrent writer may land
+    /// events between another task's append and its catch-up. That is safe:
+    /// SQLite serializes writers, so sequence numbers become visible in order,
+    /// and whichever task catches up first projects everything committed so far.
     async fn catch_up_projection_from_store_locked(
         &self,
         state: &mut ProjectionRuntimeState,
     ) -> Result<(), AppError> {
         let since_seq = state.last_projected_seq.unwrap_or(GlobalSeq(0));
-        let records = self.store.read_events_since(since_seq).await?;
-        project_records_with_monotonic_guard(state, &records)
+        let records = self
+            .store
+            .read_events_since(since_seq)
+            .await
+            .map_err(AppError::store_op("catch_up"))?;
+        project_records_with_monotonic_guard(state, &records)?;
+
+        let mut latest_movement = None;
+        for record in &records {
+            if let Some(event) = esi_movement_from_record(record)? {
+                self.alert_if_watched(&event).await;
+                latest_movement = Some(event);
+            }
+        }
+        if let Some(event) = latest_movement {
+            self.publish_movement(event);
+        }
+        Ok(())
     }
 
     pub(crate) fn store(&self) -> &EventLogStore {
         &self.store
     }
 }
+
+fn character_location_views(projection: &AppProjection) -> Vec<CharacterLocationView> {
+    let mut characters: Vec<CharacterLocationView> = projection
+        .characters
+        .iter()
+        .map(|(character_id, status)| CharacterLocationView {
+            character_id: *character_id,
+            current_system_id: status.current_system_id,
+            last_movement_observed_at: status.last_movement_observed_at,
+        })
+        .collect();
+    characters.sort_by_key(|character| character.character_id.0);
+    characters
+}
+
+fn system_signatures_view(
+    system_id: SolarSystemId,
+    system: &SystemSignaturesProjection,
+) -> SystemSignaturesView {
+    let mut signatures: Vec<ProjectedSignature> =
+        system.signatures_by_id.values().cloned().collect();
+    signatures.sort_by(|left, right| left.signature_id.cmp(&right.signature_id));
+
+    SystemSignaturesView {
+        system_id,
+        last_snapshot_id: system
+            .last_snapshot
+            .as_ref()
+            .map(|snapshot| snapshot.snapshot_id.clone()),
+        last_snapshot_at: system
+            .last_snapshot
+            .as_ref()
+            .map(|snapshot| snapshot.observed_at),
+        signatures,
+    }
+}
+
+/// Republishes each reason set on a client's reauth feed, including one
+/// already set at subscription time.
+async fn forward_reauth_needed(
+    character_id: CharacterId,
+    mut reauth_rx: watch::Receiver<Option<String>>,
+    reauth_needed: broadcast::Sender<ReauthNeeded>,
+) {
+    reauth_rx.mark_changed();
+    while reauth_rx.changed().await.is_ok() {
+        let reason = reauth_rx.borrow_and_update().clone();
+        if let Some(reason) = reason {
+            // No subscribers is fine; the notice is only for live UIs.
+            let _ = reauth_needed.send(ReauthNeeded {
+                character_id,
+                reason,
+            });
+        }
+    }
+}
+
+/// Why a snapshot of `entries` for `system_id` should not be appended, if
+/// it is empty or a duplicate; shared by the single and batch record paths.
+fn skipped_snapshot_outcome(
+    state: &ProjectionRuntimeState,
+    system_id: SolarSystemId,
+    entries: &[SignatureEntry],
+    observed_at: Timestamp,
+) -> Option<SignatureSnapshotRecordOutcome> {
+    if entries.is_empty() {
+        Some(SignatureSnapshotRecordOutcome::Empty)
+    } else if is_duplicate_snapshot(state, system_id, entries, observed_at) {
+        Some(SignatureSnapshotRecordOutcome::AlreadyRecorded { system_id })
+    } else {
+        None
+    }
+}
+
+/// Whether `entries` repeat the latest snapshot for `system_id` within
+/// [`DUPLICATE_SNAPSHOT_WINDOW`], e.g. from a double-submitted paste.
+fn is_duplicate_snapshot(
+    state: &ProjectionRuntimeState,
+    system_id: SolarSystemId,
+    entries: &[SignatureEntry],
+    observed_at: Timestamp,
+) -> bool {
+    let Some(system) = state.projection.signatures_by_system.get(&system_id) else {
+        return false;
+    };
+    let Some(last_snapshot) = &system.last_snapshot else {
+        return false;
+    };
+
+    let since_last = observed_at.signed_duration_since(last_snapshot.observed_at);
+    since_last
+        .to_std()
+        .is_ok_and(|since_last| since_last <= DUPLICATE_SNAPSHOT_WINDOW)
+        && system.latest_entries == entries
+}
+
+fn esi_movement_from_record(record: &EventRecord) -> Result<Option<MovementEvent>, AppError> {
+    if record.envelope.source != EventSource::Esi {
+        return Ok(None);
+    }
+
+    Ok(match decode_event_record(record)? {
+        Some(DecodedEvent::CharacterMoved {
+            occurred_at,
+            character_id,
+            payload,
+            ..
+        }) => Some(MovementEvent {
+            character_id,
+            from_system_id: payload.from_system_id,
+            to_system_id: payload.to_system_id,
+            observed_at: occurred_at,
+            source: MovementEventSource::Esi,
+            reason: payload.reason,
+        }),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use jaytripper_core::{
+        MovementEvent, MovementEventSource,
+        ids::{CharacterId, SolarSystemId},
+        time::Timestamp,
+    };
+    use jaytripper_esi::test_util::FixedClock;
+    use jaytripper_store::test_util::temp_store;
+
+    use super::{AppRuntime, SignatureSnapshotRecordContext};
+    use crate::AppError;
+
+    #[tokio::test]
+    async fn movement_feed_is_shared_across_clones() {
+        let (_temp_dir, store) = temp_store().await;
+        let app = AppRuntime::from_store(store).await.expect("app");
+        let clone = app.clone();
+        let mut movements = clone.subscribe_movements();
+
+        app.store()
+            .append_movement_event(&MovementEvent {
+                character_id: CharacterId(7),
+                from_system_id: None,
+                to_system_id: SolarSystemId(30000142),
+                observed_at: Timestamp::from_epoch_secs(1_700_000_000).expect("timestamp"),
+                source: MovementEventSource::Esi,
+                reason: None,
+            })
+            .await
+            .expect("append movement");
+        app.catch_up().await.expect("catch up");
+
+        assert!(movements.has_changed().expect("feed sender alive"));
+        assert_eq!(
+            movements
+                .borrow_and_update()
+                .as_ref()
+                .map(|event| event.character_id),
+            Some(CharacterId(7))
+        );
+    }
+
+    #[tokio::test]
+    async fn store_failures_report_the_failing_operation() {
+        let (_temp_dir, store) = temp_store().await;
+        let app = AppRuntime::from_store(store).await.expect("connect app");
+        app.store().pool().close().await;
+
+        let err = app
+            .initialize_from_event_log()
+            .await
+            .expect_err("closed pool should fail");
+        assert!(matches!(
+            err,
+            AppError::StoreOp {
+                op: "initialize",
+                ..
+            }
+        ));
+        assert!(
+            err.to_string()
+                .starts_with("store error during initialize:")
+        );
+
+        let err = app.catch_up().await.expect_err("closed pool should fail");
+        assert!(matches!(err, AppError::StoreOp { op: "catch_up", .. }));
+
+        let err = app
+            .record_signature_snapshot(
+                SignatureSnapshotRecordContext::Explicit {
+                    system_id: SolarSystemId(30000142),
+                    attribution_character_id: None,
+                },
+                "ABC-123\tCosmic Signature\tGas Site\t\t10.0%",
+            )
+            .await
+            .expect_err("closed pool should fail");
+        assert!(matches!(
+            err,
+            AppError::StoreOp {
+                op: "record_signature_snapshot",
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn recorded_snapshots_are_stamped_by_the_app_clock() {
+        let (_temp_dir, store) = temp_store().await;
+        let app = AppRuntime::from_store(store)
+            .await
+            .expect("connect app")
+            .with_clock(FixedClock(ts(1_700_000_000)));
+
+        app.record_signature_snapshot(
+            SignatureSnapshotRecordContext::Explicit {
+                system_id: SolarSystemId(30000142),
+                attribution_character_id: None,
+            },
+            "ABC-123\tCosmic Signature\tGas Site\t\t10.0%",
+        )
+        .await
+        .expect("record snapshot");
+
+        let records = app.store().read_ordered_events().await.expect("read");
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].envelope.occurred_at, ts(1_700_000_000));
+        assert_eq!(records[0].envelope.recorded_at, ts(1_700_000_000));
+    }
+
+    fn ts(epoch_secs: i64) -> Timestamp {
+        Timestamp::from_epoch_secs(epoch_secs).expect("valid epoch seconds")
+    }
+}
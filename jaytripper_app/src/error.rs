@@ -3,15 +3,27 @@ pub enum AppError {
     #[error("store error: {0}")]
     Store(#[from] jaytripper_store::StoreError),
 
+    #[error("store error during {op}: {source}")]
+    StoreOp {
+        op: &'static str,
+        source: jaytripper_store::StoreError,
+    },
+
     #[error("esi error: {0}")]
     Esi(#[from] jaytripper_esi::EsiError),
 
+    #[error("ingestion task failed: {0}")]
+    IngestionTask(#[from] tokio::task::JoinError),
+
     #[error("payload serialization error: {0}")]
     PayloadSerialization(#[from] serde_json::Error),
 
     #[error("signature parse error: {0}")]
     SignatureParse(#[from] jaytripper_core::SignatureParseError),
 
+    #[error("import error: {0}")]
+    Import(#[from] crate::import::ImportError),
+
     #[error("unsupported schema version {schema_version} for event type '{event_type}'")]
     UnsupportedSchemaVersion {
         event_type: String,
@@ -24,3 +36,10 @@ pub enum AppError {
         global_seq: jaytripper_store::GlobalSeq,
     },
 }
+
+impl AppError {
+    /// Wraps a store failure with the app operation that triggered it.
+    pub(crate) fn store_op(op: &'static str) -> impl FnOnce(jaytripper_store::StoreError) -> Self {
+        move |source| Self::StoreOp { op, source }
+    }
+}
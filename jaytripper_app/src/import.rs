@@ -0,0 +1,183 @@
+//! Adapters for chain-mapping tool exports.
+//!
+//! The Tripwire-style export is a JSON object with a `signatures` array:
+//!
+//! ```json
+//! { "signatures": [
+//!     { "systemID": 31000001, "signatureID": "abc123", "type": "gas", "name": "Bountiful Frontier Reservoir" }
+//! ] }
+//! ```
+//!
+//! Mapping into [`SignatureEntry`]:
+//!
+//! - `systemID` (number or numeric string) selects the event's system.
+//! - `signatureID` accepts `abc123` or `ABC-123` and is normalized to `ABC-123`.
+//! - `type` maps to `site_type` (`wormhole`, `combat`, `data`, `relic`, `gas`,
+//!   `ore`); `unknown` or a missing type leaves it empty.
+//! - `name` is optional; blank names are dropped.
+//! - `group` is always `Cosmic Signature` and `scan_percent` is left empty,
+//!   since these tools do not track scan progress.
+//!
+//! Signatures are grouped into one snapshot event per system, ordered by
+//! system id and observed at import time.
+
+use std::collections::BTreeMap;
+
+use jaytripper_core::{
+    SignatureEntry, SignatureEventSource, SystemSignaturesObservedEvent, Timestamp,
+    ids::SolarSystemId, is_valid_signature_id,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+const IMPORTED_SIGNATURE_GROUP: &str = "Cosmic Signature";
+
+#[derive(Debug, thiserror::Error)]
+pub enum ImportError {
+    #[error("invalid chain export json: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("signature {index}: invalid signature id '{value}'")]
+    InvalidSignatureId { index: usize, value: String },
+
+    #[error("signature {index}: invalid system id '{value}'")]
+    InvalidSystemId { index: usize, value: String },
+}
+
+#[derive(Deserialize)]
+struct TripwireExport {
+    #[serde(default)]
+    signatures: Vec<TripwireSignature>,
+}
+
+#[derive(Deserialize)]
+struct TripwireSignature {
+    #[serde(rename = "systemID")]
+    system_id: TripwireId,
+    #[serde(rename = "signatureID")]
+    signature_id: String,
+    #[serde(rename = "type", default)]
+    site_type: Option<String>,
+    #[serde(default)]
+    name: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TripwireId {
+    Number(i64),
+    Text(String),
+}
+
+pub fn import_tripwire(json: &str) -> Result<Vec<SystemSignaturesObservedEvent>, ImportError> {
+    import_tripwire_at(json, Timestamp::now())
+}
+
+pub(crate) fn import_tripwire_at(
+    json: &str,
+    observed_at: Timestamp,
+) -> Result<Vec<SystemSignaturesObservedEvent>, ImportError> {
+    let export: TripwireExport = serde_json::from_str(json)?;
+
+    let mut entries_by_system: BTreeMap<i32, Vec<SignatureEntry>> = BTreeMap::new();
+    for (index, signature) in export.signatures.into_iter().enumerate() {
+        let system_id = parse_system_id(index, &signature.system_id)?;
+        let signature_id = normalize_signature_id(&signature.signature_id).ok_or_else(|| {
+            ImportError::InvalidSignatureId {
+                index,
+                value: signature.signature_id.clone(),
+            }
+        })?;
+
+        entries_by_system
+            .entry(system_id.0)
+            .or_default()
+            .push(SignatureEntry {
+                signature_id,
+                group: IMPORTED_SIGNATURE_GROUP.to_owned(),
+                site_type: signature.site_type.as_deref().and_then(map_site_type),
+                name: signature
+                    .name
+                    .map(|name| name.trim().to_owned())
+                    .filter(|name| !name.is_empty()),
+                scan_percent: None,
+            });
+    }
+
+    Ok(entries_by_system
+        .into_iter()
+        .map(|(system_id, entries)| SystemSignaturesObservedEvent {
+            system_id: SolarSystemId(system_id),
+            snapshot_id: Uuid::now_v7().to_string(),
+            entries,
+            observed_at,
+            attribution_character_id: None,
+            source: SignatureEventSource::Import,
+        })
+        .collect())
+}
+
+fn parse_system_id(index: usize, raw: &TripwireId) -> Result<SolarSystemId, ImportError> {
+    let invalid = |value: String| ImportError::InvalidSystemId { index, value };
+
+    match raw {
+        TripwireId::Number(value) => i32::try_from(*value)
+            .map(SolarSystemId)
+            .map_err(|_| invalid(value.to_string())),
+        TripwireId::Text(value) => value
+            .trim()
+            .parse::<i32>()
+            .map(SolarSystemId)
+            .map_err(|_| invalid(value.clone())),
+    }
+}
+
+fn normalize_signature_id(raw: &str) -> Option<String> {
+    let compact: String = raw
+        .trim()
+        .chars()
+        .filter(|ch| *ch != '-')
+        .map(|ch| ch.to_ascii_uppercase())
+        .collect();
+    if compact.len() != 6 || !compact.is_ascii() {
+        return None;
+    }
+
+    let normalized = format!("{}-{}", &compact[..3], &compact[3..]);
+    is_valid_signature_id(&normalized).then_some(normalized)
+}
+
+fn map_site_type(raw: &str) -> Option<String> {
+    let site_type = match raw.trim().to_ascii_lowercase().as_str() {
+        "wormhole" => "Wormhole",
+        "combat" => "Combat Site",
+        "data" => "Data Site",
+        "relic" => "Relic Site",
+        "gas" => "Gas Site",
+        "ore" => "Ore Site",
+        _ => return None,
+    };
+    Some(site_type.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{map_site_type, normalize_signature_id};
+
+    #[test]
+    fn normalizes_compact_and_dashed_signature_ids() {
+        assert_eq!(normalize_signature_id("abc123").as_deref(), Some("ABC-123"));
+        assert_eq!(
+            normalize_signature_id("ABC-123").as_deref(),
+            Some("ABC-123")
+        );
+        assert_eq!(normalize_signature_id("ab1234"), None);
+        assert_eq!(normalize_signature_id("abc12"), None);
+    }
+
+    #[test]
+    fn unknown_site_types_are_left_empty() {
+        assert_eq!(map_site_type("Gas").as_deref(), Some("Gas Site"));
+        assert_eq!(map_site_type("unknown"), None);
+    }
+}
@@ -1,12 +1,19 @@
 mod app;
 mod error;
+mod import;
+mod location_names;
 mod projection_runtime;
 mod signature_resolution;
 mod sink;
 mod state;
 
 pub use app::{
-    AppRuntime, CharacterLocationView, SignatureSnapshotRecordContext,
-    SignatureSnapshotRecordOutcome,
+    AppRuntime, CharacterActivity, CharacterLocationView, CharacterMovement, IngestionStatus,
+    KnownEntities, PendingSignatureRecord, ProjectionStats, ReauthNeeded,
+    SignatureSnapshotRecordContext, SignatureSnapshotRecordOutcome, StateExport, SystemAlert,
+    SystemSignaturesView,
 };
 pub use error::AppError;
+pub use import::{ImportError, import_tripwire};
+pub use location_names::LocationNameResolver;
+pub use state::DecodedEvent;
@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+
+use jaytripper_core::ids::{StationId, StructureId};
+use jaytripper_esi::{CharacterLocation, DockLocation, EsiClient};
+use tokio::sync::Mutex;
+
+/// Caches station and structure names looked up through ESI, so rendering the
+/// same dock repeatedly costs one lookup. Failed lookups are not cached: a
+/// structure the character cannot see may become visible after an ACL change.
+#[derive(Debug, Default)]
+pub struct LocationNameResolver {
+    stations: Mutex<HashMap<StationId, String>>,
+    structures: Mutex<HashMap<StructureId, String>>,
+}
+
+impl LocationNameResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn cache_station_name(&self, station_id: StationId, name: impl Into<String>) {
+        self.stations.lock().await.insert(station_id, name.into());
+    }
+
+    pub async fn cache_structure_name(&self, structure_id: StructureId, name: impl Into<String>) {
+        self.structures
+            .lock()
+            .await
+            .insert(structure_id, name.into());
+    }
+
+    /// Names where `location` is docked (see [`CharacterLocation::docked_at`]).
+    /// Falls back to the numeric id when the name cannot be resolved, and
+    /// returns `None` when undocked.
+    pub async fn docked_label<C>(&self, client: &C, location: &CharacterLocation) -> Option<String>
+    where
+        C: EsiClient + Sync,
+    {
+        let label = match location.docked_at()? {
+            DockLocation::Station(station_id) => self
+                .station_name(client, station_id)
+                .await
+                .unwrap_or_else(|| format!("station {station_id}")),
+            DockLocation::Structure(structure_id) => self
+                .structure_name(client, structure_id)
+                .await
+                .unwrap_or_else(|| format!("structure {structure_id}")),
+        };
+        Some(label)
+    }
+
+    async fn station_name<C>(&self, client: &C, station_id: StationId) -> Option<String>
+    where
+        C: EsiClient + Sync,
+    {
+        if let Some(name) = self.stations.lock().await.get(&station_id) {
+            return Some(name.clone());
+        }
+
+        match client.get_station_name(station_id).await {
+            Ok(name) => {
+                self.cache_station_name(station_id, name.clone()).await;
+                Some(name)
+            }
+            Err(err) => {
+                log::debug!(
+                    "could not resolve station {station_id}: {}",
+                    err.redacted_display()
+                );
+                None
+            }
+        }
+    }
+
+    async fn structure_name<C>(&self, client: &C, structure_id: StructureId) -> Option<String>
+    where
+        C: EsiClient + Sync,
+    {
+        if let Some(name) = self.structures.lock().await.get(&structure_id) {
+            return Some(name.clone());
+        }
+
+        match client.get_structure_name(structure_id).await {
+            Ok(name) => {
+                self.cache_structure_name(structure_id, name.clone()).await;
+                Some(name)
+            }
+            Err(err) => {
+                log::debug!(
+                    "could not resolve structure {structure_id}: {}",
+                    err.redacted_display()
+                );
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use async_trait::async_trait;
+    use jaytripper_core::ids::{CharacterId, SolarSystemId, StationId, StructureId};
+    use jaytripper_esi::{CharacterLocation, EsiClient, EsiError, EsiResult};
+
+    use super::LocationNameResolver;
+
+    /// Resolves nothing, counting how often a name was asked for.
+    #[derive(Default)]
+    struct NoNamesClient {
+        lookups: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl EsiClient for NoNamesClient {
+        fn character_id(&self) -> CharacterId {
+            CharacterId(42)
+        }
+
+        fn requires_reauth(&self) -> bool {
+            false
+        }
+
+        fn reauth_reason(&self) -> Option<String> {
+            None
+        }
+
+        async fn get_current_location(&self) -> EsiResult<CharacterLocation> {
+            Err(EsiError::message("location not scripted"))
+        }
+
+        async fn get_station_name(&self, _station_id: StationId) -> EsiResult<String> {
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            Err(EsiError::message("station lookup failed"))
+        }
+
+        async fn get_structure_name(&self, _structure_id: StructureId) -> EsiResult<String> {
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            Err(EsiError::message("forbidden"))
+        }
+    }
+
+    fn docked(station_id: Option<i32>, structure_id: Option<i64>) -> CharacterLocation {
+        CharacterLocation {
+            solar_system_id: SolarSystemId(30000142),
+            station_id: station_id.map(StationId),
+            structure_id: structure_id.map(StructureId),
+        }
+    }
+
+    #[tokio::test]
+    async fn cached_station_name_renders_over_numeric_id() {
+        let client = NoNamesClient::default();
+        let resolver = LocationNameResolver::new();
+        resolver
+            .cache_station_name(
+                StationId(60003760),
+                "Jita IV - Moon 4 - Caldari Navy Assembly Plant",
+            )
+            .await;
+
+        assert_eq!(
+            resolver
+                .docked_label(&client, &docked(Some(60003760), None))
+                .await
+                .as_deref(),
+            Some("Jita IV - Moon 4 - Caldari Navy Assembly Plant")
+        );
+        assert_eq!(client.lookups.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn unresolved_dock_falls_back_to_numeric_id() {
+        let client = NoNamesClient::default();
+        let resolver = LocationNameResolver::new();
+
+        assert_eq!(
+            resolver
+                .docked_label(&client, &docked(None, Some(1_000_000_000_001)))
+                .await
+                .as_deref(),
+            Some("structure 1000000000001")
+        );
+        assert_eq!(
+            resolver
+                .docked_label(&client, &docked(Some(60003760), None))
+                .await
+                .as_deref(),
+            Some("station 60003760")
+        );
+        assert_eq!(
+            resolver.docked_label(&client, &docked(None, None)).await,
+            None
+        );
+    }
+}
@@ -9,24 +9,63 @@ use crate::{
 pub(crate) struct ProjectionRuntimeState {
     pub(crate) projection: AppProjection,
     pub(crate) last_projected_seq: Option<GlobalSeq>,
+    /// Records skipped by the monotonic guard since the last full replay.
+    pub(crate) skipped_stale_records: u64,
+    /// Incremental batches since the last full replay whose first record did
+    /// not directly follow `last_projected_seq`.
+    pub(crate) seq_gaps: u64,
 }
 
+/// Projects records newer than `last_projected_seq`, returning how many stale
+/// records were skipped. Skips are expected to be rare; a steady nonzero count
+/// points at out-of-order delivery.
+///
+/// Also warns when an incremental batch starts past `last_projected_seq + 1`.
+/// Pruning only removes rows older than what is already projected, so such a
+/// gap means rows went missing; the batch is still projected. Gaps within a
+/// batch are not checked, since a full replay of a pruned log has them.
 pub(crate) fn project_records_with_monotonic_guard(
     state: &mut ProjectionRuntimeState,
     records: &[EventRecord],
-) -> Result<(), AppError> {
+) -> Result<usize, AppError> {
+    if let Some(last_seq) = state.last_projected_seq
+        && let Some(first_new) = records.iter().find(|record| record.global_seq > last_seq)
+        && first_new.global_seq.0 > last_seq.0 + 1
+    {
+        log::warn!(
+            "event log gap: projected through seq {} but next record is seq {}",
+            last_seq,
+            first_new.global_seq
+        );
+        state.seq_gaps += 1;
+    }
+
+    let mut skipped = 0;
     for record in records {
-        if let Some(last_seq) = state.last_projected_seq
-            && record.global_seq <= last_seq
-        {
-            continue;
+        if !project_record_with_monotonic_guard(state, record)? {
+            skipped += 1;
         }
+    }
+    Ok(skipped)
+}
 
-        project_event_record(&mut state.projection, record)?;
-        state.last_projected_seq = Some(record.global_seq);
+/// Projects one record if it is newer than `last_projected_seq`, returning
+/// whether it was applied. Used directly when replaying a streamed log, where
+/// no gap check applies.
+pub(crate) fn project_record_with_monotonic_guard(
+    state: &mut ProjectionRuntimeState,
+    record: &EventRecord,
+) -> Result<bool, AppError> {
+    if let Some(last_seq) = state.last_projected_seq
+        && record.global_seq <= last_seq
+    {
+        state.skipped_stale_records += 1;
+        return Ok(false);
     }
 
-    Ok(())
+    project_event_record(&mut state.projection, record)?;
+    state.last_projected_seq = Some(record.global_seq);
+    Ok(true)
 }
 
 #[cfg(test)]
@@ -57,8 +96,11 @@ mod tests {
         );
 
         project_records_with_monotonic_guard(&mut state, &[newer]).expect("apply newer record");
-        project_records_with_monotonic_guard(&mut state, &[stale]).expect("skip stale record");
+        let skipped =
+            project_records_with_monotonic_guard(&mut state, &[stale]).expect("skip stale record");
 
+        assert_eq!(skipped, 1);
+        assert_eq!(state.skipped_stale_records, 1);
         assert_eq!(state.last_projected_seq, Some(GlobalSeq(3)));
         assert_eq!(
             state
@@ -71,6 +113,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn gap_after_last_projected_seq_is_counted_but_still_projected() {
+        let mut state = ProjectionRuntimeState::default();
+        let first = movement_record(1, CharacterId(1), None, SolarSystemId(30000142));
+        let after_gap = movement_record(
+            5,
+            CharacterId(1),
+            Some(SolarSystemId(30000142)),
+            SolarSystemId(30002510),
+        );
+
+        project_records_with_monotonic_guard(&mut state, &[first]).expect("apply first");
+        assert_eq!(state.seq_gaps, 0);
+        project_records_with_monotonic_guard(&mut state, &[after_gap])
+            .expect("apply record after gap");
+
+        assert_eq!(state.seq_gaps, 1);
+        assert_eq!(state.last_projected_seq, Some(GlobalSeq(5)));
+        assert_eq!(
+            state
+                .projection
+                .characters
+                .get(&CharacterId(1))
+                .expect("character projection")
+                .current_system_id,
+            SolarSystemId(30002510)
+        );
+    }
+
     #[test]
     fn monotonically_advances_sequence_for_in_order_batch() {
         let mut state = ProjectionRuntimeState::default();
@@ -82,9 +153,10 @@ mod tests {
             SolarSystemId(30002510),
         );
 
-        project_records_with_monotonic_guard(&mut state, &[first, second])
+        let skipped = project_records_with_monotonic_guard(&mut state, &[first, second])
             .expect("apply in-order batch");
 
+        assert_eq!(skipped, 0);
         assert_eq!(state.last_projected_seq, Some(GlobalSeq(2)));
     }
 
@@ -97,6 +169,7 @@ mod tests {
         let payload = CharacterMovedPayload {
             from_system_id,
             to_system_id,
+            reason: None,
         };
 
         EventRecord {
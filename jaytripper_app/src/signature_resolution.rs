@@ -2,7 +2,7 @@ use jaytripper_core::ids::{CharacterId, SolarSystemId};
 
 use crate::{app::SignatureSnapshotRecordContext, state::AppProjection};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) enum SignatureTargetSystemResolution {
     Record {
         system_id: SolarSystemId,
@@ -13,6 +13,36 @@ pub(crate) enum SignatureTargetSystemResolution {
         character_system_id: SolarSystemId,
         character_id: CharacterId,
     },
+    NeedsDisambiguation {
+        focused_system_id: SolarSystemId,
+        candidates: Vec<CharacterId>,
+    },
+}
+
+/// Which tracked character a signature paste in a system can be attributed to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum AttributionInference {
+    Single(CharacterId),
+    /// Zero or several characters are in the system; candidates are sorted.
+    Ambiguous(Vec<CharacterId>),
+}
+
+pub(crate) fn infer_attribution_character(
+    projection: &AppProjection,
+    system_id: SolarSystemId,
+) -> AttributionInference {
+    let mut candidates: Vec<CharacterId> = projection
+        .characters
+        .iter()
+        .filter(|(_, status)| status.current_system_id == system_id)
+        .map(|(character_id, _)| *character_id)
+        .collect();
+    candidates.sort_by_key(|character_id| character_id.0);
+
+    match candidates.as_slice() {
+        [character_id] => AttributionInference::Single(*character_id),
+        _ => AttributionInference::Ambiguous(candidates),
+    }
 }
 
 pub(crate) fn resolve_signature_target_system(
@@ -54,6 +84,22 @@ pub(crate) fn resolve_signature_target_system(
                 },
             }
         }
+        SignatureSnapshotRecordContext::AutoInferCharacter { focused_system_id } => {
+            match infer_attribution_character(projection, focused_system_id) {
+                AttributionInference::Single(character_id) => {
+                    SignatureTargetSystemResolution::Record {
+                        system_id: focused_system_id,
+                        attribution_character_id: Some(character_id),
+                    }
+                }
+                AttributionInference::Ambiguous(candidates) => {
+                    SignatureTargetSystemResolution::NeedsDisambiguation {
+                        focused_system_id,
+                        candidates,
+                    }
+                }
+            }
+        }
     }
 }
 
@@ -64,7 +110,10 @@ mod tests {
         time::Timestamp,
     };
 
-    use super::{SignatureTargetSystemResolution, resolve_signature_target_system};
+    use super::{
+        AttributionInference, SignatureTargetSystemResolution, infer_attribution_character,
+        resolve_signature_target_system,
+    };
     use crate::{
         app::SignatureSnapshotRecordContext,
         state::{AppProjection, CharacterLocationProjection},
@@ -141,6 +190,90 @@ mod tests {
         );
     }
 
+    #[test]
+    fn auto_infer_context_attributes_to_the_only_character_in_system() {
+        let projection = projection_with(&[(42, 30000142), (43, 30002510)]);
+
+        let result = resolve_signature_target_system(
+            &projection,
+            SignatureSnapshotRecordContext::AutoInferCharacter {
+                focused_system_id: SolarSystemId(30002510),
+            },
+        );
+
+        assert_eq!(
+            result,
+            SignatureTargetSystemResolution::Record {
+                system_id: SolarSystemId(30002510),
+                attribution_character_id: Some(CharacterId(43)),
+            }
+        );
+    }
+
+    #[test]
+    fn auto_infer_context_needs_disambiguation_when_characters_share_system() {
+        let projection = projection_with(&[(44, 30002510), (43, 30002510)]);
+
+        let result = resolve_signature_target_system(
+            &projection,
+            SignatureSnapshotRecordContext::AutoInferCharacter {
+                focused_system_id: SolarSystemId(30002510),
+            },
+        );
+
+        assert_eq!(
+            result,
+            SignatureTargetSystemResolution::NeedsDisambiguation {
+                focused_system_id: SolarSystemId(30002510),
+                candidates: vec![CharacterId(43), CharacterId(44)],
+            }
+        );
+    }
+
+    #[test]
+    fn attribution_inference_is_ambiguous_when_no_character_in_system() {
+        let projection = projection_with(&[(42, 30000142)]);
+
+        assert_eq!(
+            infer_attribution_character(&projection, SolarSystemId(30002510)),
+            AttributionInference::Ambiguous(Vec::new())
+        );
+    }
+
+    #[test]
+    fn attribution_inference_picks_single_character_in_system() {
+        let projection = projection_with(&[(42, 30000142), (43, 30002510)]);
+
+        assert_eq!(
+            infer_attribution_character(&projection, SolarSystemId(30002510)),
+            AttributionInference::Single(CharacterId(43))
+        );
+    }
+
+    #[test]
+    fn attribution_inference_lists_all_characters_sharing_system() {
+        let projection = projection_with(&[(44, 30002510), (42, 30000142), (43, 30002510)]);
+
+        assert_eq!(
+            infer_attribution_character(&projection, SolarSystemId(30002510)),
+            AttributionInference::Ambiguous(vec![CharacterId(43), CharacterId(44)])
+        );
+    }
+
+    fn projection_with(characters: &[(u64, i32)]) -> AppProjection {
+        let mut projection = AppProjection::default();
+        for (character_id, system_id) in characters {
+            projection.characters.insert(
+                CharacterId(*character_id),
+                CharacterLocationProjection {
+                    current_system_id: SolarSystemId(*system_id),
+                    last_movement_observed_at: ts(1_700_000_000),
+                },
+            );
+        }
+        projection
+    }
+
     fn ts(epoch_secs: i64) -> Timestamp {
         Timestamp::from_epoch_secs(epoch_secs).expect("valid epoch seconds")
     }
@@ -1,16 +1,42 @@
+use std::future::Future;
+
 use async_trait::async_trait;
-use jaytripper_core::{MovementEvent, MovementEventSink};
+use jaytripper_core::{MovementEvent, MovementEventSink, StructureEnteredEvent};
+use jaytripper_store::{GlobalSeq, StoreError};
 
 use crate::{AppError, app::AppRuntime};
 
+/// Persists movements and projects them.
+///
+/// By default the append and the catch-up take the projection lock
+/// separately, so a reader can briefly see an event in the store that the
+/// projection has not applied. [`Self::atomic`] closes that window at the cost
+/// of holding the lock across the store write.
 #[derive(Clone)]
 pub(crate) struct AppMovementSink {
     app: AppRuntime,
+    atomic: bool,
 }
 
 impl AppMovementSink {
     pub(crate) fn new(app: AppRuntime) -> Self {
-        Self { app }
+        Self { app, atomic: false }
+    }
+
+    pub(crate) fn atomic(app: AppRuntime) -> Self {
+        Self { app, atomic: true }
+    }
+
+    async fn persist(
+        &self,
+        op: &'static str,
+        append: impl Future<Output = Result<GlobalSeq, StoreError>>,
+    ) -> Result<(), AppError> {
+        if self.atomic {
+            return self.app.append_and_project(op, append).await;
+        }
+        append.await.map_err(AppError::store_op(op))?;
+        self.app.catch_up().await
     }
 }
 
@@ -19,13 +45,62 @@ impl MovementEventSink for AppMovementSink {
     type Error = AppError;
 
     async fn emit_movement(&self, event: MovementEvent) -> Result<(), Self::Error> {
-        self.app.store().append_movement_event(&event).await?;
-        self.app.catch_up_projection_from_store().await
+        self.persist(
+            "emit_movement",
+            self.app
+                .store()
+                .append_movement_event_at(&event, self.app.now()),
+        )
+        .await
+    }
+
+    async fn emit_structure_entered(
+        &self,
+        event: StructureEnteredEvent,
+    ) -> Result<(), Self::Error> {
+        self.persist(
+            "emit_structure_entered",
+            self.app
+                .store()
+                .append_structure_entered_event_at(&event, self.app.now()),
+        )
+        .await
+    }
+}
+
+pub(crate) type MovementObserver = Box<dyn Fn(&MovementEvent) + Send + Sync>;
+
+/// Hands detected movements to an observer without persisting them, still
+/// raising the runtime's watched-system alerts.
+pub(crate) struct ObservingMovementSink {
+    app: AppRuntime,
+    on_movement: MovementObserver,
+}
+
+impl ObservingMovementSink {
+    pub(crate) fn new(app: AppRuntime, on_movement: MovementObserver) -> Self {
+        Self { app, on_movement }
+    }
+}
+
+#[async_trait]
+impl MovementEventSink for ObservingMovementSink {
+    type Error = AppError;
+
+    async fn emit_movement(&self, event: MovementEvent) -> Result<(), Self::Error> {
+        self.app.alert_if_watched(&event).await;
+        (self.on_movement)(&event);
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    };
+
     use jaytripper_core::{
         MovementEvent, MovementEventSink, MovementEventSource,
         ids::{CharacterId, SolarSystemId},
@@ -34,7 +109,7 @@ mod tests {
     use tempfile::tempdir;
 
     use super::AppMovementSink;
-    use crate::app::AppRuntime;
+    use crate::{AppError, app::AppRuntime};
 
     #[tokio::test]
     async fn emit_movement_updates_store_and_projection() {
@@ -49,6 +124,7 @@ mod tests {
             to_system_id: SolarSystemId(30002053),
             observed_at: ts(1_700_000_777),
             source: MovementEventSource::Esi,
+            reason: None,
         })
         .await
         .expect("emit movement");
@@ -67,6 +143,162 @@ mod tests {
         assert_eq!(events.len(), 1);
     }
 
+    #[tokio::test]
+    async fn emit_movement_reaches_movement_feed() {
+        let temp_dir = tempdir().expect("tempdir");
+        let db_path = temp_dir.path().join("events.sqlite");
+        let app = AppRuntime::connect(&db_path).await.expect("connect app");
+        let mut feed = app.subscribe_movements();
+        let sink = AppMovementSink::new(app.clone());
+        let event = MovementEvent {
+            character_id: CharacterId(1337),
+            from_system_id: Some(SolarSystemId(30000142)),
+            to_system_id: SolarSystemId(30002053),
+            observed_at: ts(1_700_000_777),
+            source: MovementEventSource::Esi,
+            reason: None,
+        };
+
+        sink.emit_movement(event.clone())
+            .await
+            .expect("emit movement");
+
+        tokio::time::timeout(std::time::Duration::from_secs(1), feed.changed())
+            .await
+            .expect("feed should update promptly")
+            .expect("feed sender alive");
+        assert_eq!(*feed.borrow_and_update(), Some(event));
+    }
+
+    #[tokio::test]
+    async fn concurrent_emits_for_different_characters_are_all_projected() {
+        let temp_dir = tempdir().expect("tempdir");
+        let db_path = temp_dir.path().join("events.sqlite");
+        let app = AppRuntime::connect(&db_path).await.expect("connect app");
+        let first_sink = AppMovementSink::new(app.clone());
+        let second_sink = AppMovementSink::new(app.clone());
+
+        let (first, second) = tokio::join!(
+            first_sink.emit_movement(MovementEvent {
+                character_id: CharacterId(1),
+                from_system_id: None,
+                to_system_id: SolarSystemId(30000142),
+                observed_at: ts(1_700_000_001),
+                source: MovementEventSource::Esi,
+                reason: None,
+            }),
+            second_sink.emit_movement(MovementEvent {
+                character_id: CharacterId(2),
+                from_system_id: None,
+                to_system_id: SolarSystemId(30002510),
+                observed_at: ts(1_700_000_002),
+                source: MovementEventSource::Esi,
+                reason: None,
+            }),
+        );
+        first.expect("first emit");
+        second.expect("second emit");
+
+        assert_eq!(
+            app.character_current_system(CharacterId(1)).await,
+            Some(SolarSystemId(30000142))
+        );
+        assert_eq!(
+            app.character_current_system(CharacterId(2)).await,
+            Some(SolarSystemId(30002510))
+        );
+
+        let max_seq = app
+            .store()
+            .read_ordered_events()
+            .await
+            .expect("read ordered events")
+            .iter()
+            .map(|record| record.global_seq)
+            .max();
+        assert_eq!(app.last_projected_seq().await, max_seq);
+    }
+
+    #[tokio::test]
+    async fn atomic_sink_never_exposes_unprojected_events() {
+        let temp_dir = tempdir().expect("tempdir");
+        let db_path = temp_dir.path().join("events.sqlite");
+        let app = AppRuntime::connect(&db_path).await.expect("connect app");
+        let sink = AppMovementSink::atomic(app.clone());
+        let done = Arc::new(AtomicBool::new(false));
+
+        let reader = tokio::spawn({
+            let app = app.clone();
+            let done = Arc::clone(&done);
+            async move {
+                let mut checks = 0_u32;
+                while !done.load(Ordering::Acquire) {
+                    assert!(
+                        !app.projection_lags_store().await,
+                        "reader saw an appended but unprojected event"
+                    );
+                    checks += 1;
+                    tokio::task::yield_now().await;
+                }
+                checks
+            }
+        });
+
+        for offset in 0..20 {
+            sink.emit_movement(MovementEvent {
+                character_id: CharacterId(1337),
+                from_system_id: None,
+                to_system_id: SolarSystemId(30000142 + offset),
+                observed_at: ts(1_700_000_000 + i64::from(offset)),
+                source: MovementEventSource::Esi,
+                reason: None,
+            })
+            .await
+            .expect("emit movement");
+        }
+        done.store(true, Ordering::Release);
+
+        let checks = reader.await.expect("reader task");
+        assert!(checks > 0, "reader should have observed the runtime");
+        assert_eq!(
+            app.character_current_system(CharacterId(1337)).await,
+            Some(SolarSystemId(30000161))
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_appends_report_the_sink_operation() {
+        let temp_dir = tempdir().expect("tempdir");
+        let db_path = temp_dir.path().join("events.sqlite");
+        let app = AppRuntime::connect(&db_path).await.expect("connect app");
+        app.store().pool().close().await;
+        let event = MovementEvent {
+            character_id: CharacterId(1337),
+            from_system_id: None,
+            to_system_id: SolarSystemId(30002053),
+            observed_at: ts(1_700_000_777),
+            source: MovementEventSource::Esi,
+            reason: None,
+        };
+
+        for sink in [
+            AppMovementSink::new(app.clone()),
+            AppMovementSink::atomic(app.clone()),
+        ] {
+            let err = sink
+                .emit_movement(event.clone())
+                .await
+                .expect_err("closed pool should fail");
+            assert!(matches!(
+                err,
+                AppError::StoreOp {
+                    op: "emit_movement",
+                    ..
+                }
+            ));
+        }
+    }
+
     fn ts(epoch_secs: i64) -> Timestamp {
         Timestamp::from_epoch_secs(epoch_secs).expect("valid epoch seconds")
     }
@@ -2,8 +2,11 @@ use std::collections::HashMap;
 
 use jaytripper_core::{
     CHARACTER_MOVED_EVENT_TYPE, CHARACTER_MOVED_SCHEMA_VERSION, CharacterMovedPayload,
-    ProjectedSignature, SYSTEM_SIGNATURES_OBSERVED_EVENT_TYPE,
-    SYSTEM_SIGNATURES_OBSERVED_SCHEMA_VERSION, SystemSignaturesObservedPayload, Timestamp,
+    DeferredMissingFlags, ProjectedSignature, SYSTEM_SIGNATURES_BASELINE_EVENT_TYPE,
+    SYSTEM_SIGNATURES_BASELINE_SCHEMA_VERSION, SYSTEM_SIGNATURES_CLEARED_EVENT_TYPE,
+    SYSTEM_SIGNATURES_CLEARED_SCHEMA_VERSION, SYSTEM_SIGNATURES_OBSERVED_EVENT_TYPE,
+    SYSTEM_SIGNATURES_OBSERVED_SCHEMA_VERSION, SignatureEntry, SystemSignaturesBaselinePayload,
+    SystemSignaturesClearedPayload, SystemSignaturesObservedPayload, Timestamp,
     ids::{CharacterId, SolarSystemId},
     merge_signature_snapshot,
 };
@@ -27,12 +30,36 @@ pub(crate) struct SnapshotMetadata {
 pub(crate) struct SystemSignaturesProjection {
     pub(crate) last_snapshot: Option<SnapshotMetadata>,
     pub(crate) signatures_by_id: HashMap<String, ProjectedSignature>,
+    /// Entries exactly as pasted in `last_snapshot`, before merging.
+    pub(crate) latest_entries: Vec<SignatureEntry>,
+    /// Missing flags owed by snapshots merged during a replay.
+    pub(crate) deferred_missing: Option<DeferredMissingFlags>,
 }
 
 #[derive(Clone, Debug, Default, PartialEq)]
 pub(crate) struct AppProjection {
     pub(crate) characters: HashMap<CharacterId, CharacterLocationProjection>,
     pub(crate) signatures_by_system: HashMap<SolarSystemId, SystemSignaturesProjection>,
+    /// While set, snapshot merges skip the per-snapshot missing-flag sweep;
+    /// [`Self::finish_replay`] settles the flags once.
+    replaying: bool,
+}
+
+impl AppProjection {
+    /// Starts a bulk replay, where sweeping every signature per snapshot
+    /// would make long histories quadratic.
+    pub(crate) fn begin_replay(&mut self) {
+        self.replaying = true;
+    }
+
+    pub(crate) fn finish_replay(&mut self) {
+        self.replaying = false;
+        for system_projection in self.signatures_by_system.values_mut() {
+            if let Some(deferred) = system_projection.deferred_missing.take() {
+                deferred.settle(&mut system_projection.signatures_by_id);
+            }
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -75,6 +102,57 @@ impl ProjectionReducer<SystemSignaturesObservedPayload> for AppProjection {
         &mut self,
         event: &SystemSignaturesObservedPayload,
         metadata: &EventMetadata,
+    ) -> Result<(), AppError> {
+        let replaying = self.replaying;
+        let system_projection = self
+            .signatures_by_system
+            .entry(event.system_id)
+            .or_default();
+
+        let latest_snapshot_at = system_projection
+            .last_snapshot
+            .as_ref()
+            .map(|last| last.observed_at);
+        let is_latest_snapshot =
+            latest_snapshot_at.is_none_or(|latest| metadata.occurred_at >= latest);
+        if is_latest_snapshot {
+            system_projection.last_snapshot = Some(SnapshotMetadata {
+                snapshot_id: event.snapshot_id.clone(),
+                observed_at: metadata.occurred_at,
+            });
+            system_projection.latest_entries = event.entries.clone();
+        }
+        if replaying {
+            system_projection
+                .deferred_missing
+                .get_or_insert_default()
+                .merge(
+                    &mut system_projection.signatures_by_id,
+                    &event.entries,
+                    metadata.occurred_at,
+                    latest_snapshot_at,
+                );
+        } else {
+            merge_signature_snapshot(
+                &mut system_projection.signatures_by_id,
+                &event.entries,
+                metadata.occurred_at,
+                latest_snapshot_at,
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl ProjectionReducer<SystemSignaturesBaselinePayload> for AppProjection {
+    /// Replaces the system's signatures with the compacted ones. Compaction
+    /// only writes a baseline once nothing newer is in its stream, so
+    /// whatever was projected before it is what it summarizes.
+    fn project(
+        &mut self,
+        event: &SystemSignaturesBaselinePayload,
+        metadata: &EventMetadata,
     ) -> Result<(), AppError> {
         let system_projection = self
             .signatures_by_system
@@ -85,23 +163,100 @@ impl ProjectionReducer<SystemSignaturesObservedPayload> for AppProjection {
             snapshot_id: event.snapshot_id.clone(),
             observed_at: metadata.occurred_at,
         });
-        merge_signature_snapshot(&mut system_projection.signatures_by_id, &event.entries);
+        system_projection.latest_entries = event.entries.clone();
+        system_projection.deferred_missing = None;
+        system_projection.signatures_by_id = event
+            .signatures
+            .iter()
+            .map(|signature| (signature.signature_id.clone(), signature.clone()))
+            .collect();
 
         Ok(())
     }
 }
 
-pub(crate) fn project_event_record(
-    projection: &mut AppProjection,
-    record: &EventRecord,
-) -> Result<(), AppError> {
+impl ProjectionReducer<SystemSignaturesClearedPayload> for AppProjection {
+    /// Empties the system unless a newer snapshot has already been projected.
+    /// The clear counts as the latest snapshot, so the system reads as scanned
+    /// with nothing in it.
+    fn project(
+        &mut self,
+        event: &SystemSignaturesClearedPayload,
+        metadata: &EventMetadata,
+    ) -> Result<(), AppError> {
+        let system_projection = self
+            .signatures_by_system
+            .entry(event.system_id)
+            .or_default();
+
+        let is_latest_snapshot = system_projection
+            .last_snapshot
+            .as_ref()
+            .is_none_or(|last| metadata.occurred_at >= last.observed_at);
+        if is_latest_snapshot {
+            system_projection.last_snapshot = Some(SnapshotMetadata {
+                snapshot_id: metadata.event_id.clone(),
+                observed_at: metadata.occurred_at,
+            });
+            system_projection.latest_entries.clear();
+            system_projection.signatures_by_id.clear();
+            system_projection.deferred_missing = None;
+        }
+
+        Ok(())
+    }
+}
+
+/// A stored event whose payload has been decoded into its typed form.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DecodedEvent {
+    CharacterMoved {
+        global_seq: GlobalSeq,
+        occurred_at: Timestamp,
+        character_id: CharacterId,
+        payload: CharacterMovedPayload,
+    },
+    SystemSignaturesObserved {
+        global_seq: GlobalSeq,
+        occurred_at: Timestamp,
+        payload: SystemSignaturesObservedPayload,
+    },
+    SystemSignaturesCleared {
+        global_seq: GlobalSeq,
+        occurred_at: Timestamp,
+        payload: SystemSignaturesClearedPayload,
+    },
+    SystemSignaturesBaseline {
+        global_seq: GlobalSeq,
+        occurred_at: Timestamp,
+        payload: SystemSignaturesBaselinePayload,
+    },
+}
+
+impl DecodedEvent {
+    pub fn global_seq(&self) -> GlobalSeq {
+        match self {
+            Self::CharacterMoved { global_seq, .. }
+            | Self::SystemSignaturesObserved { global_seq, .. }
+            | Self::SystemSignaturesCleared { global_seq, .. }
+            | Self::SystemSignaturesBaseline { global_seq, .. } => *global_seq,
+        }
+    }
+
+    pub fn occurred_at(&self) -> Timestamp {
+        match self {
+            Self::CharacterMoved { occurred_at, .. }
+            | Self::SystemSignaturesObserved { occurred_at, .. }
+            | Self::SystemSignaturesCleared { occurred_at, .. }
+            | Self::SystemSignaturesBaseline { occurred_at, .. } => *occurred_at,
+        }
+    }
+}
+
+/// Decodes a stored record, returning `None` for event types this crate does
+/// not understand.
+pub(crate) fn decode_event_record(record: &EventRecord) -> Result<Option<DecodedEvent>, AppError> {
     let envelope = &record.envelope;
-    let metadata = EventMetadata {
-        global_seq: record.global_seq,
-        occurred_at: envelope.occurred_at,
-        event_id: envelope.event_id.clone(),
-        source: envelope.source,
-    };
 
     match envelope.event_type.as_str() {
         CHARACTER_MOVED_EVENT_TYPE => {
@@ -120,13 +275,12 @@ pub(crate) fn project_event_record(
                 }
             })?;
 
-            projection.project(
-                &CharacterMovedProjectionEvent {
-                    character_id,
-                    payload,
-                },
-                &metadata,
-            )?;
+            Ok(Some(DecodedEvent::CharacterMoved {
+                global_seq: record.global_seq,
+                occurred_at: envelope.occurred_at,
+                character_id,
+                payload,
+            }))
         }
         SYSTEM_SIGNATURES_OBSERVED_EVENT_TYPE => {
             if envelope.schema_version != SYSTEM_SIGNATURES_OBSERVED_SCHEMA_VERSION {
@@ -138,10 +292,81 @@ pub(crate) fn project_event_record(
 
             let payload: SystemSignaturesObservedPayload =
                 serde_json::from_str(&envelope.payload_json)?;
-            projection.project(&payload, &metadata)?;
+            Ok(Some(DecodedEvent::SystemSignaturesObserved {
+                global_seq: record.global_seq,
+                occurred_at: envelope.occurred_at,
+                payload,
+            }))
         }
-        _ => {}
+        SYSTEM_SIGNATURES_CLEARED_EVENT_TYPE => {
+            if envelope.schema_version != SYSTEM_SIGNATURES_CLEARED_SCHEMA_VERSION {
+                return Err(AppError::UnsupportedSchemaVersion {
+                    event_type: envelope.event_type.clone(),
+                    schema_version: envelope.schema_version,
+                });
+            }
+
+            let payload: SystemSignaturesClearedPayload =
+                serde_json::from_str(&envelope.payload_json)?;
+            Ok(Some(DecodedEvent::SystemSignaturesCleared {
+                global_seq: record.global_seq,
+                occurred_at: envelope.occurred_at,
+                payload,
+            }))
+        }
+        SYSTEM_SIGNATURES_BASELINE_EVENT_TYPE => {
+            if envelope.schema_version != SYSTEM_SIGNATURES_BASELINE_SCHEMA_VERSION {
+                return Err(AppError::UnsupportedSchemaVersion {
+                    event_type: envelope.event_type.clone(),
+                    schema_version: envelope.schema_version,
+                });
+            }
+
+            let payload: SystemSignaturesBaselinePayload =
+                serde_json::from_str(&envelope.payload_json)?;
+            Ok(Some(DecodedEvent::SystemSignaturesBaseline {
+                global_seq: record.global_seq,
+                occurred_at: envelope.occurred_at,
+                payload,
+            }))
+        }
+        _ => Ok(None),
     }
+}
+
+pub(crate) fn project_event_record(
+    projection: &mut AppProjection,
+    record: &EventRecord,
+) -> Result<(), AppError> {
+    let envelope = &record.envelope;
+    let metadata = EventMetadata {
+        global_seq: record.global_seq,
+        occurred_at: envelope.occurred_at,
+        event_id: envelope.event_id.clone(),
+        source: envelope.source,
+    };
 
-    Ok(())
+    match decode_event_record(record)? {
+        Some(DecodedEvent::CharacterMoved {
+            character_id,
+            payload,
+            ..
+        }) => projection.project(
+            &CharacterMovedProjectionEvent {
+                character_id,
+                payload,
+            },
+            &metadata,
+        ),
+        Some(DecodedEvent::SystemSignaturesObserved { payload, .. }) => {
+            projection.project(&payload, &metadata)
+        }
+        Some(DecodedEvent::SystemSignaturesCleared { payload, .. }) => {
+            projection.project(&payload, &metadata)
+        }
+        Some(DecodedEvent::SystemSignaturesBaseline { payload, .. }) => {
+            projection.project(&payload, &metadata)
+        }
+        None => Ok(()),
+    }
 }
@@ -1,14 +1,31 @@
-use std::{collections::VecDeque, sync::Mutex};
+use std::{
+    collections::VecDeque,
+    fs,
+    path::PathBuf,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
 
 use async_trait::async_trait;
 use jaytripper_app::{
-    AppError, AppRuntime, SignatureSnapshotRecordContext, SignatureSnapshotRecordOutcome,
+    AppError, AppRuntime, CharacterActivity, DecodedEvent, IngestionStatus, KnownEntities,
+    ProjectionStats, ReauthNeeded, SignatureSnapshotRecordContext, SignatureSnapshotRecordOutcome,
+    StateExport, SystemAlert, SystemSignaturesView, import_tripwire,
 };
 use jaytripper_core::{
-    SignatureEntry,
+    SignatureEntry, SignatureEventSource,
     ids::{CharacterId, SolarSystemId},
 };
-use jaytripper_esi::{CharacterLocation, EsiClient, EsiError, LocationPollConfig};
+use jaytripper_esi::{
+    AuthService, AuthSession, CharacterLocation, EsiClient, EsiError, LocationPollConfig,
+    RefreshTokens, TokenStore,
+    test_util::{
+        FixedClock, MemoryTokenStore, StubEsiApiClient, rejected_refresh_error, unavailable_error,
+    },
+};
 use jaytripper_store::{EventEnvelope, EventSource};
 use tokio::sync::watch;
 
@@ -19,6 +36,28 @@ use support::{TestHarness, ts};
 struct MockEsiClient {
     character_id: CharacterId,
     responses: Mutex<VecDeque<Result<CharacterLocation, EsiError>>>,
+    polls: Arc<AtomicUsize>,
+}
+
+impl MockEsiClient {
+    fn new(character_id: CharacterId, systems: &[i32]) -> Self {
+        Self {
+            character_id,
+            responses: Mutex::new(
+                systems
+                    .iter()
+                    .map(|system| {
+                        Ok(CharacterLocation {
+                            solar_system_id: SolarSystemId(*system),
+                            station_id: None,
+                            structure_id: None,
+                        })
+                    })
+                    .collect(),
+            ),
+            polls: Arc::new(AtomicUsize::new(0)),
+        }
+    }
 }
 
 #[async_trait]
@@ -36,17 +75,18 @@ impl EsiClient for MockEsiClient {
     }
 
     async fn get_current_location(&self) -> Result<CharacterLocation, EsiError> {
+        self.polls.fetch_add(1, Ordering::SeqCst);
         self.responses
             .lock()
             .expect("responses lock")
             .pop_front()
-            .unwrap_or_else(|| Err(EsiError::message("no response configured")))
+            .unwrap_or_else(|| Err(unavailable_error()))
     }
 }
 
 #[tokio::test]
 async fn track_latest_system_for_character() {
-    let h = TestHarness::new();
+    let h = TestHarness::new().await;
     h.append_movement(
         CharacterId(42),
         None,
@@ -71,13 +111,10 @@ async fn track_latest_system_for_character() {
 
 #[tokio::test]
 async fn run_ingestion_until_shutdown_returns_when_shutdown_already_signaled() {
-    let h = TestHarness::new();
+    let h = TestHarness::new().await;
     let app = h.app().await;
 
-    let client = MockEsiClient {
-        character_id: CharacterId(4242),
-        responses: Mutex::new(VecDeque::new()),
-    };
+    let client = MockEsiClient::new(CharacterId(4242), &[]);
     let config = LocationPollConfig::default();
     let (_shutdown_tx, shutdown_rx) = watch::channel(true);
 
@@ -88,9 +125,236 @@ async fn run_ingestion_until_shutdown_returns_when_shutdown_already_signaled() {
     assert_eq!(h.ordered_events().await.len(), 0);
 }
 
+#[tokio::test]
+async fn multi_ingestion_polls_each_character_at_its_own_interval() {
+    let h = TestHarness::new().await;
+    let app = h.app().await;
+    let mut movements = app.subscribe_movements();
+
+    let scout = MockEsiClient::new(CharacterId(1), &[30000142, 30000144, 30000145]);
+    let docked_alt = MockEsiClient::new(CharacterId(2), &[30002510, 30002053]);
+    let alt_polls = Arc::clone(&docked_alt.polls);
+    let config_with_interval = |base_interval| LocationPollConfig {
+        base_interval,
+        jitter_factor: 0.0,
+        ..LocationPollConfig::default()
+    };
+
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let app_for_task = app.clone();
+    let run = tokio::spawn(async move {
+        app_for_task
+            .run_multi_ingestion_until_shutdown(
+                vec![
+                    (scout, config_with_interval(Duration::from_millis(5))),
+                    (docked_alt, config_with_interval(Duration::from_secs(3600))),
+                ],
+                shutdown_rx,
+            )
+            .await
+    });
+
+    while app.character_current_system(CharacterId(1)).await != Some(SolarSystemId(30000145)) {
+        movements.changed().await.expect("movement feed open");
+    }
+
+    shutdown_tx.send(true).expect("signal shutdown");
+    run.await
+        .expect("join multi ingestion")
+        .expect("multi ingestion should stop cleanly");
+
+    assert_eq!(
+        app.character_current_system(CharacterId(2)).await,
+        Some(SolarSystemId(30002510))
+    );
+    assert_eq!(alt_polls.load(Ordering::SeqCst), 1);
+}
+
+struct ReauthRequiredClient(CharacterId);
+
+#[async_trait]
+impl EsiClient for ReauthRequiredClient {
+    fn character_id(&self) -> CharacterId {
+        self.0
+    }
+
+    fn requires_reauth(&self) -> bool {
+        true
+    }
+
+    fn reauth_reason(&self) -> Option<String> {
+        Some("refresh token revoked".to_owned())
+    }
+
+    async fn get_current_location(&self) -> Result<CharacterLocation, EsiError> {
+        Err(EsiError::NeedsReauth {
+            reason: "refresh token revoked".to_owned(),
+        })
+    }
+}
+
+#[tokio::test]
+async fn managed_client_reauth_is_broadcast_to_app_subscribers() {
+    let h = TestHarness::new().await;
+    let app = h.app().await;
+    let stub = StubEsiApiClient::new();
+    let store = MemoryTokenStore::default();
+    store
+        .save_session(&AuthSession {
+            character_id: CharacterId(9001),
+            character_name: Some("Pilot".to_owned()),
+            scopes: vec!["esi-location.read_location.v1".to_owned()],
+            access_token: "stale-access".to_owned(),
+            access_expires_at: ts(510),
+            refresh_token: "refresh".to_owned(),
+            updated_at: ts(100),
+        })
+        .expect("save session");
+    // Still inside the refresh skew, so the background refresh loop retries
+    // at once and has its refresh token rejected.
+    stub.push_refresh(Ok(RefreshTokens {
+        access_token: "fresh-access".to_owned(),
+        access_expires_at: ts(520),
+        refresh_token: "fresh-refresh".to_owned(),
+    }));
+    stub.push_refresh(Err(rejected_refresh_error()));
+    let mut reauth_rx = app.subscribe_reauth_needed();
+
+    let client = AuthService::with_clock(
+        stub,
+        store,
+        vec!["esi-location.read_location.v1".to_owned()],
+        FixedClock(ts(500)),
+    )
+    .connect_character(CharacterId(9001))
+    .await
+    .expect("connect against stub");
+    let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+    let runner = tokio::spawn({
+        let app = app.clone();
+        async move {
+            app.run_ingestion_until_shutdown(client, LocationPollConfig::default(), shutdown_rx)
+                .await
+        }
+    });
+
+    let notice = tokio::time::timeout(Duration::from_secs(1), reauth_rx.recv())
+        .await
+        .expect("reauth notice should arrive")
+        .expect("receive reauth notice");
+    runner.abort();
+
+    assert_eq!(
+        notice,
+        ReauthNeeded {
+            character_id: CharacterId(9001),
+            reason: rejected_refresh_error().redacted_display(),
+        }
+    );
+}
+
+#[tokio::test]
+async fn reauth_retry_keeps_ingestion_running_until_shutdown() {
+    let h = TestHarness::new().await;
+    let app = h.app().await.with_reauth_retry(Duration::from_millis(5));
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let runner = tokio::spawn({
+        let app = app.clone();
+        async move {
+            app.run_ingestion_until_shutdown(
+                ReauthRequiredClient(CharacterId(2)),
+                LocationPollConfig::default(),
+                shutdown_rx,
+            )
+            .await
+        }
+    });
+
+    tokio::time::timeout(Duration::from_secs(1), async {
+        while !app
+            .ingestion_status()
+            .get(&CharacterId(2))
+            .is_some_and(|status| status.needs_reauth)
+        {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("reauth should be observed");
+    assert!(
+        !runner.is_finished(),
+        "ingestion should wait for reauth, not exit"
+    );
+
+    shutdown_tx.send(true).expect("signal shutdown");
+    runner
+        .await
+        .expect("join runner")
+        .expect("paused ingestion should shut down cleanly");
+}
+
+#[tokio::test]
+async fn ingestion_status_distinguishes_healthy_and_reauth_characters() {
+    let h = TestHarness::new().await;
+    let app = h.app().await;
+    let config = LocationPollConfig {
+        base_interval: Duration::from_secs(3600),
+        ..LocationPollConfig::default()
+    };
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+
+    let err = app
+        .run_ingestion_until_shutdown(
+            ReauthRequiredClient(CharacterId(2)),
+            config.clone(),
+            shutdown_rx.clone(),
+        )
+        .await
+        .expect_err("reauth should stop ingestion");
+    assert!(matches!(err, AppError::Esi(EsiError::NeedsReauth { .. })));
+
+    let healthy = MockEsiClient::new(CharacterId(1), &[30000142]);
+    let runner = tokio::spawn({
+        let app = app.clone();
+        async move {
+            app.run_ingestion_until_shutdown(healthy, config, shutdown_rx)
+                .await
+        }
+    });
+    tokio::time::timeout(Duration::from_secs(1), async {
+        while app
+            .ingestion_status()
+            .get(&CharacterId(1))
+            .is_none_or(|status| status.metrics.last_success_at.is_none())
+        {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("healthy character should poll");
+    shutdown_tx.send(true).expect("signal shutdown");
+    runner
+        .await
+        .expect("join runner")
+        .expect("healthy ingestion");
+
+    let statuses = app.ingestion_status();
+    let healthy = &statuses[&CharacterId(1)];
+    assert!(!healthy.needs_reauth);
+    assert_eq!(healthy.consecutive_failures, 0);
+    assert!(healthy.metrics.last_poll_latency.is_some());
+    assert_eq!(
+        statuses[&CharacterId(2)],
+        IngestionStatus {
+            needs_reauth: true,
+            ..IngestionStatus::default()
+        }
+    );
+}
+
 #[tokio::test]
 async fn record_signature_snapshot_auto_uses_focused_without_character_location() {
-    let h = TestHarness::new();
+    let h = TestHarness::new().await;
     let app = h.app().await;
 
     let outcome = app
@@ -114,155 +378,881 @@ async fn record_signature_snapshot_auto_uses_focused_without_character_location(
 }
 
 #[tokio::test]
-async fn record_signature_snapshot_auto_requests_confirmation_when_mismatch() {
-    let h = TestHarness::new();
+async fn catch_up_projects_events_appended_by_another_store_handle() {
+    let h = TestHarness::new().await;
+    let app = h.app().await;
+    let mut movements = app.subscribe_movements();
+
     h.append_movement(
-        CharacterId(42),
+        CharacterId(77),
         None,
         SolarSystemId(30000142),
-        ts(1_700_000_000),
+        ts(1_700_000_100),
     )
     .await;
+    assert_eq!(app.character_current_system(CharacterId(77)).await, None);
+
+    app.catch_up().await.expect("catch up");
+
+    assert_eq!(
+        app.character_current_system(CharacterId(77)).await,
+        Some(SolarSystemId(30000142))
+    );
+    assert!(movements.has_changed().expect("feed sender alive"));
+    assert_eq!(
+        movements
+            .borrow_and_update()
+            .as_ref()
+            .map(|event| event.to_system_id),
+        Some(SolarSystemId(30000142))
+    );
+
+    app.catch_up().await.expect("second catch up");
+    assert!(!movements.has_changed().expect("feed sender alive"));
+}
+
+#[tokio::test]
+async fn double_submitted_paste_is_recorded_once() {
+    let h = TestHarness::new().await;
     let app = h.app().await;
+    let system_id = SolarSystemId(31000005);
+    let context = SignatureSnapshotRecordContext::Explicit {
+        system_id,
+        attribution_character_id: None,
+    };
+    let paste = "ABC-123\tCosmic Signature\tGas Site\t\t10.0%\n";
 
-    let outcome = app
-        .record_signature_snapshot(
-            SignatureSnapshotRecordContext::Auto {
-                focused_system_id: SolarSystemId(30002510),
-                attribution_character_id: Some(CharacterId(42)),
-            },
-            "ABC-123\tCosmic Signature\tGas Site\t\t10.0%\n",
-        )
+    let first = app
+        .record_signature_snapshot(context, paste)
         .await
-        .expect("record call should not fail");
+        .expect("record first paste");
+    let second = app
+        .record_signature_snapshot(context, paste)
+        .await
+        .expect("record repeated paste");
 
     assert_eq!(
-        outcome,
-        SignatureSnapshotRecordOutcome::NeedsConfirmation {
-            focused_system_id: SolarSystemId(30002510),
-            character_system_id: SolarSystemId(30000142),
-            character_id: CharacterId(42),
-        }
+        first,
+        SignatureSnapshotRecordOutcome::Recorded { system_id }
+    );
+    assert_eq!(
+        second,
+        SignatureSnapshotRecordOutcome::AlreadyRecorded { system_id }
     );
     assert_eq!(h.ordered_events().await.len(), 1);
+
+    app.record_signature_snapshot(context, "ABC-123\tCosmic Signature\tGas Site\t\t20.0%\n")
+        .await
+        .expect("record changed paste");
+    assert_eq!(h.ordered_events().await.len(), 2);
 }
 
 #[tokio::test]
-async fn record_signature_snapshot_explicit_applies_even_when_character_mismatch() {
-    let h = TestHarness::new();
-    h.append_movement(
-        CharacterId(42),
+async fn empty_paste_records_nothing_until_explicitly_cleared() {
+    let h = TestHarness::new().await;
+    let app = h.app().await;
+    let system_id = SolarSystemId(31000005);
+    let context = SignatureSnapshotRecordContext::Explicit {
+        system_id,
+        attribution_character_id: None,
+    };
+
+    app.record_signature_snapshot(context, "ABC-123\tCosmic Signature\tGas Site\t\t10.0%\n")
+        .await
+        .expect("record snapshot");
+    let outcome = app
+        .record_signature_snapshot(context, "  \n\t\n")
+        .await
+        .expect("record empty paste");
+
+    assert_eq!(outcome, SignatureSnapshotRecordOutcome::Empty);
+    assert_eq!(h.ordered_events().await.len(), 1);
+
+    app.clear_system_signatures(system_id, None)
+        .await
+        .expect("clear system");
+    let view = app
+        .system_signatures(system_id)
+        .await
+        .expect("cleared system is still known");
+    assert!(view.signatures.is_empty());
+    assert_eq!(
+        app.latest_snapshot_entries(system_id).await,
+        Some(Vec::new())
+    );
+}
+
+#[tokio::test]
+async fn compaction_keeps_rebuilt_projection_unchanged() {
+    let h = TestHarness::new().await;
+    let system_id = SolarSystemId(31000005);
+    let entry = |signature_id: &str, scan_percent: f32| SignatureEntry {
+        signature_id: signature_id.to_owned(),
+        group: "Cosmic Signature".to_owned(),
+        site_type: None,
+        name: None,
+        scan_percent: Some(scan_percent),
+    };
+    for (snapshot_id, entries, observed_at) in [
+        (
+            "snap-c1",
+            vec![entry("ABC-123", 80.0), entry("DEF-456", 10.0)],
+            1_700_000_000,
+        ),
+        ("snap-c2", vec![entry("ABC-123", 40.0)], 1_700_000_060),
+        (
+            "snap-c3",
+            vec![entry("ABC-123", 45.0), entry("GHI-789", 5.0)],
+            1_700_000_120,
+        ),
+    ] {
+        h.append_signature_snapshot(system_id, snapshot_id, entries, None, ts(observed_at))
+            .await;
+    }
+    let app = h.app().await;
+    let before = app.system_signatures(system_id).await;
+    let latest_before = app.latest_snapshot_entries(system_id).await;
+
+    let pruned = app
+        .compact_system_signatures(system_id)
+        .await
+        .expect("compact system");
+
+    assert_eq!(pruned, 3);
+    let stream_events = h
+        .ordered_events()
+        .await
+        .into_iter()
+        .filter(|record| record.envelope.stream_key == "system:31000005")
+        .count();
+    assert_eq!(stream_events, 1);
+    assert_eq!(app.system_signatures(system_id).await, before);
+
+    let rebuilt = h.app().await;
+    let after = rebuilt.system_signatures(system_id).await;
+    assert_eq!(after, before);
+    assert_eq!(
+        rebuilt.latest_snapshot_entries(system_id).await,
+        latest_before
+    );
+    let abc = after
+        .expect("system has signatures")
+        .signatures
+        .into_iter()
+        .find(|signature| signature.signature_id == "ABC-123")
+        .expect("ABC-123 kept");
+    assert_eq!(abc.highest_scan_percent_seen, Some(80.0));
+    assert_eq!(abc.latest_scan_percent, Some(45.0));
+
+    h.append_signature_snapshot(
+        system_id,
+        "snap-c4",
+        vec![entry("ABC-123", 50.0)],
+        None,
+        ts(1_700_000_180),
+    )
+    .await;
+    let before = h.app().await.system_signatures(system_id).await;
+    let pruned = rebuilt
+        .compact_system_signatures(system_id)
+        .await
+        .expect("compact again");
+    assert_eq!(pruned, 2, "the earlier baseline and snap-c4 are folded in");
+    assert_eq!(h.app().await.system_signatures(system_id).await, before);
+}
+
+#[tokio::test]
+async fn pruning_to_latest_snapshot_keeps_falling_scans_and_missing_signatures() {
+    let h = TestHarness::new().await;
+    let system_id = SolarSystemId(31000006);
+    let entry = |signature_id: &str, scan_percent: f32| SignatureEntry {
+        signature_id: signature_id.to_owned(),
+        group: "Cosmic Signature".to_owned(),
+        site_type: Some("Gas Site".to_owned()),
+        name: None,
+        scan_percent: Some(scan_percent),
+    };
+    h.append_signature_snapshot(
+        system_id,
+        "snap-p1",
+        vec![entry("ABC-123", 80.0), entry("DEF-456", 10.0)],
         None,
-        SolarSystemId(30000142),
         ts(1_700_000_000),
     )
     .await;
+    h.append_signature_snapshot(
+        system_id,
+        "snap-p2",
+        vec![entry("ABC-123", 40.0)],
+        None,
+        ts(1_700_000_060),
+    )
+    .await;
+    let app = h.app().await;
+    let before = app.system_signatures(system_id).await;
+
+    app.compact_system_signatures(system_id)
+        .await
+        .expect("prune to latest snapshot");
+
+    let rebuilt = h.app().await;
+    let after = rebuilt.system_signatures(system_id).await;
+    assert_eq!(after, before);
+    assert_eq!(
+        rebuilt.latest_snapshot_entries(system_id).await,
+        Some(vec![entry("ABC-123", 40.0)])
+    );
+    let signatures = after.expect("system has signatures").signatures;
+    let abc = signatures
+        .iter()
+        .find(|signature| signature.signature_id == "ABC-123")
+        .expect("ABC-123 kept");
+    assert_eq!(abc.highest_scan_percent_seen, Some(80.0));
+    assert_eq!(abc.latest_scan_percent, Some(40.0));
+    let def = signatures
+        .iter()
+        .find(|signature| signature.signature_id == "DEF-456")
+        .expect("DEF-456 kept after disappearing");
+    assert!(def.missing_from_latest_snapshot);
+}
+
+#[tokio::test]
+async fn record_signature_snapshot_at_keeps_historical_occurred_at() {
+    let h = TestHarness::new().await;
     let app = h.app().await;
+    let system_id = SolarSystemId(31000005);
 
     let outcome = app
-        .record_signature_snapshot(
+        .record_signature_snapshot_at(
             SignatureSnapshotRecordContext::Explicit {
-                system_id: SolarSystemId(30002510),
-                attribution_character_id: Some(CharacterId(42)),
+                system_id,
+                attribution_character_id: None,
             },
             "ABC-123\tCosmic Signature\tGas Site\t\t10.0%\n",
+            ts(1_600_000_000),
         )
         .await
-        .expect("record signatures");
+        .expect("record backfilled snapshot");
 
     assert_eq!(
         outcome,
-        SignatureSnapshotRecordOutcome::Recorded {
-            system_id: SolarSystemId(30002510),
-        }
+        SignatureSnapshotRecordOutcome::Recorded { system_id }
+    );
+    let events = h.ordered_events().await;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].envelope.occurred_at, ts(1_600_000_000));
+    assert!(events[0].envelope.recorded_at > ts(1_600_000_000));
+    assert_eq!(
+        app.system_signatures(system_id)
+            .await
+            .expect("system has signatures")
+            .last_snapshot_at,
+        Some(ts(1_600_000_000))
     );
 }
 
 #[tokio::test]
-async fn record_signature_snapshot_returns_parse_error() {
-    let h = TestHarness::new();
-    let app = h.app().await;
-
-    let err = app
-        .record_signature_snapshot(
-            SignatureSnapshotRecordContext::Auto {
-                focused_system_id: SolarSystemId(30000142),
+async fn replayed_signatures_match_incrementally_projected_signatures() {
+    let h = TestHarness::new().await;
+    let live = h.app().await;
+    let system_id = SolarSystemId(31000005);
+    for (snapshot, observed_at) in [
+        (
+            "ABC-123\tCosmic Signature\tGas Site\t\t10.0%\nDEF-456\tCosmic Signature\t\t\t0.0%\n",
+            1_700_000_100,
+        ),
+        (
+            "GHI-789\tCosmic Signature\tData Site\t\t40.0%\n",
+            1_700_000_300,
+        ),
+        (
+            "ABC-123\tCosmic Signature\tGas Site\t\t80.0%\n",
+            1_700_000_200,
+        ),
+        (
+            "DEF-456\tCosmic Signature\tRelic Site\t\t55.0%\n",
+            1_700_000_400,
+        ),
+    ] {
+        live.record_signature_snapshot_at(
+            SignatureSnapshotRecordContext::Explicit {
+                system_id,
                 attribution_character_id: None,
             },
-            "BAD\tCosmic Signature\tGas Site\t\t10.0%\n",
+            snapshot,
+            ts(observed_at),
         )
         .await
-        .expect_err("expected parse error");
+        .expect("record snapshot");
+    }
 
-    assert!(matches!(
-        err,
-        AppError::SignatureParse(jaytripper_core::SignatureParseError::InvalidSignatureId { .. })
-    ));
+    let replayed = h.app().await;
+
+    let live_view = live
+        .system_signatures(system_id)
+        .await
+        .expect("live system");
+    assert_eq!(
+        replayed.system_signatures(system_id).await,
+        Some(live_view.clone())
+    );
+    assert!(
+        live_view
+            .signatures
+            .iter()
+            .any(|signature| signature.missing_from_latest_snapshot)
+    );
 }
 
 #[tokio::test]
-async fn unknown_event_type_is_skipped_and_projection_still_replays() {
-    let h = TestHarness::new();
-    h.append_movement(
-        CharacterId(9001),
-        None,
-        SolarSystemId(30000142),
-        ts(1_700_000_000),
-    )
-    .await;
-    h.store()
-        .await
-        .append_event(&EventEnvelope {
-            event_id: uuid::Uuid::now_v7().to_string(),
-            event_type: "some_future_event".to_owned(),
-            schema_version: 1,
-            stream_key: "future:stream".to_owned(),
-            occurred_at: ts(1_700_000_010),
-            recorded_at: ts(1_700_000_010),
-            attribution_character_id: None,
-            source: EventSource::Import,
-            payload_json: "{\"hello\":\"world\"}".to_owned(),
+async fn backfilled_paste_does_not_override_newer_missing_flags() {
+    let h = TestHarness::new().await;
+    let app = h.app().await;
+    let system_id = SolarSystemId(31000005);
+    let context = SignatureSnapshotRecordContext::Explicit {
+        system_id,
+        attribution_character_id: None,
+    };
+    for (snapshot, observed_at) in [
+        (
+            "ABC-123\tCosmic Signature\tGas Site\t\t10.0%\nDEF-456\tCosmic Signature\t\t\t0.0%\n",
+            1_700_000_100,
+        ),
+        (
+            "ABC-123\tCosmic Signature\tGas Site\t\t20.0%\n",
+            1_700_000_200,
+        ),
+        (
+            "DEF-456\tCosmic Signature\tRelic Site\t\t30.0%\nGHI-789\tCosmic Signature\t\t\t5.0%\n",
+            1_700_000_150,
+        ),
+    ] {
+        app.record_signature_snapshot_at(context, snapshot, ts(observed_at))
+            .await
+            .expect("record snapshot");
+    }
+
+    let view = app.system_signatures(system_id).await.expect("system view");
+    let missing: Vec<(&str, bool)> = view
+        .signatures
+        .iter()
+        .map(|signature| {
+            (
+                signature.signature_id.as_str(),
+                signature.missing_from_latest_snapshot,
+            )
         })
-        .await
-        .expect("append unknown event");
+        .collect();
+    assert_eq!(
+        missing,
+        vec![("ABC-123", false), ("DEF-456", true), ("GHI-789", true)]
+    );
+    assert_eq!(view.last_snapshot_at, Some(ts(1_700_000_200)));
+    assert_eq!(
+        h.app().await.system_signatures(system_id).await,
+        Some(view),
+        "replay should agree with the live projection"
+    );
+}
 
+#[tokio::test]
+async fn latest_snapshot_entries_returns_only_the_last_paste() {
+    let h = TestHarness::new().await;
     let app = h.app().await;
+    let system_id = SolarSystemId(31000005);
+    let context = SignatureSnapshotRecordContext::Explicit {
+        system_id,
+        attribution_character_id: None,
+    };
+
+    app.record_signature_snapshot(context, "ABC-123\tCosmic Signature\tGas Site\t\t10.0%\n")
+        .await
+        .expect("record first snapshot");
+    app.record_signature_snapshot(context, "XYZ-999\tCosmic Signature\t\t\t0.0%\n")
+        .await
+        .expect("record second snapshot");
+
+    let latest = app
+        .latest_snapshot_entries(system_id)
+        .await
+        .expect("system has a snapshot");
+    let ids: Vec<&str> = latest
+        .iter()
+        .map(|entry| entry.signature_id.as_str())
+        .collect();
+    assert_eq!(ids, vec!["XYZ-999"]);
 
+    let merged = app
+        .system_signatures(system_id)
+        .await
+        .expect("system has signatures");
+    assert_eq!(merged.signatures.len(), 2);
     assert_eq!(
-        app.character_current_system(CharacterId(9001)).await,
-        Some(SolarSystemId(30000142))
+        app.latest_snapshot_entries(SolarSystemId(30000142)).await,
+        None
     );
 }
 
 #[tokio::test]
-async fn restart_preserves_character_positions_from_mixed_stream() {
-    let h = TestHarness::new();
+async fn watched_system_raises_alert_on_matching_move() {
+    let h = TestHarness::new().await;
+    let app = h.app().await;
+    let watched = SolarSystemId(30002813);
+    app.watch_system(watched).await;
+    let mut alerts = app.subscribe_alerts();
+
     h.append_movement(
-        CharacterId(42),
+        CharacterId(5),
         None,
         SolarSystemId(30000142),
         ts(1_700_000_000),
     )
     .await;
+    app.catch_up().await.expect("catch up unwatched move");
+    assert!(alerts.is_empty());
+
     h.append_movement(
-        CharacterId(42),
+        CharacterId(5),
         Some(SolarSystemId(30000142)),
-        SolarSystemId(30002510),
+        watched,
         ts(1_700_000_060),
     )
     .await;
     h.append_movement(
-        CharacterId(100),
-        None,
-        SolarSystemId(30005196),
-        ts(1_700_000_090),
+        CharacterId(5),
+        Some(watched),
+        SolarSystemId(30000144),
+        ts(1_700_000_120),
     )
     .await;
-    h.append_signature_snapshot(
-        SolarSystemId(30000142),
-        "snap-a1",
-        vec![SignatureEntry {
-            signature_id: "ABC-123".to_owned(),
+    h.append_movement(
+        CharacterId(5),
+        Some(SolarSystemId(30000144)),
+        watched,
+        ts(1_700_000_180),
+    )
+    .await;
+    app.catch_up().await.expect("catch up watched moves");
+
+    for observed_at in [1_700_000_060, 1_700_000_180] {
+        assert_eq!(
+            alerts.try_recv().expect("queued alert"),
+            SystemAlert {
+                character_id: CharacterId(5),
+                system_id: watched,
+                observed_at: ts(observed_at),
+            }
+        );
+    }
+    assert!(alerts.is_empty());
+}
+
+#[tokio::test]
+async fn system_signatures_view_round_trips_through_json() {
+    let h = TestHarness::new().await;
+    let app = h.app().await;
+    let system_id = SolarSystemId(31000005);
+
+    app.record_signature_snapshot(
+        SignatureSnapshotRecordContext::Explicit {
+            system_id,
+            attribution_character_id: None,
+        },
+        "XYZ-999\tCosmic Signature\tWormhole\tK162\t100.0%\n\
+         ABC-123\tCosmic Signature\tGas Site\t\t12.5%\n",
+    )
+    .await
+    .expect("record signatures");
+
+    let view = app
+        .system_signatures(system_id)
+        .await
+        .expect("system should have signatures");
+    let ids: Vec<&str> = view
+        .signatures
+        .iter()
+        .map(|signature| signature.signature_id.as_str())
+        .collect();
+    assert_eq!(ids, vec!["ABC-123", "XYZ-999"]);
+    assert!(view.last_snapshot_id.is_some());
+
+    let json = serde_json::to_string(&view).expect("serialize view");
+    let decoded: SystemSignaturesView = serde_json::from_str(&json).expect("deserialize view");
+    assert_eq!(decoded, view);
+    assert_eq!(app.system_signatures(SolarSystemId(30000142)).await, None);
+}
+
+#[tokio::test]
+async fn record_signature_snapshot_auto_requests_confirmation_when_mismatch() {
+    let h = TestHarness::new().await;
+    h.append_movement(
+        CharacterId(42),
+        None,
+        SolarSystemId(30000142),
+        ts(1_700_000_000),
+    )
+    .await;
+    let app = h.app().await;
+
+    let outcome = app
+        .record_signature_snapshot(
+            SignatureSnapshotRecordContext::Auto {
+                focused_system_id: SolarSystemId(30002510),
+                attribution_character_id: Some(CharacterId(42)),
+            },
+            "ABC-123\tCosmic Signature\tGas Site\t\t10.0%\n",
+        )
+        .await
+        .expect("record call should not fail");
+
+    let SignatureSnapshotRecordOutcome::NeedsConfirmation(pending) = outcome else {
+        panic!("expected confirmation request, got {outcome:?}");
+    };
+    assert_eq!(pending.focused_system_id, SolarSystemId(30002510));
+    assert_eq!(pending.character_system_id, SolarSystemId(30000142));
+    assert_eq!(pending.character_id, CharacterId(42));
+    assert_eq!(h.ordered_events().await.len(), 1);
+}
+
+#[tokio::test]
+async fn confirm_and_record_uses_pending_entries_in_chosen_system() {
+    let h = TestHarness::new().await;
+    h.append_movement(
+        CharacterId(42),
+        None,
+        SolarSystemId(30000142),
+        ts(1_700_000_000),
+    )
+    .await;
+    let app = h.app().await;
+
+    let outcome = app
+        .record_signature_snapshot(
+            SignatureSnapshotRecordContext::Auto {
+                focused_system_id: SolarSystemId(30002510),
+                attribution_character_id: Some(CharacterId(42)),
+            },
+            "ABC-123\tCosmic Signature\tGas Site\t\t10.0%\n",
+        )
+        .await
+        .expect("record call should not fail");
+    let SignatureSnapshotRecordOutcome::NeedsConfirmation(mut pending) = outcome else {
+        panic!("expected confirmation request, got {outcome:?}");
+    };
+    assert_eq!(pending.entries().len(), 1);
+    pending.target_system_id = pending.character_system_id;
+
+    let outcome = app
+        .confirm_and_record(pending)
+        .await
+        .expect("confirm pending snapshot");
+
+    assert_eq!(
+        outcome,
+        SignatureSnapshotRecordOutcome::Recorded {
+            system_id: SolarSystemId(30000142),
+        }
+    );
+    let latest = app
+        .latest_snapshot_entries(SolarSystemId(30000142))
+        .await
+        .expect("snapshot recorded in chosen system");
+    assert_eq!(latest[0].signature_id, "ABC-123");
+    let events = h.ordered_events().await;
+    assert_eq!(events.len(), 2);
+    assert_eq!(
+        events[1].envelope.attribution_character_id,
+        Some(CharacterId(42))
+    );
+}
+
+#[tokio::test]
+async fn confirmed_backfill_keeps_its_observed_at() {
+    let h = TestHarness::new().await;
+    h.append_movement(
+        CharacterId(42),
+        None,
+        SolarSystemId(30000142),
+        ts(1_700_000_000),
+    )
+    .await;
+    let app = h.app().await;
+
+    let outcome = app
+        .record_signature_snapshot_at(
+            SignatureSnapshotRecordContext::Auto {
+                focused_system_id: SolarSystemId(30002510),
+                attribution_character_id: Some(CharacterId(42)),
+            },
+            "ABC-123\tCosmic Signature\tGas Site\t\t10.0%\n",
+            ts(1_600_000_000),
+        )
+        .await
+        .expect("record call should not fail");
+    let SignatureSnapshotRecordOutcome::NeedsConfirmation(pending) = outcome else {
+        panic!("expected confirmation request, got {outcome:?}");
+    };
+    assert_eq!(pending.observed_at, ts(1_600_000_000));
+
+    app.confirm_and_record(pending)
+        .await
+        .expect("confirm pending snapshot");
+
+    let events = h.ordered_events().await;
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[1].envelope.occurred_at, ts(1_600_000_000));
+    assert_eq!(
+        app.system_signatures(SolarSystemId(30002510))
+            .await
+            .expect("system has signatures")
+            .last_snapshot_at,
+        Some(ts(1_600_000_000))
+    );
+}
+
+#[tokio::test]
+async fn record_signature_snapshot_infers_attribution_from_single_character_in_system() {
+    let h = TestHarness::new().await;
+    h.append_movement(
+        CharacterId(42),
+        None,
+        SolarSystemId(30000142),
+        ts(1_700_000_000),
+    )
+    .await;
+    h.append_movement(
+        CharacterId(43),
+        None,
+        SolarSystemId(30002510),
+        ts(1_700_000_001),
+    )
+    .await;
+    let app = h.app().await;
+
+    let outcome = app
+        .record_signature_snapshot_with_inferred_attribution(
+            SolarSystemId(30002510),
+            "ABC-123\tCosmic Signature\tGas Site\t\t10.0%\n",
+        )
+        .await
+        .expect("record signatures");
+
+    assert_eq!(
+        outcome,
+        SignatureSnapshotRecordOutcome::Recorded {
+            system_id: SolarSystemId(30002510),
+        }
+    );
+    let events = h.ordered_events().await;
+    assert_eq!(
+        events[2].envelope.attribution_character_id,
+        Some(CharacterId(43))
+    );
+}
+
+#[tokio::test]
+async fn record_signature_snapshot_inferred_attribution_needs_disambiguation() {
+    let h = TestHarness::new().await;
+    for character_id in [42, 43] {
+        h.append_movement(
+            CharacterId(character_id),
+            None,
+            SolarSystemId(30002510),
+            ts(1_700_000_000),
+        )
+        .await;
+    }
+    let app = h.app().await;
+
+    let outcome = app
+        .record_signature_snapshot_with_inferred_attribution(
+            SolarSystemId(30002510),
+            "ABC-123\tCosmic Signature\tGas Site\t\t10.0%\n",
+        )
+        .await
+        .expect("record call should not fail");
+
+    assert_eq!(
+        outcome,
+        SignatureSnapshotRecordOutcome::NeedsDisambiguation {
+            focused_system_id: SolarSystemId(30002510),
+            candidates: vec![CharacterId(42), CharacterId(43)],
+        }
+    );
+    assert_eq!(h.ordered_events().await.len(), 2);
+}
+
+#[tokio::test]
+async fn record_signature_snapshot_explicit_applies_even_when_character_mismatch() {
+    let h = TestHarness::new().await;
+    h.append_movement(
+        CharacterId(42),
+        None,
+        SolarSystemId(30000142),
+        ts(1_700_000_000),
+    )
+    .await;
+    let app = h.app().await;
+
+    let outcome = app
+        .record_signature_snapshot(
+            SignatureSnapshotRecordContext::Explicit {
+                system_id: SolarSystemId(30002510),
+                attribution_character_id: Some(CharacterId(42)),
+            },
+            "ABC-123\tCosmic Signature\tGas Site\t\t10.0%\n",
+        )
+        .await
+        .expect("record signatures");
+
+    assert_eq!(
+        outcome,
+        SignatureSnapshotRecordOutcome::Recorded {
+            system_id: SolarSystemId(30002510),
+        }
+    );
+}
+
+#[tokio::test]
+async fn record_signature_snapshot_lenient_returns_skipped_line_warnings() {
+    let h = TestHarness::new().await;
+    let app = h.app().await;
+
+    let outcome = app
+        .record_signature_snapshot_lenient(
+            SignatureSnapshotRecordContext::Explicit {
+                system_id: SolarSystemId(30000142),
+                attribution_character_id: None,
+            },
+            "ABC-123\tCosmic Signature\tGas Site\t\t10.0%\n\
+             BAD\tCosmic Signature\tGas Site\t\t10.0%\n\
+             DEF-456\tCosmic Signature\t\t\t0.0%\n",
+        )
+        .await
+        .expect("lenient record");
+
+    let SignatureSnapshotRecordOutcome::RecordedWithWarnings {
+        system_id,
+        warnings,
+    } = outcome
+    else {
+        panic!("expected recorded with warnings, got {outcome:?}");
+    };
+    assert_eq!(system_id, SolarSystemId(30000142));
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].0, 2);
+    assert!(warnings[0].1.contains("invalid signature id"));
+    assert_eq!(h.ordered_events().await.len(), 1);
+}
+
+#[tokio::test]
+async fn record_signature_snapshot_lenient_clean_parse_reports_recorded() {
+    let h = TestHarness::new().await;
+    let app = h.app().await;
+
+    let outcome = app
+        .record_signature_snapshot_lenient(
+            SignatureSnapshotRecordContext::Explicit {
+                system_id: SolarSystemId(30000142),
+                attribution_character_id: None,
+            },
+            "ABC-123\tCosmic Signature\tGas Site\t\t10.0%\n",
+        )
+        .await
+        .expect("lenient record");
+
+    assert_eq!(
+        outcome,
+        SignatureSnapshotRecordOutcome::Recorded {
+            system_id: SolarSystemId(30000142),
+        }
+    );
+}
+
+#[tokio::test]
+async fn record_signature_snapshot_returns_parse_error() {
+    let h = TestHarness::new().await;
+    let app = h.app().await;
+
+    let err = app
+        .record_signature_snapshot(
+            SignatureSnapshotRecordContext::Auto {
+                focused_system_id: SolarSystemId(30000142),
+                attribution_character_id: None,
+            },
+            "BAD\tCosmic Signature\tGas Site\t\t10.0%\n",
+        )
+        .await
+        .expect_err("expected parse error");
+
+    assert!(matches!(
+        err,
+        AppError::SignatureParse(jaytripper_core::SignatureParseError::InvalidSignatureId { .. })
+    ));
+}
+
+#[tokio::test]
+async fn unknown_event_type_is_skipped_and_projection_still_replays() {
+    let h = TestHarness::new().await;
+    h.append_movement(
+        CharacterId(9001),
+        None,
+        SolarSystemId(30000142),
+        ts(1_700_000_000),
+    )
+    .await;
+    h.store()
+        .append_event(&EventEnvelope {
+            event_id: uuid::Uuid::now_v7().to_string(),
+            event_type: "some_future_event".to_owned(),
+            schema_version: 1,
+            stream_key: "future:stream".to_owned(),
+            occurred_at: ts(1_700_000_010),
+            recorded_at: ts(1_700_000_010),
+            attribution_character_id: None,
+            source: EventSource::Import,
+            payload_json: "{\"hello\":\"world\"}".to_owned(),
+        })
+        .await
+        .expect("append unknown event");
+
+    let app = h.app().await;
+
+    assert_eq!(
+        app.character_current_system(CharacterId(9001)).await,
+        Some(SolarSystemId(30000142))
+    );
+}
+
+#[tokio::test]
+async fn restart_preserves_character_positions_from_mixed_stream() {
+    let h = TestHarness::new().await;
+    h.append_movement(
+        CharacterId(42),
+        None,
+        SolarSystemId(30000142),
+        ts(1_700_000_000),
+    )
+    .await;
+    h.append_movement(
+        CharacterId(42),
+        Some(SolarSystemId(30000142)),
+        SolarSystemId(30002510),
+        ts(1_700_000_060),
+    )
+    .await;
+    h.append_movement(
+        CharacterId(100),
+        None,
+        SolarSystemId(30005196),
+        ts(1_700_000_090),
+    )
+    .await;
+    h.append_signature_snapshot(
+        SolarSystemId(30000142),
+        "snap-a1",
+        vec![SignatureEntry {
+            signature_id: "ABC-123".to_owned(),
             group: "Cosmic Signature".to_owned(),
             site_type: Some("Gas Site".to_owned()),
             name: None,
@@ -296,3 +1286,583 @@ async fn restart_preserves_character_positions_from_mixed_stream() {
     assert_eq!(before_char_42, after_char_42);
     assert_eq!(before_char_100, after_char_100);
 }
+
+#[tokio::test]
+async fn known_entities_lists_projected_characters_and_systems() {
+    let h = TestHarness::new().await;
+    h.append_movement(
+        CharacterId(100),
+        None,
+        SolarSystemId(30002510),
+        ts(1_700_000_000),
+    )
+    .await;
+    h.append_movement(
+        CharacterId(42),
+        None,
+        SolarSystemId(30000142),
+        ts(1_700_000_010),
+    )
+    .await;
+    h.append_signature_snapshot(
+        SolarSystemId(31000001),
+        "snap-k1",
+        Vec::new(),
+        None,
+        ts(1_700_000_020),
+    )
+    .await;
+
+    let app = h.app().await;
+
+    assert_eq!(
+        app.known_entities().await,
+        KnownEntities {
+            characters: vec![CharacterId(42), CharacterId(100)],
+            systems: vec![
+                SolarSystemId(30000142),
+                SolarSystemId(30002510),
+                SolarSystemId(31000001),
+            ],
+        }
+    );
+}
+
+#[tokio::test]
+async fn character_locations_and_signatures_are_sorted_by_id() {
+    let h = TestHarness::new().await;
+    for (character_id, system_id) in [
+        (300, 30000142),
+        (7, 30002510),
+        (42, 30002053),
+        (1_000, 30000142),
+    ] {
+        h.append_movement(
+            CharacterId(character_id),
+            None,
+            SolarSystemId(system_id),
+            ts(1_700_000_000),
+        )
+        .await;
+    }
+    h.append_signature_snapshot(
+        SolarSystemId(30000142),
+        "snap-sorted",
+        vec![
+            imported_entry("XYZ-789", None, None),
+            imported_entry("ABC-123", None, None),
+            imported_entry("MNO-456", None, None),
+        ],
+        None,
+        ts(1_700_000_010),
+    )
+    .await;
+
+    let app = h.app().await;
+
+    let characters: Vec<CharacterId> = app
+        .character_locations()
+        .await
+        .into_iter()
+        .map(|view| view.character_id)
+        .collect();
+    assert_eq!(
+        characters,
+        vec![
+            CharacterId(7),
+            CharacterId(42),
+            CharacterId(300),
+            CharacterId(1_000),
+        ]
+    );
+
+    let signature_ids: Vec<String> = app
+        .system_signatures(SolarSystemId(30000142))
+        .await
+        .expect("signatures for system")
+        .signatures
+        .into_iter()
+        .map(|signature| signature.signature_id)
+        .collect();
+    assert_eq!(signature_ids, vec!["ABC-123", "MNO-456", "XYZ-789"]);
+}
+
+#[tokio::test]
+async fn exported_state_json_round_trips_the_projection() {
+    let h = TestHarness::new().await;
+    h.append_movement(
+        CharacterId(100),
+        None,
+        SolarSystemId(30002510),
+        ts(1_700_000_000),
+    )
+    .await;
+    h.append_movement(
+        CharacterId(42),
+        None,
+        SolarSystemId(31000005),
+        ts(1_700_000_010),
+    )
+    .await;
+    let app = h.app().await;
+    app.record_signature_snapshot(
+        SignatureSnapshotRecordContext::Explicit {
+            system_id: SolarSystemId(31000005),
+            attribution_character_id: Some(CharacterId(42)),
+        },
+        "ABC-123\tCosmic Signature\tWormhole\tUnstable Wormhole K162\t100.0%\n",
+    )
+    .await
+    .expect("record snapshot");
+
+    let json = app
+        .export_state_json(Some(SolarSystemId(31000005)))
+        .await
+        .expect("export state");
+    let exported: StateExport = serde_json::from_str(&json).expect("parse exported state");
+
+    assert_eq!(
+        exported,
+        app.export_state(Some(SolarSystemId(31000005))).await
+    );
+    assert_eq!(
+        exported.last_applied_seq,
+        app.last_projected_seq().await.map(|seq| seq.0)
+    );
+    assert_eq!(exported.focused_system_id, Some(SolarSystemId(31000005)));
+    assert_eq!(
+        exported
+            .characters
+            .iter()
+            .map(|character| (character.character_id, character.current_system_id))
+            .collect::<Vec<_>>(),
+        vec![
+            (CharacterId(42), SolarSystemId(31000005)),
+            (CharacterId(100), SolarSystemId(30002510)),
+        ]
+    );
+    assert_eq!(
+        exported.systems,
+        vec![
+            app.system_signatures(SolarSystemId(31000005))
+                .await
+                .expect("system exported")
+        ]
+    );
+}
+
+#[tokio::test]
+async fn projection_stats_counts_characters_systems_and_signatures() {
+    let h = TestHarness::new().await;
+    h.append_movement(
+        CharacterId(100),
+        None,
+        SolarSystemId(30002510),
+        ts(1_700_000_000),
+    )
+    .await;
+    h.append_movement(
+        CharacterId(42),
+        None,
+        SolarSystemId(30000142),
+        ts(1_700_000_010),
+    )
+    .await;
+    let app = h.app().await;
+    assert_eq!(
+        app.projection_stats().await,
+        ProjectionStats {
+            character_count: 2,
+            system_count: 0,
+            total_signatures: 0,
+        }
+    );
+
+    for (system_id, paste) in [
+        (
+            SolarSystemId(31000005),
+            "ABC-123\tCosmic Signature\tGas Site\t\t10.0%\nDEF-456\tCosmic Signature\t\t\t0.0%\n",
+        ),
+        (
+            SolarSystemId(31000006),
+            "XYZ-999\tCosmic Signature\t\t\t0.0%\n",
+        ),
+    ] {
+        app.record_signature_snapshot(
+            SignatureSnapshotRecordContext::Explicit {
+                system_id,
+                attribution_character_id: None,
+            },
+            paste,
+        )
+        .await
+        .expect("record snapshot");
+    }
+
+    assert_eq!(
+        app.projection_stats().await,
+        ProjectionStats {
+            character_count: 2,
+            system_count: 2,
+            total_signatures: 3,
+        }
+    );
+}
+
+fn read_tripwire_fixture(name: &str) -> String {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("tripwire")
+        .join(name);
+    fs::read_to_string(path).expect("fixture should be readable")
+}
+
+fn imported_entry(
+    signature_id: &str,
+    site_type: Option<&str>,
+    name: Option<&str>,
+) -> SignatureEntry {
+    SignatureEntry {
+        signature_id: signature_id.to_owned(),
+        group: "Cosmic Signature".to_owned(),
+        site_type: site_type.map(ToOwned::to_owned),
+        name: name.map(ToOwned::to_owned),
+        scan_percent: None,
+    }
+}
+
+#[test]
+fn chain_export_fixture_maps_to_one_event_per_system() {
+    let events =
+        import_tripwire(&read_tripwire_fixture("chain_export.json")).expect("import fixture");
+
+    assert_eq!(events.len(), 2);
+
+    assert_eq!(events[0].system_id, SolarSystemId(31000001));
+    assert_eq!(
+        events[0].entries,
+        vec![
+            imported_entry("DVA-081", Some("Data Site"), None),
+            imported_entry("GJP-344", None, None),
+        ]
+    );
+
+    assert_eq!(events[1].system_id, SolarSystemId(31000005));
+    assert_eq!(
+        events[1].entries,
+        vec![
+            imported_entry(
+                "CWT-368",
+                Some("Gas Site"),
+                Some("Bountiful Frontier Reservoir")
+            ),
+            imported_entry("ZZO-660", None, None),
+        ]
+    );
+
+    assert!(events.iter().all(|event| {
+        event.source == SignatureEventSource::Import && event.attribution_character_id.is_none()
+    }));
+}
+
+#[test]
+fn rejects_unparseable_signature_id() {
+    let err = import_tripwire(r#"{"signatures":[{"systemID":31000001,"signatureID":"nope"}]}"#)
+        .expect_err("invalid signature id should fail");
+
+    assert!(err.to_string().contains("signature 0"));
+}
+
+#[tokio::test]
+async fn app_import_records_events_with_import_source() {
+    let h = TestHarness::new().await;
+    let app = h.app().await;
+
+    let systems = app
+        .import_tripwire(&read_tripwire_fixture("chain_export.json"))
+        .await
+        .expect("import into app");
+
+    assert_eq!(
+        systems,
+        vec![SolarSystemId(31000001), SolarSystemId(31000005)]
+    );
+    let events = h.ordered_events().await;
+    assert_eq!(events.len(), 2);
+    assert!(
+        events
+            .iter()
+            .all(|record| record.envelope.source == EventSource::Import)
+    );
+    assert_eq!(app.known_entities().await.systems, systems);
+}
+
+#[tokio::test]
+async fn movement_histogram_counts_moves_per_minute_bucket() {
+    let h = TestHarness::new().await;
+    let moves = [
+        (None, 30000142, 1_700_000_040),
+        (Some(30000142), 30002510, 1_700_000_055),
+        (Some(30002510), 30002645, 1_700_000_110),
+        (Some(30002645), 30000142, 1_700_000_230),
+    ];
+    for (from, to, observed_at) in moves {
+        h.append_movement(
+            CharacterId(42),
+            from.map(SolarSystemId),
+            SolarSystemId(to),
+            ts(observed_at),
+        )
+        .await;
+    }
+    h.append_movement(
+        CharacterId(43),
+        None,
+        SolarSystemId(30000142),
+        ts(1_700_000_041),
+    )
+    .await;
+    let app = h.app().await;
+
+    let histogram = app
+        .movement_histogram(CharacterId(42), Duration::from_secs(60))
+        .await
+        .expect("movement histogram");
+
+    assert_eq!(
+        histogram,
+        vec![
+            (ts(1_700_000_040), 2),
+            (ts(1_700_000_040 + 60), 1),
+            (ts(1_700_000_040 + 180), 1),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn events_affecting_system_merges_snapshots_and_arrivals_in_seq_order() {
+    let h = TestHarness::new().await;
+    h.append_movement(
+        CharacterId(42),
+        None,
+        SolarSystemId(30000142),
+        ts(1_700_000_000),
+    )
+    .await;
+    h.append_signature_snapshot(
+        SolarSystemId(30000142),
+        "snap-j1",
+        Vec::new(),
+        Some(CharacterId(42)),
+        ts(1_700_000_010),
+    )
+    .await;
+    h.append_movement(
+        CharacterId(42),
+        Some(SolarSystemId(30000142)),
+        SolarSystemId(30002510),
+        ts(1_700_000_020),
+    )
+    .await;
+    h.append_signature_snapshot(
+        SolarSystemId(30002510),
+        "snap-other",
+        Vec::new(),
+        Some(CharacterId(42)),
+        ts(1_700_000_025),
+    )
+    .await;
+    h.append_movement(
+        CharacterId(43),
+        Some(SolarSystemId(30002510)),
+        SolarSystemId(30000142),
+        ts(1_700_000_030),
+    )
+    .await;
+    let app = h.app().await;
+
+    let events = app
+        .events_affecting_system(SolarSystemId(30000142))
+        .await
+        .expect("events affecting system");
+
+    assert_eq!(events.len(), 3);
+    assert!(
+        events
+            .windows(2)
+            .all(|pair| pair[0].global_seq() < pair[1].global_seq())
+    );
+    assert!(matches!(
+        &events[0],
+        DecodedEvent::CharacterMoved { character_id: CharacterId(42), payload, .. }
+            if payload.to_system_id == SolarSystemId(30000142)
+    ));
+    assert!(matches!(
+        &events[1],
+        DecodedEvent::SystemSignaturesObserved { payload, .. } if payload.snapshot_id == "snap-j1"
+    ));
+    assert!(matches!(
+        &events[2],
+        DecodedEvent::CharacterMoved { character_id: CharacterId(43), payload, .. }
+            if payload.from_system_id == Some(SolarSystemId(30002510))
+    ));
+}
+
+#[tokio::test]
+async fn record_signature_snapshots_projects_every_system_in_one_batch() {
+    let h = TestHarness::new().await;
+    let app = h.app().await;
+    let systems = [
+        SolarSystemId(31000001),
+        SolarSystemId(31000005),
+        SolarSystemId(30000142),
+    ];
+
+    let seqs = app
+        .record_signature_snapshots(
+            systems
+                .iter()
+                .map(|system_id| {
+                    (
+                        *system_id,
+                        vec![imported_entry("ABC-123", Some("Gas Site"), None)],
+                    )
+                })
+                .collect(),
+        )
+        .await
+        .expect("record snapshots");
+
+    assert_eq!(seqs.len(), 3);
+    assert!(seqs.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(app.last_projected_seq().await, seqs.last().copied());
+    assert_eq!(
+        app.known_entities().await,
+        KnownEntities {
+            characters: Vec::new(),
+            systems: vec![
+                SolarSystemId(30000142),
+                SolarSystemId(31000001),
+                SolarSystemId(31000005),
+            ],
+        }
+    );
+
+    let stored = h.ordered_events().await;
+    assert_eq!(stored.len(), 3);
+}
+
+#[tokio::test]
+async fn record_signature_snapshots_skips_empty_and_repeated_snapshots() {
+    let h = TestHarness::new().await;
+    let app = h.app().await;
+    let repeated = vec![imported_entry("ABC-123", Some("Gas Site"), None)];
+    app.record_signature_snapshots(vec![(SolarSystemId(31000001), repeated.clone())])
+        .await
+        .expect("record first snapshot");
+
+    let seqs = app
+        .record_signature_snapshots(vec![
+            (SolarSystemId(31000001), repeated),
+            (SolarSystemId(31000005), Vec::new()),
+            (
+                SolarSystemId(30000142),
+                vec![imported_entry("XYZ-789", Some("Data Site"), None)],
+            ),
+        ])
+        .await
+        .expect("record snapshots");
+
+    assert_eq!(seqs.len(), 1);
+    assert_eq!(h.ordered_events().await.len(), 2);
+    assert_eq!(app.system_signatures(SolarSystemId(31000005)).await, None);
+}
+
+#[tokio::test]
+async fn observe_ingestion_reports_movements_without_persisting() {
+    let h = TestHarness::new().await;
+    let app = h.app().await;
+    app.watch_system(SolarSystemId(30002510)).await;
+    let mut alerts = app.subscribe_alerts();
+    let client = MockEsiClient::new(CharacterId(42), &[30000142, 30002510]);
+    let observed = Arc::new(Mutex::new(Vec::new()));
+    let observed_for_hook = Arc::clone(&observed);
+
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let app_for_task = app.clone();
+    let run = tokio::spawn(async move {
+        app_for_task
+            .observe_ingestion_until_shutdown(
+                client,
+                LocationPollConfig {
+                    base_interval: Duration::from_millis(5),
+                    jitter_factor: 0.0,
+                    ..LocationPollConfig::default()
+                },
+                shutdown_rx,
+                move |event| {
+                    observed_for_hook
+                        .lock()
+                        .expect("observed lock")
+                        .push(event.to_system_id);
+                },
+            )
+            .await
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    shutdown_tx.send(true).expect("signal shutdown");
+    run.await
+        .expect("join observation")
+        .expect("observation run");
+
+    assert_eq!(
+        *observed.lock().expect("observed lock"),
+        vec![SolarSystemId(30000142), SolarSystemId(30002510)]
+    );
+    assert!(h.ordered_events().await.is_empty());
+    assert_eq!(app.character_current_system(CharacterId(42)).await, None);
+    let alert = alerts.try_recv().expect("observed arrival raises an alert");
+    assert_eq!(
+        (alert.character_id, alert.system_id),
+        (CharacterId(42), SolarSystemId(30002510))
+    );
+}
+
+#[tokio::test]
+async fn character_activity_summarizes_movement_stream() {
+    let h = TestHarness::new().await;
+    let moves = [
+        (None, 30000142, 1_700_000_000),
+        (Some(30000142), 30002510, 1_700_003_600),
+        (Some(30002510), 30002053, 1_700_086_400),
+    ];
+    for (from, to, observed_at) in moves {
+        h.append_movement(
+            CharacterId(42),
+            from.map(SolarSystemId),
+            SolarSystemId(to),
+            ts(observed_at),
+        )
+        .await;
+    }
+    let app = h.app().await;
+
+    assert_eq!(
+        app.character_activity(CharacterId(42))
+            .await
+            .expect("character activity"),
+        Some(CharacterActivity {
+            first_movement_at: ts(1_700_000_000),
+            last_movement_at: ts(1_700_086_400),
+            total_movements: 3,
+        })
+    );
+    assert_eq!(
+        app.character_activity(CharacterId(43))
+            .await
+            .expect("character activity"),
+        None
+    );
+}
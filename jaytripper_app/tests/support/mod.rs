@@ -7,21 +7,26 @@ use jaytripper_core::{
     ids::{CharacterId, SolarSystemId},
     time::Timestamp,
 };
-use jaytripper_store::{EventLogStore, EventRecord};
+use jaytripper_store::{
+    EventLogStore, EventRecord,
+    test_util::{temp_store, temp_store_path},
+};
 use tempfile::TempDir;
 
 pub struct TestHarness {
     _temp_dir: TempDir,
     db_path: PathBuf,
+    store: EventLogStore,
 }
 
 impl TestHarness {
-    pub fn new() -> Self {
-        let temp_dir = tempfile::tempdir().expect("tempdir");
-        let db_path = temp_dir.path().join("events.sqlite");
+    pub async fn new() -> Self {
+        let (temp_dir, store) = temp_store().await;
+        let db_path = temp_store_path(&temp_dir);
         Self {
             _temp_dir: temp_dir,
             db_path,
+            store,
         }
     }
 
@@ -35,10 +40,8 @@ impl TestHarness {
             .expect("connect app")
     }
 
-    pub async fn store(&self) -> EventLogStore {
-        EventLogStore::connect(self.db_path())
-            .await
-            .expect("connect store")
+    pub fn store(&self) -> &EventLogStore {
+        &self.store
     }
 
     pub async fn append_movement(
@@ -48,14 +51,14 @@ impl TestHarness {
         to_system_id: SolarSystemId,
         observed_at: Timestamp,
     ) {
-        self.store()
-            .await
+        self.store
             .append_movement_event(&MovementEvent {
                 character_id,
                 from_system_id,
                 to_system_id,
                 observed_at,
                 source: MovementEventSource::Esi,
+                reason: None,
             })
             .await
             .expect("append movement");
@@ -69,8 +72,7 @@ impl TestHarness {
         attribution_character_id: Option<CharacterId>,
         observed_at: Timestamp,
     ) {
-        self.store()
-            .await
+        self.store
             .append_system_signatures_observed_event(&SystemSignaturesObservedEvent {
                 system_id,
                 snapshot_id: snapshot_id.to_owned(),
@@ -84,8 +86,7 @@ impl TestHarness {
     }
 
     pub async fn ordered_events(&self) -> Vec<EventRecord> {
-        self.store()
-            .await
+        self.store
             .read_ordered_events()
             .await
             .expect("read ordered events")
@@ -2,7 +2,9 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    ids::{CharacterId, SolarSystemId},
+    gate_graph::GateGraph,
+    ids::{CharacterId, SolarSystemId, StructureId},
+    signatures::{ProjectedSignature, canonical_group},
     time::Timestamp,
 };
 
@@ -10,6 +12,12 @@ pub const CHARACTER_MOVED_EVENT_TYPE: &str = "character_moved";
 pub const CHARACTER_MOVED_SCHEMA_VERSION: i64 = 1;
 pub const SYSTEM_SIGNATURES_OBSERVED_EVENT_TYPE: &str = "system_signatures_observed";
 pub const SYSTEM_SIGNATURES_OBSERVED_SCHEMA_VERSION: i64 = 1;
+pub const SYSTEM_SIGNATURES_CLEARED_EVENT_TYPE: &str = "system_signatures_cleared";
+pub const SYSTEM_SIGNATURES_CLEARED_SCHEMA_VERSION: i64 = 1;
+pub const SYSTEM_SIGNATURES_BASELINE_EVENT_TYPE: &str = "system_signatures_baseline";
+pub const SYSTEM_SIGNATURES_BASELINE_SCHEMA_VERSION: i64 = 1;
+pub const STRUCTURE_ENTERED_EVENT_TYPE: &str = "structure_entered";
+pub const STRUCTURE_ENTERED_SCHEMA_VERSION: i64 = 1;
 
 pub fn character_stream_key(character_id: CharacterId) -> String {
     format!("character:{}", character_id.0)
@@ -23,17 +31,71 @@ pub fn system_stream_key(system_id: SolarSystemId) -> String {
 pub struct CharacterMovedPayload {
     pub from_system_id: Option<SolarSystemId>,
     pub to_system_id: SolarSystemId,
+    /// Added after the first schema version; absent in older payloads.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<MovementReason>,
 }
 
+/// Entry into a structure without leaving the system, e.g. docking in a
+/// citadel or moving between two citadels on grid.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StructureEnteredPayload {
+    pub system_id: SolarSystemId,
+    pub from_structure_id: Option<StructureId>,
+    pub structure_id: StructureId,
+}
+
+/// Inferred cause of a movement.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MovementReason {
+    /// First location seen for the character, e.g. logging in.
+    Login,
+    /// Travel between stargate-adjacent systems.
+    Gate,
+    /// Travel between systems without a stargate connection: a wormhole, or
+    /// a bridge or jump drive, which are indistinguishable from location data.
+    Wormhole,
+}
+
+impl MovementReason {
+    /// Classifies a movement. Telling gate from wormhole travel needs a gate
+    /// graph, so without one only first sightings are classified.
+    pub fn classify(
+        from_system_id: Option<SolarSystemId>,
+        to_system_id: SolarSystemId,
+        gate_graph: Option<&GateGraph>,
+    ) -> Option<Self> {
+        let Some(from_system_id) = from_system_id else {
+            return Some(Self::Login);
+        };
+        let gate_graph = gate_graph?;
+        if gate_graph.are_adjacent(from_system_id, to_system_id) {
+            Some(Self::Gate)
+        } else {
+            Some(Self::Wormhole)
+        }
+    }
+}
+
+/// Stored inside `system_signatures_observed` payloads, so changes must be
+/// additive: new fields are optional and `#[serde(default)]` so previously
+/// stored payloads still decode. Anything else requires bumping
+/// [`SYSTEM_SIGNATURES_OBSERVED_SCHEMA_VERSION`] and handling the old version
+/// when projecting.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct SignatureEntry {
     pub signature_id: String,
     pub group: String,
+    #[serde(default)]
     pub site_type: Option<String>,
+    #[serde(default)]
     pub name: Option<String>,
+    #[serde(default)]
     pub scan_percent: Option<f32>,
 }
 
+/// Follows the same additive-only policy as [`SignatureEntry`].
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct SystemSignaturesObservedPayload {
     pub system_id: SolarSystemId,
@@ -41,6 +103,26 @@ pub struct SystemSignaturesObservedPayload {
     pub entries: Vec<SignatureEntry>,
 }
 
+/// Written by compaction in place of a system's pruned snapshot history.
+/// Carries the latest snapshot as it was observed plus the projected
+/// signatures the pruned snapshots produced, including cumulative fields
+/// such as `highest_scan_percent_seen` that `entries` cannot carry.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SystemSignaturesBaselinePayload {
+    pub system_id: SolarSystemId,
+    pub snapshot_id: String,
+    pub entries: Vec<SignatureEntry>,
+    /// Replaces the projected signatures instead of merging into them.
+    pub signatures: Vec<ProjectedSignature>,
+}
+
+/// A system was scanned down and found to have no signatures, as opposed to
+/// never having been scanned.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SystemSignaturesClearedPayload {
+    pub system_id: SolarSystemId,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum MovementEventSource {
     Esi,
@@ -49,6 +131,7 @@ pub enum MovementEventSource {
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum SignatureEventSource {
     Manual,
+    Import,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -58,6 +141,17 @@ pub struct MovementEvent {
     pub to_system_id: SolarSystemId,
     pub observed_at: Timestamp,
     pub source: MovementEventSource,
+    pub reason: Option<MovementReason>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StructureEnteredEvent {
+    pub character_id: CharacterId,
+    pub system_id: SolarSystemId,
+    pub from_structure_id: Option<StructureId>,
+    pub structure_id: StructureId,
+    pub observed_at: Timestamp,
+    pub source: MovementEventSource,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -70,15 +164,113 @@ pub struct SystemSignaturesObservedEvent {
     pub source: SignatureEventSource,
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SystemSignaturesClearedEvent {
+    pub system_id: SolarSystemId,
+    pub observed_at: Timestamp,
+    pub attribution_character_id: Option<CharacterId>,
+    pub source: SignatureEventSource,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum MovementEventError {
+    #[error("movement from system {system_id} to itself is not a move")]
+    SameSystem { system_id: SolarSystemId },
+}
+
+#[derive(Clone, Debug)]
+pub struct MovementEventBuilder {
+    character_id: CharacterId,
+    from_system_id: Option<SolarSystemId>,
+    to_system_id: SolarSystemId,
+    observed_at: Option<Timestamp>,
+    source: MovementEventSource,
+    reason: Option<MovementReason>,
+}
+
+impl MovementEventBuilder {
+    pub fn from(mut self, from_system_id: impl Into<Option<SolarSystemId>>) -> Self {
+        self.from_system_id = from_system_id.into();
+        self
+    }
+
+    pub fn observed_at(mut self, observed_at: Timestamp) -> Self {
+        self.observed_at = Some(observed_at);
+        self
+    }
+
+    pub fn source(mut self, source: MovementEventSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    pub fn reason(mut self, reason: impl Into<Option<MovementReason>>) -> Self {
+        self.reason = reason.into();
+        self
+    }
+
+    /// Builds the event, defaulting `observed_at` to now when unset.
+    pub fn build(self) -> Result<MovementEvent, MovementEventError> {
+        if self.from_system_id == Some(self.to_system_id) {
+            return Err(MovementEventError::SameSystem {
+                system_id: self.to_system_id,
+            });
+        }
+
+        Ok(MovementEvent {
+            character_id: self.character_id,
+            from_system_id: self.from_system_id,
+            to_system_id: self.to_system_id,
+            observed_at: self.observed_at.unwrap_or_else(Timestamp::now),
+            source: self.source,
+            reason: self.reason,
+        })
+    }
+}
+
 impl MovementEvent {
+    pub fn builder(character_id: CharacterId, to_system_id: SolarSystemId) -> MovementEventBuilder {
+        MovementEventBuilder {
+            character_id,
+            from_system_id: None,
+            to_system_id,
+            observed_at: None,
+            source: MovementEventSource::Esi,
+            reason: None,
+        }
+    }
+
     pub fn as_character_moved_payload(&self) -> CharacterMovedPayload {
         CharacterMovedPayload {
             from_system_id: self.from_system_id,
             to_system_id: self.to_system_id,
+            reason: self.reason,
         }
     }
 }
 
+impl StructureEnteredEvent {
+    pub fn as_payload(&self) -> StructureEnteredPayload {
+        StructureEnteredPayload {
+            system_id: self.system_id,
+            from_structure_id: self.from_structure_id,
+            structure_id: self.structure_id,
+        }
+    }
+}
+
+impl SignatureEntry {
+    /// Compares everything except `scan_percent`, which drifts between pastes
+    /// of the same signature and should not affect identity. Groups compare
+    /// by their [`canonical_group`] form.
+    pub fn same_identity(&self, other: &Self) -> bool {
+        self.signature_id == other.signature_id
+            && canonical_group(&self.group) == canonical_group(&other.group)
+            && self.site_type == other.site_type
+            && self.name == other.name
+    }
+}
+
 impl SystemSignaturesObservedEvent {
     pub fn as_payload(&self) -> SystemSignaturesObservedPayload {
         SystemSignaturesObservedPayload {
@@ -89,9 +281,222 @@ impl SystemSignaturesObservedEvent {
     }
 }
 
+impl SystemSignaturesClearedEvent {
+    pub fn as_payload(&self) -> SystemSignaturesClearedPayload {
+        SystemSignaturesClearedPayload {
+            system_id: self.system_id,
+        }
+    }
+}
+
 #[async_trait]
 pub trait MovementEventSink {
     type Error: Send + Sync + 'static;
 
     async fn emit_movement(&self, event: MovementEvent) -> Result<(), Self::Error>;
+
+    /// Called when a character enters a different structure in the system
+    /// it is already in. Sinks that only care about system changes can rely
+    /// on the default, which drops the event.
+    async fn emit_structure_entered(
+        &self,
+        _event: StructureEnteredEvent,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CharacterMovedPayload, MovementEvent, MovementEventError, MovementEventSource,
+        MovementReason, SignatureEntry, SystemSignaturesBaselinePayload,
+        SystemSignaturesObservedPayload,
+    };
+    use crate::{
+        gate_graph::GateGraph,
+        ids::{CharacterId, SolarSystemId},
+        time::Timestamp,
+    };
+
+    fn entry(signature_id: &str, site_type: Option<&str>, scan_percent: f32) -> SignatureEntry {
+        SignatureEntry {
+            signature_id: signature_id.to_owned(),
+            group: "Cosmic Signature".to_owned(),
+            site_type: site_type.map(ToOwned::to_owned),
+            name: None,
+            scan_percent: Some(scan_percent),
+        }
+    }
+
+    #[test]
+    fn same_identity_ignores_scan_percent() {
+        let first = entry("CWT-368", Some("Gas Site"), 28.6);
+        let second = entry("CWT-368", Some("Gas Site"), 28.7);
+
+        assert!(first.same_identity(&second));
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn same_identity_rejects_differing_fields() {
+        let base = entry("CWT-368", Some("Gas Site"), 28.6);
+
+        assert!(!base.same_identity(&entry("DVQ-434", Some("Gas Site"), 28.6)));
+        assert!(!base.same_identity(&entry("CWT-368", None, 28.6)));
+    }
+
+    #[test]
+    fn signatures_payload_serialization_is_stable() {
+        let payload = SystemSignaturesObservedPayload {
+            system_id: SolarSystemId(31000005),
+            snapshot_id: "snap-1".to_owned(),
+            entries: vec![entry("CWT-368", Some("Gas Site"), 28.5)],
+        };
+        let golden = r#"{"system_id":31000005,"snapshot_id":"snap-1","entries":[{"signature_id":"CWT-368","group":"Cosmic Signature","site_type":"Gas Site","name":null,"scan_percent":28.5}]}"#;
+
+        assert_eq!(serde_json::to_string(&payload).expect("serialize"), golden);
+        assert_eq!(
+            serde_json::from_str::<SystemSignaturesObservedPayload>(golden).expect("deserialize"),
+            payload
+        );
+    }
+
+    #[test]
+    fn signatures_payload_decodes_entries_without_optional_fields() {
+        let old_shape = r#"{"system_id":31000005,"snapshot_id":"snap-1","entries":[{"signature_id":"CWT-368","group":"Cosmic Signature"}]}"#;
+
+        let payload: SystemSignaturesObservedPayload =
+            serde_json::from_str(old_shape).expect("deserialize old shape");
+
+        assert_eq!(
+            payload.entries,
+            vec![SignatureEntry {
+                signature_id: "CWT-368".to_owned(),
+                group: "Cosmic Signature".to_owned(),
+                site_type: None,
+                name: None,
+                scan_percent: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn baseline_payload_decodes_signatures_without_wormhole_code() {
+        let old_shape = r#"{"system_id":31000005,"snapshot_id":"snap-1","entries":[],"signatures":[{"signature_id":"CWT-368","group":"Cosmic Signature","site_type":"Wormhole","name":"K162","latest_scan_percent":100.0,"highest_scan_percent_seen":100.0,"missing_from_latest_snapshot":false,"last_observed_at":"2023-11-14T22:13:20Z"}]}"#;
+
+        let payload: SystemSignaturesBaselinePayload =
+            serde_json::from_str(old_shape).expect("deserialize old baseline");
+
+        assert_eq!(payload.signatures.len(), 1);
+        assert_eq!(payload.signatures[0].signature_id, "CWT-368");
+        assert_eq!(payload.signatures[0].wormhole_code, None);
+    }
+
+    #[test]
+    fn baseline_payload_decodes_signatures_without_last_observed_at() {
+        let old_shape = r#"{"system_id":31000005,"snapshot_id":"snap-1","entries":[],"signatures":[{"signature_id":"CWT-368","group":"Cosmic Signature","site_type":"Gas Site","name":null,"latest_scan_percent":28.5,"highest_scan_percent_seen":28.5,"missing_from_latest_snapshot":false}]}"#;
+
+        let payload: SystemSignaturesBaselinePayload =
+            serde_json::from_str(old_shape).expect("deserialize old baseline");
+
+        assert_eq!(payload.signatures.len(), 1);
+        assert_eq!(payload.signatures[0].wormhole_code, None);
+        assert_eq!(
+            payload.signatures[0].last_observed_at,
+            Timestamp::UNIX_EPOCH
+        );
+    }
+
+    #[test]
+    fn first_sighting_is_classified_as_login() {
+        assert_eq!(
+            MovementReason::classify(None, SolarSystemId(30000142), None),
+            Some(MovementReason::Login)
+        );
+    }
+
+    #[test]
+    fn adjacent_move_is_classified_as_gate() {
+        let gate_graph =
+            GateGraph::from_jumps([(SolarSystemId(30000142), SolarSystemId(30000144))]);
+
+        assert_eq!(
+            MovementReason::classify(
+                Some(SolarSystemId(30000142)),
+                SolarSystemId(30000144),
+                Some(&gate_graph)
+            ),
+            Some(MovementReason::Gate)
+        );
+        assert_eq!(
+            MovementReason::classify(
+                Some(SolarSystemId(30000142)),
+                SolarSystemId(31000005),
+                Some(&gate_graph)
+            ),
+            Some(MovementReason::Wormhole)
+        );
+        assert_eq!(
+            MovementReason::classify(Some(SolarSystemId(30000142)), SolarSystemId(30000144), None),
+            None
+        );
+    }
+
+    #[test]
+    fn moved_payload_without_reason_keeps_original_shape() {
+        let legacy = r#"{"from_system_id":30000142,"to_system_id":30002510}"#;
+
+        let payload: CharacterMovedPayload = serde_json::from_str(legacy).expect("deserialize");
+        assert_eq!(payload.reason, None);
+        assert_eq!(serde_json::to_string(&payload).expect("serialize"), legacy);
+
+        let with_reason = CharacterMovedPayload {
+            reason: Some(MovementReason::Gate),
+            ..payload
+        };
+        assert_eq!(
+            serde_json::to_string(&with_reason).expect("serialize"),
+            r#"{"from_system_id":30000142,"to_system_id":30002510,"reason":"gate"}"#
+        );
+    }
+
+    #[test]
+    fn movement_builder_builds_valid_event() {
+        let observed_at = Timestamp::from_epoch_secs(1_700_000_000).expect("valid epoch seconds");
+
+        let event = MovementEvent::builder(CharacterId(42), SolarSystemId(30002510))
+            .from(SolarSystemId(30000142))
+            .observed_at(observed_at)
+            .source(MovementEventSource::Esi)
+            .build()
+            .expect("valid movement");
+
+        assert_eq!(
+            event,
+            MovementEvent {
+                character_id: CharacterId(42),
+                from_system_id: Some(SolarSystemId(30000142)),
+                to_system_id: SolarSystemId(30002510),
+                observed_at,
+                source: MovementEventSource::Esi,
+                reason: None,
+            }
+        );
+    }
+
+    #[test]
+    fn movement_builder_rejects_move_to_same_system() {
+        let err = MovementEvent::builder(CharacterId(42), SolarSystemId(30000142))
+            .from(SolarSystemId(30000142))
+            .build()
+            .expect_err("same-system move should be rejected");
+
+        assert_eq!(
+            err,
+            MovementEventError::SameSystem {
+                system_id: SolarSystemId(30000142),
+            }
+        );
+    }
 }
@@ -0,0 +1,142 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::ids::SolarSystemId;
+
+const FROM_SYSTEM_COLUMN: &str = "fromSolarSystemID";
+const TO_SYSTEM_COLUMN: &str = "toSolarSystemID";
+
+/// Stargate adjacency between solar systems.
+///
+/// Systems absent from the graph (e.g. wormhole space) are never adjacent to
+/// anything, so any transition into or out of them reads as a non-gate jump.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GateGraph {
+    neighbors: HashMap<SolarSystemId, HashSet<SolarSystemId>>,
+}
+
+impl GateGraph {
+    pub fn from_jumps(jumps: impl IntoIterator<Item = (SolarSystemId, SolarSystemId)>) -> Self {
+        let mut graph = Self::default();
+        for (from, to) in jumps {
+            graph.add_jump(from, to);
+        }
+        graph
+    }
+
+    /// Parses the SDE `mapSolarSystemJumps.csv` export.
+    ///
+    /// Only the `fromSolarSystemID` and `toSolarSystemID` columns are read;
+    /// their position is taken from the header row.
+    pub fn parse_sde_jumps_csv(input: &str) -> Result<Self, GateGraphParseError> {
+        let mut lines = input
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty());
+
+        let (_, header) = lines.next().ok_or(GateGraphParseError::MissingHeader)?;
+        let columns: Vec<&str> = header.split(',').map(str::trim).collect();
+        let from_idx = column_index(&columns, FROM_SYSTEM_COLUMN)?;
+        let to_idx = column_index(&columns, TO_SYSTEM_COLUMN)?;
+
+        let mut graph = Self::default();
+        for (idx, raw_line) in lines {
+            let line_number = idx + 1;
+            let fields: Vec<&str> = raw_line.split(',').map(str::trim).collect();
+            let from = parse_system_id(&fields, from_idx, line_number)?;
+            let to = parse_system_id(&fields, to_idx, line_number)?;
+            graph.add_jump(from, to);
+        }
+
+        Ok(graph)
+    }
+
+    pub fn add_jump(&mut self, from: SolarSystemId, to: SolarSystemId) {
+        self.neighbors.entry(from).or_default().insert(to);
+        self.neighbors.entry(to).or_default().insert(from);
+    }
+
+    pub fn are_adjacent(&self, a: SolarSystemId, b: SolarSystemId) -> bool {
+        self.neighbors
+            .get(&a)
+            .is_some_and(|neighbors| neighbors.contains(&b))
+    }
+
+    pub fn contains_system(&self, system_id: SolarSystemId) -> bool {
+        self.neighbors.contains_key(&system_id)
+    }
+}
+
+fn column_index(columns: &[&str], name: &'static str) -> Result<usize, GateGraphParseError> {
+    columns
+        .iter()
+        .position(|column| *column == name)
+        .ok_or(GateGraphParseError::MissingColumn { column: name })
+}
+
+fn parse_system_id(
+    fields: &[&str],
+    idx: usize,
+    line: usize,
+) -> Result<SolarSystemId, GateGraphParseError> {
+    let raw = fields
+        .get(idx)
+        .copied()
+        .ok_or(GateGraphParseError::InvalidColumnCount {
+            line,
+            expected_at_least: idx + 1,
+            actual: fields.len(),
+        })?;
+
+    raw.parse::<i32>()
+        .map(SolarSystemId)
+        .map_err(|_| GateGraphParseError::InvalidSystemId {
+            line,
+            value: raw.to_owned(),
+        })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum GateGraphParseError {
+    #[error("jumps data is missing a header row")]
+    MissingHeader,
+    #[error("jumps header is missing column '{column}'")]
+    MissingColumn { column: &'static str },
+    #[error(
+        "line {line}: expected at least {expected_at_least} comma-delimited columns, got {actual}"
+    )]
+    InvalidColumnCount {
+        line: usize,
+        expected_at_least: usize,
+        actual: usize,
+    },
+    #[error("line {line}: invalid solar system id '{value}'")]
+    InvalidSystemId { line: usize, value: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GateGraph, GateGraphParseError};
+    use crate::ids::SolarSystemId;
+
+    #[test]
+    fn adjacency_is_symmetric() {
+        let graph = GateGraph::from_jumps([(SolarSystemId(30000142), SolarSystemId(30000144))]);
+
+        assert!(graph.are_adjacent(SolarSystemId(30000142), SolarSystemId(30000144)));
+        assert!(graph.are_adjacent(SolarSystemId(30000144), SolarSystemId(30000142)));
+        assert!(!graph.are_adjacent(SolarSystemId(30000142), SolarSystemId(31000001)));
+    }
+
+    #[test]
+    fn rejects_header_without_system_columns() {
+        let err = GateGraph::parse_sde_jumps_csv("fromRegionID,toRegionID\n1,2\n")
+            .expect_err("header should be rejected");
+
+        assert_eq!(
+            err,
+            GateGraphParseError::MissingColumn {
+                column: "fromSolarSystemID"
+            }
+        );
+    }
+}
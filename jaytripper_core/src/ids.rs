@@ -14,6 +14,28 @@ pub struct StationId(pub i32);
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct StructureId(pub i64);
 
+impl SolarSystemId {
+    pub const JITA: Self = Self(30000142);
+    pub const AMARR: Self = Self(30002187);
+    pub const DODIXIE: Self = Self(30002659);
+    pub const RENS: Self = Self(30002510);
+    pub const HEK: Self = Self(30002053);
+
+    pub const TRADE_HUBS: [Self; 5] = [
+        Self::JITA,
+        Self::AMARR,
+        Self::DODIXIE,
+        Self::RENS,
+        Self::HEK,
+    ];
+}
+
+/// Whether `system_id` is one of the well-known high-security market hubs,
+/// for highlighting in views.
+pub fn is_trade_hub(system_id: SolarSystemId) -> bool {
+    SolarSystemId::TRADE_HUBS.contains(&system_id)
+}
+
 impl fmt::Display for CharacterId {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.0)
@@ -37,3 +59,15 @@ impl fmt::Display for StructureId {
         write!(f, "{}", self.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{SolarSystemId, is_trade_hub};
+
+    #[test]
+    fn jita_is_a_trade_hub() {
+        assert!(is_trade_hub(SolarSystemId(30000142)));
+        assert!(is_trade_hub(SolarSystemId::AMARR));
+        assert!(!is_trade_hub(SolarSystemId(31000005)));
+    }
+}
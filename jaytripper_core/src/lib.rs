@@ -1,18 +1,27 @@
 pub mod events;
+pub mod gate_graph;
 pub mod ids;
 pub mod signatures;
 pub mod time;
 
 pub use events::{
     CHARACTER_MOVED_EVENT_TYPE, CHARACTER_MOVED_SCHEMA_VERSION, CharacterMovedPayload,
-    MovementEvent, MovementEventSink, MovementEventSource, SYSTEM_SIGNATURES_OBSERVED_EVENT_TYPE,
+    MovementEvent, MovementEventBuilder, MovementEventError, MovementEventSink,
+    MovementEventSource, MovementReason, STRUCTURE_ENTERED_EVENT_TYPE,
+    STRUCTURE_ENTERED_SCHEMA_VERSION, SYSTEM_SIGNATURES_BASELINE_EVENT_TYPE,
+    SYSTEM_SIGNATURES_BASELINE_SCHEMA_VERSION, SYSTEM_SIGNATURES_CLEARED_EVENT_TYPE,
+    SYSTEM_SIGNATURES_CLEARED_SCHEMA_VERSION, SYSTEM_SIGNATURES_OBSERVED_EVENT_TYPE,
     SYSTEM_SIGNATURES_OBSERVED_SCHEMA_VERSION, SignatureEntry, SignatureEventSource,
-    SystemSignaturesObservedEvent, SystemSignaturesObservedPayload, character_stream_key,
-    system_stream_key,
+    StructureEnteredEvent, StructureEnteredPayload, SystemSignaturesBaselinePayload,
+    SystemSignaturesClearedEvent, SystemSignaturesClearedPayload, SystemSignaturesObservedEvent,
+    SystemSignaturesObservedPayload, character_stream_key, system_stream_key,
 };
-pub use ids::{CharacterId, SolarSystemId, StationId, StructureId};
+pub use gate_graph::{GateGraph, GateGraphParseError};
+pub use ids::{CharacterId, SolarSystemId, StationId, StructureId, is_trade_hub};
 pub use signatures::{
-    ProjectedSignature, SignatureParseError, is_valid_signature_id, merge_signature_snapshot,
-    parse_signature_snapshot,
+    DeferredMissingFlags, LenientParseOptions, LenientSignatureParse, ProjectedSignature,
+    SignatureParseError, UNKNOWN_GROUP, canonical_group, is_valid_signature_id,
+    merge_signature_snapshot, merge_signature_snapshots, parse_signature_snapshot,
+    parse_signature_snapshot_lenient, parse_signature_snapshot_lenient_with, parse_wormhole_code,
 };
-pub use time::Timestamp;
+pub use time::{Clock, SystemClock, Timestamp};
@@ -1,114 +1,346 @@
-use std::{collections::HashMap, sync::OnceLock};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::OnceLock,
+};
 
 use regex::Regex;
-
-use crate::events::SignatureEntry;
+use serde::{Deserialize, Serialize};
+
+use crate::{events::SignatureEntry, time::Timestamp};
+
+const COSMIC_SIGNATURE_GROUP: &str = "Cosmic Signature";
+const COSMIC_ANOMALY_GROUP: &str = "Cosmic Anomaly";
+const KNOWN_GROUPS: &[&str] = &[COSMIC_SIGNATURE_GROUP, COSMIC_ANOMALY_GROUP];
+const WORMHOLE_SITE_TYPE: &str = "Wormhole";
+
+/// Longest numeric part a scan percent may have. `100.000000` needs 10
+/// characters; anything far beyond that is garbage, not precision.
+const MAX_SCAN_PERCENT_DIGITS: usize = 16;
+
+/// Group recorded for a signature whose group the scanner had not resolved
+/// yet, when [`LenientParseOptions::default_unknown_group`] is set.
+pub const UNKNOWN_GROUP: &str = "Unknown";
+
+/// Maps a scanner group to its canonical casing, so pastes reading
+/// `cosmic signature` and `Cosmic Signature` land in the same group. Unknown
+/// groups are only trimmed.
+pub fn canonical_group(group: &str) -> String {
+    let group = group.trim();
+    KNOWN_GROUPS
+        .iter()
+        .find(|known| known.eq_ignore_ascii_case(group))
+        .map_or(group, |known| known)
+        .to_owned()
+}
 
 pub fn parse_signature_snapshot(input: &str) -> Result<Vec<SignatureEntry>, SignatureParseError> {
     let mut entries = Vec::new();
 
     for (idx, raw_line) in input.lines().enumerate() {
-        let line_number = idx + 1;
-        let line = raw_line.trim();
-
-        if line.is_empty() {
-            continue;
+        if let Some(entry) = parse_signature_line(raw_line, idx + 1, false)? {
+            push_deduplicated(&mut entries, entry);
         }
+    }
 
-        let columns: Vec<&str> = raw_line.split('\t').map(str::trim).collect();
-        if columns.len() < 5 {
-            return Err(SignatureParseError::InvalidColumnCount {
-                line: line_number,
-                expected_at_least: 5,
-                actual: columns.len(),
-            });
-        }
+    Ok(entries)
+}
 
-        let signature_id = columns[0];
-        if !is_valid_signature_id(signature_id) {
-            return Err(SignatureParseError::InvalidSignatureId {
-                line: line_number,
-                value: signature_id.to_owned(),
-            });
-        }
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LenientSignatureParse {
+    pub entries: Vec<SignatureEntry>,
+    pub skipped: Vec<SignatureParseError>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LenientParseOptions {
+    /// Record a line with a valid signature id but an empty group under
+    /// [`UNKNOWN_GROUP`] instead of skipping it. Such lines come from pasting
+    /// before the scanner resolved the group.
+    pub default_unknown_group: bool,
+}
 
-        let group = columns[1];
-        if group.is_empty() {
-            return Err(SignatureParseError::MissingGroup { line: line_number });
+/// Parses every well-formed line and collects errors for the rest instead of
+/// failing the whole snapshot.
+pub fn parse_signature_snapshot_lenient(input: &str) -> LenientSignatureParse {
+    parse_signature_snapshot_lenient_with(input, LenientParseOptions::default())
+}
+
+pub fn parse_signature_snapshot_lenient_with(
+    input: &str,
+    options: LenientParseOptions,
+) -> LenientSignatureParse {
+    let mut parsed = LenientSignatureParse::default();
+
+    for (idx, raw_line) in input.lines().enumerate() {
+        match parse_signature_line(raw_line, idx + 1, options.default_unknown_group) {
+            Ok(Some(entry)) => push_deduplicated(&mut parsed.entries, entry),
+            Ok(None) => {}
+            Err(err) => parsed.skipped.push(err),
         }
+    }
 
-        let site_type = to_optional(columns.get(2).copied().unwrap_or_default());
-        let name = to_optional(columns.get(3).copied().unwrap_or_default());
-        let scan_percent = parse_scan_percent(columns[4], line_number)?;
+    parsed
+}
 
-        entries.push(SignatureEntry {
-            signature_id: signature_id.to_owned(),
-            group: group.to_owned(),
-            site_type: site_type.map(ToOwned::to_owned),
-            name: name.map(ToOwned::to_owned),
-            scan_percent,
+fn parse_signature_line(
+    raw_line: &str,
+    line_number: usize,
+    default_unknown_group: bool,
+) -> Result<Option<SignatureEntry>, SignatureParseError> {
+    let line = raw_line.trim();
+
+    if line.is_empty() {
+        return Ok(None);
+    }
+
+    let mut columns: Vec<&str> = raw_line.split('\t').map(str::trim).collect();
+    let width = columns.len();
+    // Some clients pad rows to a fixed width with empty trailing columns.
+    while columns.last().is_some_and(|column| column.is_empty()) {
+        columns.pop();
+    }
+    let mut group = canonical_group(columns.get(1).copied().unwrap_or_default());
+    // Anomalies are always fully scanned, and some exports drop their
+    // scan-percent column entirely.
+    let is_anomaly_without_percent = columns.len() == 4 && group == COSMIC_ANOMALY_GROUP;
+    if width < 5 && !is_anomaly_without_percent {
+        return Err(SignatureParseError::InvalidColumnCount {
+            line: line_number,
+            expected_at_least: 5,
+            actual: width,
         });
     }
 
-    Ok(entries)
+    let signature_id = columns[0];
+    let has_valid_id = is_valid_signature_id(signature_id);
+    if group.is_empty() && !has_valid_id {
+        return Err(SignatureParseError::MissingGroup { line: line_number });
+    }
+    if !has_valid_id {
+        return Err(SignatureParseError::InvalidSignatureId {
+            line: line_number,
+            value: signature_id.to_owned(),
+        });
+    }
+
+    if group.is_empty() {
+        if !default_unknown_group {
+            return Err(SignatureParseError::EmptyGroupWithKnownId {
+                line: line_number,
+                signature_id: signature_id.to_owned(),
+            });
+        }
+        group = UNKNOWN_GROUP.to_owned();
+    }
+
+    let site_type = to_optional(columns.get(2).copied().unwrap_or_default());
+    let name = to_optional(columns.get(3).copied().unwrap_or_default());
+    let scan_percent = if is_anomaly_without_percent {
+        Some(100.0)
+    } else {
+        parse_scan_percent(columns.get(4).copied().unwrap_or_default(), line_number)?
+    };
+
+    Ok(Some(SignatureEntry {
+        signature_id: signature_id.to_owned(),
+        group,
+        site_type: site_type.map(ToOwned::to_owned),
+        name: name.map(ToOwned::to_owned),
+        scan_percent,
+    }))
 }
 
-#[derive(Clone, Debug, PartialEq)]
+fn push_deduplicated(entries: &mut Vec<SignatureEntry>, entry: SignatureEntry) {
+    match entries
+        .iter()
+        .position(|existing| existing.same_identity(&entry))
+    {
+        Some(idx) => entries[idx] = entry,
+        None => entries.push(entry),
+    }
+}
+
+/// Serializable for state export. Scan percents are always finite parser
+/// output, so the `f32` fields round-trip through JSON exactly.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct ProjectedSignature {
     pub signature_id: String,
     pub group: String,
     pub site_type: Option<String>,
     pub name: Option<String>,
+    /// Wormhole type code (e.g. `K162`) recognized in `name`.
+    #[serde(default)]
+    pub wormhole_code: Option<String>,
     pub latest_scan_percent: Option<f32>,
     pub highest_scan_percent_seen: Option<f32>,
     pub missing_from_latest_snapshot: bool,
+    /// Baselines written before this field existed decode it as the epoch,
+    /// so any later paste still counts as newer.
+    #[serde(default = "unknown_observed_at")]
+    pub last_observed_at: Timestamp,
 }
 
+fn unknown_observed_at() -> Timestamp {
+    Timestamp::UNIX_EPOCH
+}
+
+/// Folds one snapshot into the projected signatures.
+///
+/// `latest_snapshot_at` is when the system's newest snapshot so far was
+/// observed. Missing flags describe that snapshot, so only an incoming
+/// snapshot at least as new sweeps or clears them; an older paste (e.g. a
+/// backfill) leaves them alone and adds unseen signatures as missing.
+///
+/// Independently, entries observed before the last snapshot that touched a
+/// signature may raise `highest_scan_percent_seen`, but never overwrite its
+/// latest fields.
 pub fn merge_signature_snapshot(
     signatures_by_id: &mut HashMap<String, ProjectedSignature>,
     incoming_entries: &[SignatureEntry],
+    observed_at: Timestamp,
+    latest_snapshot_at: Option<Timestamp>,
 ) {
-    for signature in signatures_by_id.values_mut() {
-        signature.missing_from_latest_snapshot = true;
+    let is_latest = is_latest_snapshot(observed_at, latest_snapshot_at);
+    if is_latest {
+        for signature in signatures_by_id.values_mut() {
+            signature.missing_from_latest_snapshot = true;
+        }
     }
 
     for entry in incoming_entries {
-        let signature = signatures_by_id
-            .entry(entry.signature_id.clone())
-            .or_insert_with(|| ProjectedSignature {
-                signature_id: entry.signature_id.clone(),
-                group: entry.group.clone(),
-                site_type: None,
-                name: None,
-                latest_scan_percent: None,
-                highest_scan_percent_seen: None,
-                missing_from_latest_snapshot: false,
-            });
+        apply_signature_entry(signatures_by_id, entry, observed_at, is_latest);
+    }
+}
 
-        if !entry.group.is_empty() {
-            signature.group = entry.group.clone();
-        }
+/// Folds `snapshots` in order with the same result as calling
+/// [`merge_signature_snapshot`] for each, without sweeping every signature's
+/// missing flag per snapshot. Meant for replaying long histories.
+pub fn merge_signature_snapshots<'a>(
+    signatures_by_id: &mut HashMap<String, ProjectedSignature>,
+    snapshots: impl IntoIterator<Item = (&'a [SignatureEntry], Timestamp)>,
+    mut latest_snapshot_at: Option<Timestamp>,
+) {
+    let mut deferred = DeferredMissingFlags::default();
+    for (entries, observed_at) in snapshots {
+        deferred.merge(signatures_by_id, entries, observed_at, latest_snapshot_at);
+        latest_snapshot_at =
+            Some(latest_snapshot_at.map_or(observed_at, |latest| latest.max(observed_at)));
+    }
+    deferred.settle(signatures_by_id);
+}
 
-        if entry.site_type.is_some() {
-            signature.site_type = entry.site_type.clone();
-        }
+/// Merges snapshots like [`merge_signature_snapshot`] but settles
+/// `missing_from_latest_snapshot` once, in [`Self::settle`], instead of per
+/// snapshot. Missing flags are stale until then.
+///
+/// Each sweep fully determines the flags, so only the ids of the last
+/// snapshot that was the system's latest need keeping; older pastes already
+/// set the flags of signatures they add.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DeferredMissingFlags {
+    latest_ids: Option<HashSet<String>>,
+}
 
-        if entry.name.is_some() {
-            signature.name = entry.name.clone();
+impl DeferredMissingFlags {
+    pub fn merge(
+        &mut self,
+        signatures_by_id: &mut HashMap<String, ProjectedSignature>,
+        incoming_entries: &[SignatureEntry],
+        observed_at: Timestamp,
+        latest_snapshot_at: Option<Timestamp>,
+    ) {
+        let is_latest = is_latest_snapshot(observed_at, latest_snapshot_at);
+        if is_latest {
+            self.latest_ids = Some(
+                incoming_entries
+                    .iter()
+                    .map(|entry| entry.signature_id.clone())
+                    .collect(),
+            );
         }
+        for entry in incoming_entries {
+            apply_signature_entry(signatures_by_id, entry, observed_at, is_latest);
+        }
+    }
 
-        if let Some(percent) = entry.scan_percent {
-            signature.latest_scan_percent = Some(percent);
-            signature.highest_scan_percent_seen = Some(
-                signature
-                    .highest_scan_percent_seen
-                    .unwrap_or(percent)
-                    .max(percent),
-            );
+    pub fn settle(self, signatures_by_id: &mut HashMap<String, ProjectedSignature>) {
+        let Some(latest_ids) = self.latest_ids else {
+            return;
+        };
+        for signature in signatures_by_id.values_mut() {
+            signature.missing_from_latest_snapshot = !latest_ids.contains(&signature.signature_id);
         }
+    }
+}
+
+fn is_latest_snapshot(observed_at: Timestamp, latest_snapshot_at: Option<Timestamp>) -> bool {
+    latest_snapshot_at.is_none_or(|latest| observed_at >= latest)
+}
+
+/// Applies one entry from a snapshot. `is_latest` says whether that snapshot
+/// is the system's newest, and so whether the entry counts as present.
+fn apply_signature_entry(
+    signatures_by_id: &mut HashMap<String, ProjectedSignature>,
+    entry: &SignatureEntry,
+    observed_at: Timestamp,
+    is_latest: bool,
+) {
+    let signature = signatures_by_id
+        .entry(entry.signature_id.clone())
+        .or_insert_with(|| ProjectedSignature {
+            signature_id: entry.signature_id.clone(),
+            group: canonical_group(&entry.group),
+            site_type: None,
+            name: None,
+            wormhole_code: None,
+            latest_scan_percent: None,
+            highest_scan_percent_seen: None,
+            missing_from_latest_snapshot: !is_latest,
+            last_observed_at: observed_at,
+        });
 
+    if let Some(percent) = entry.scan_percent {
+        signature.highest_scan_percent_seen = Some(
+            signature
+                .highest_scan_percent_seen
+                .unwrap_or(percent)
+                .max(percent),
+        );
+    }
+
+    if is_latest {
         signature.missing_from_latest_snapshot = false;
     }
+
+    if observed_at < signature.last_observed_at {
+        return;
+    }
+
+    if !entry.group.is_empty() {
+        signature.group = canonical_group(&entry.group);
+    }
+
+    if entry.site_type.is_some() {
+        signature.site_type = entry.site_type.clone();
+    }
+
+    if let Some(name) = &entry.name {
+        signature.name = Some(name.clone());
+    }
+
+    // Only wormholes carry a type code; other sites can have code-like words
+    // in their names.
+    signature.wormhole_code = signature
+        .name
+        .as_deref()
+        .filter(|_| signature.site_type.as_deref() == Some(WORMHOLE_SITE_TYPE))
+        .and_then(parse_wormhole_code);
+
+    if entry.scan_percent.is_some() {
+        signature.latest_scan_percent = entry.scan_percent;
+    }
+
+    signature.last_observed_at = observed_at;
 }
 
 fn to_optional(value: &str) -> Option<&str> {
@@ -130,6 +362,13 @@ fn parse_scan_percent(raw: &str, line: usize) -> Result<Option<f32>, SignaturePa
     })?;
 
     let numeric = captures.get(1).map(|m| m.as_str()).unwrap_or_default();
+    if numeric.len() > MAX_SCAN_PERCENT_DIGITS {
+        return Err(SignatureParseError::InvalidScanPercent {
+            line,
+            value: truncate_for_error(trimmed),
+            reason: format!("numeric part longer than {MAX_SCAN_PERCENT_DIGITS} characters"),
+        });
+    }
 
     let parsed = numeric
         .parse::<f32>()
@@ -139,7 +378,7 @@ fn parse_scan_percent(raw: &str, line: usize) -> Result<Option<f32>, SignaturePa
             reason: "not a valid number".to_owned(),
         })?;
 
-    if !(0.0..=100.0).contains(&parsed) {
+    if !parsed.is_finite() || !(0.0..=100.0).contains(&parsed) {
         return Err(SignatureParseError::InvalidScanPercent {
             line,
             value: trimmed.to_owned(),
@@ -150,10 +389,34 @@ fn parse_scan_percent(raw: &str, line: usize) -> Result<Option<f32>, SignaturePa
     Ok(Some(parsed))
 }
 
+/// Keeps error messages bounded when echoing an oversized cell back.
+fn truncate_for_error(value: &str) -> String {
+    const MAX_ECHOED_CHARS: usize = 32;
+    if value.chars().count() <= MAX_ECHOED_CHARS {
+        return value.to_owned();
+    }
+    let mut truncated: String = value.chars().take(MAX_ECHOED_CHARS).collect();
+    truncated.push('…');
+    truncated
+}
+
 pub fn is_valid_signature_id(value: &str) -> bool {
     signature_id_regex().is_match(value)
 }
 
+/// Finds an EVE wormhole type code (a letter followed by three digits, such
+/// as `K162` or `C247`) among the words of a signature name.
+pub fn parse_wormhole_code(name: &str) -> Option<String> {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .find(|word| wormhole_code_regex().is_match(word))
+        .map(ToOwned::to_owned)
+}
+
+fn wormhole_code_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r"^[A-Z][0-9]{3}$").expect("valid regex"))
+}
+
 fn signature_id_regex() -> &'static Regex {
     static REGEX: OnceLock<Regex> = OnceLock::new();
     REGEX.get_or_init(|| Regex::new(r"^[A-Z]{3}-[0-9]{3}$").expect("valid regex"))
@@ -178,6 +441,10 @@ pub enum SignatureParseError {
     InvalidSignatureId { line: usize, value: String },
     #[error("line {line}: missing group column")]
     MissingGroup { line: usize },
+    /// The signature id is well-formed but the group is empty, which usually
+    /// means the paste happened before the scanner resolved the group.
+    #[error("line {line}: signature {signature_id} has no group yet; rescan before pasting")]
+    EmptyGroupWithKnownId { line: usize, signature_id: String },
     #[error("line {line}: invalid scan percent '{value}': {reason}")]
     InvalidScanPercent {
         line: usize,
@@ -186,12 +453,40 @@ pub enum SignatureParseError {
     },
 }
 
+impl SignatureParseError {
+    pub fn line(&self) -> usize {
+        match self {
+            Self::InvalidColumnCount { line, .. }
+            | Self::InvalidSignatureId { line, .. }
+            | Self::MissingGroup { line }
+            | Self::EmptyGroupWithKnownId { line, .. }
+            | Self::InvalidScanPercent { line, .. } => *line,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
 
-    use super::{is_valid_signature_id, merge_signature_snapshot, parse_signature_snapshot};
-    use crate::events::SignatureEntry;
+    use super::{
+        LenientParseOptions, ProjectedSignature, SignatureParseError, UNKNOWN_GROUP,
+        canonical_group, is_valid_signature_id, merge_signature_snapshot,
+        merge_signature_snapshots, parse_signature_snapshot, parse_signature_snapshot_lenient,
+        parse_signature_snapshot_lenient_with, parse_wormhole_code,
+    };
+    use crate::{events::SignatureEntry, time::Timestamp};
+
+    #[test]
+    fn parses_wormhole_codes_from_names() {
+        assert_eq!(parse_wormhole_code("K162"), Some("K162".to_owned()));
+        assert_eq!(
+            parse_wormhole_code("C247 -> Class 3"),
+            Some("C247".to_owned())
+        );
+        assert_eq!(parse_wormhole_code("Unstable Wormhole"), None);
+        assert_eq!(parse_wormhole_code("Ordinary Perimeter Deposit"), None);
+    }
 
     #[test]
     fn validates_signature_ids() {
@@ -212,6 +507,115 @@ mod tests {
         assert_eq!(entries[0].site_type.as_deref(), Some("Gas Site"));
     }
 
+    #[test]
+    fn mixed_case_groups_parse_to_canonical_group() {
+        let input = "ABC-123\tcosmic signature\tGas Site\t\t28.6%\t21.93 AU\n\
+                     DEF-456\tCOSMIC SIGNATURE\t\t\t0.0%\t4.10 AU\n\
+                     GHI-789\tcosmic anomaly\tCombat Site\tTraining Site\n";
+        let entries = parse_signature_snapshot(input).expect("parse snapshot");
+
+        let groups: Vec<&str> = entries.iter().map(|entry| entry.group.as_str()).collect();
+        assert_eq!(
+            groups,
+            vec!["Cosmic Signature", "Cosmic Signature", "Cosmic Anomaly"]
+        );
+        assert_eq!(entries[2].scan_percent, Some(100.0));
+        assert_eq!(canonical_group("  Data Site "), "Data Site");
+    }
+
+    #[test]
+    fn merge_canonicalizes_groups_from_stored_entries() {
+        let mut projected = HashMap::new();
+        let entry = |group: &str| SignatureEntry {
+            signature_id: "ABC-123".to_owned(),
+            group: group.to_owned(),
+            site_type: None,
+            name: None,
+            scan_percent: Some(10.0),
+        };
+
+        merge_signature_snapshot(&mut projected, &[entry("cosmic signature")], ts(100), None);
+        assert_eq!(projected["ABC-123"].group, "Cosmic Signature");
+        assert!(entry("cosmic signature").same_identity(&entry("Cosmic Signature")));
+    }
+
+    #[test]
+    fn collapses_repeated_identity_rows_keeping_latest_percent() {
+        let input = "ABC-123\tCosmic Signature\tGas Site\t\t28.6%\t21.93 AU\n\
+                     ABC-123\tCosmic Signature\tGas Site\t\t28.7%\t21.90 AU\n";
+        let entries = parse_signature_snapshot(input).expect("parse snapshot");
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].scan_percent, Some(28.7));
+    }
+
+    #[test]
+    fn lenient_parse_keeps_valid_lines_and_reports_skipped_ones() {
+        let input = "ABC-123\tCosmic Signature\tGas Site\t\t28.6%\t21.93 AU\n\
+                     BAD\tCosmic Signature\t\t\t0.0%\t1.00 AU\n\
+                     DEF-456\tCosmic Signature\n\
+                     GHI-789\tCosmic Anomaly\tCombat Site\tTraining Site\t100.0%\t2.00 AU\n";
+
+        let parsed = parse_signature_snapshot_lenient(input);
+
+        assert_eq!(parsed.entries.len(), 2);
+        assert_eq!(
+            parsed
+                .skipped
+                .iter()
+                .map(|err| err.line())
+                .collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+    }
+
+    #[test]
+    fn empty_group_with_valid_id_fails_strict_parse() {
+        let input = "ABC-123\t\t\t\t0.0%\t1.00 AU\n\t\t\t\t0.0%\t1.00 AU\n";
+
+        let err = parse_signature_snapshot(input).expect_err("parse should fail");
+        assert_eq!(
+            err,
+            SignatureParseError::EmptyGroupWithKnownId {
+                line: 1,
+                signature_id: "ABC-123".to_owned(),
+            }
+        );
+
+        let parsed = parse_signature_snapshot_lenient(input);
+        assert!(parsed.entries.is_empty());
+        assert_eq!(
+            parsed.skipped,
+            vec![
+                SignatureParseError::EmptyGroupWithKnownId {
+                    line: 1,
+                    signature_id: "ABC-123".to_owned(),
+                },
+                SignatureParseError::MissingGroup { line: 2 },
+            ]
+        );
+    }
+
+    #[test]
+    fn lenient_parse_can_default_empty_group_to_unknown() {
+        let input = "ABC-123\t\t\t\t0.0%\t1.00 AU\n\t\t\t\t0.0%\t1.00 AU\n";
+
+        let parsed = parse_signature_snapshot_lenient_with(
+            input,
+            LenientParseOptions {
+                default_unknown_group: true,
+            },
+        );
+
+        assert_eq!(parsed.entries.len(), 1);
+        assert_eq!(parsed.entries[0].signature_id, "ABC-123");
+        assert_eq!(parsed.entries[0].group, UNKNOWN_GROUP);
+        assert_eq!(
+            parsed.skipped,
+            vec![SignatureParseError::MissingGroup { line: 2 }]
+        );
+    }
+
     #[test]
     fn reports_invalid_percent_with_line_context() {
         let input = "ABC-123\tCosmic Signature\tGas Site\t\tabs%\t21.93 AU\n";
@@ -219,6 +623,35 @@ mod tests {
         assert!(err.to_string().contains("line 1"));
     }
 
+    #[test]
+    fn rejects_absurdly_long_percent_without_parsing_it() {
+        let digits = "9".repeat(10_000);
+        let input = format!("ABC-123\tCosmic Signature\tGas Site\t\t{digits}%\t21.93 AU\n");
+
+        let err = parse_signature_snapshot(&input).expect_err("parse should fail");
+
+        match &err {
+            SignatureParseError::InvalidScanPercent {
+                line,
+                value,
+                reason,
+            } => {
+                assert_eq!(*line, 1);
+                assert!(value.chars().count() <= 33, "{value}");
+                assert!(reason.contains("longer than"), "{reason}");
+            }
+            other => panic!("expected invalid scan percent, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn accepts_long_but_reasonable_percent() {
+        let input = "ABC-123\tCosmic Signature\tGas Site\t\t28.6000000%\t21.93 AU\n";
+        let entries = parse_signature_snapshot(input).expect("parse snapshot");
+
+        assert_eq!(entries[0].scan_percent, Some(28.6));
+    }
+
     #[test]
     fn reports_out_of_range_percent() {
         let input = "ABC-123\tCosmic Signature\tGas Site\t\t101.0%\t21.93 AU\n";
@@ -239,6 +672,8 @@ mod tests {
                 name: None,
                 scan_percent: Some(70.0),
             }],
+            ts(100),
+            None,
         );
 
         merge_signature_snapshot(
@@ -250,6 +685,8 @@ mod tests {
                 name: Some("Relic Training Site".to_owned()),
                 scan_percent: Some(0.0),
             }],
+            ts(200),
+            Some(ts(100)),
         );
 
         let signature = projected.get("ABC-123").expect("signature should exist");
@@ -263,6 +700,70 @@ mod tests {
         assert!(!signature.missing_from_latest_snapshot);
     }
 
+    #[test]
+    fn projected_signature_round_trips_through_json() {
+        let signature = ProjectedSignature {
+            signature_id: "ABC-123".to_owned(),
+            group: "Cosmic Signature".to_owned(),
+            site_type: Some("Wormhole".to_owned()),
+            name: Some("K162".to_owned()),
+            wormhole_code: Some("K162".to_owned()),
+            latest_scan_percent: Some(33.3),
+            highest_scan_percent_seen: Some(87.5),
+            missing_from_latest_snapshot: true,
+            last_observed_at: ts(100),
+        };
+
+        let json = serde_json::to_string(&signature).expect("serialize signature");
+        assert!(json.contains("\"latest_scan_percent\":33.3"), "{json}");
+        let decoded: ProjectedSignature =
+            serde_json::from_str(&json).expect("deserialize signature");
+
+        assert_eq!(decoded, signature);
+    }
+
+    #[test]
+    fn merge_exposes_wormhole_code_from_name() {
+        let mut projected = HashMap::new();
+
+        merge_signature_snapshot(
+            &mut projected,
+            &[SignatureEntry {
+                signature_id: "ABC-123".to_owned(),
+                group: "Cosmic Signature".to_owned(),
+                site_type: Some("Wormhole".to_owned()),
+                name: Some("K162".to_owned()),
+                scan_percent: Some(100.0),
+            }],
+            ts(100),
+            None,
+        );
+
+        let signature = projected.get("ABC-123").expect("signature should exist");
+        assert_eq!(signature.wormhole_code.as_deref(), Some("K162"));
+    }
+
+    #[test]
+    fn merge_ignores_code_like_names_outside_wormholes() {
+        let mut projected = HashMap::new();
+
+        merge_signature_snapshot(
+            &mut projected,
+            &[SignatureEntry {
+                signature_id: "ABC-123".to_owned(),
+                group: "Cosmic Signature".to_owned(),
+                site_type: Some("Data Site".to_owned()),
+                name: Some("Unsecured Frontier B123 Server".to_owned()),
+                scan_percent: Some(100.0),
+            }],
+            ts(100),
+            None,
+        );
+
+        let signature = projected.get("ABC-123").expect("signature should exist");
+        assert_eq!(signature.wormhole_code, None);
+    }
+
     #[test]
     fn merge_marks_absent_signatures_as_missing_from_latest_snapshot() {
         let mut projected = HashMap::new();
@@ -285,6 +786,8 @@ mod tests {
                     scan_percent: Some(25.0),
                 },
             ],
+            ts(100),
+            None,
         );
 
         merge_signature_snapshot(
@@ -296,6 +799,8 @@ mod tests {
                 name: None,
                 scan_percent: Some(50.0),
             }],
+            ts(200),
+            Some(ts(100)),
         );
 
         let abc = projected.get("ABC-123").expect("ABC should exist");
@@ -303,4 +808,145 @@ mod tests {
         assert!(!abc.missing_from_latest_snapshot);
         assert!(def.missing_from_latest_snapshot);
     }
+
+    #[test]
+    fn merge_ignores_latest_fields_from_out_of_order_older_snapshot() {
+        let mut projected = HashMap::new();
+        let entry = |scan_percent: f32| SignatureEntry {
+            signature_id: "ABC-123".to_owned(),
+            group: "Cosmic Signature".to_owned(),
+            site_type: None,
+            name: None,
+            scan_percent: Some(scan_percent),
+        };
+
+        merge_signature_snapshot(&mut projected, &[entry(70.0)], ts(200), None);
+        merge_signature_snapshot(&mut projected, &[entry(30.0)], ts(100), Some(ts(200)));
+
+        let signature = projected.get("ABC-123").expect("signature should exist");
+        assert_eq!(signature.latest_scan_percent, Some(70.0));
+        assert_eq!(signature.highest_scan_percent_seen, Some(70.0));
+        assert_eq!(signature.last_observed_at, ts(200));
+    }
+
+    #[test]
+    fn merge_stale_snapshot_does_not_mark_newer_signatures_missing() {
+        let mut projected = HashMap::new();
+        let entry = |signature_id: &str| SignatureEntry {
+            signature_id: signature_id.to_owned(),
+            group: "Cosmic Signature".to_owned(),
+            site_type: None,
+            name: None,
+            scan_percent: Some(10.0),
+        };
+
+        merge_signature_snapshot(&mut projected, &[entry("ABC-123")], ts(200), None);
+        merge_signature_snapshot(&mut projected, &[entry("DEF-456")], ts(100), Some(ts(200)));
+
+        let abc = projected.get("ABC-123").expect("ABC should exist");
+        assert!(!abc.missing_from_latest_snapshot);
+    }
+
+    #[test]
+    fn merge_old_paste_after_newer_snapshot_keeps_missing_flags() {
+        let mut projected = HashMap::new();
+        let entry = |signature_id: &str| SignatureEntry {
+            signature_id: signature_id.to_owned(),
+            group: "Cosmic Signature".to_owned(),
+            site_type: None,
+            name: None,
+            scan_percent: Some(10.0),
+        };
+
+        merge_signature_snapshot(
+            &mut projected,
+            &[entry("ABC-123"), entry("DEF-456")],
+            ts(50),
+            None,
+        );
+        merge_signature_snapshot(&mut projected, &[entry("ABC-123")], ts(200), Some(ts(50)));
+        merge_signature_snapshot(
+            &mut projected,
+            &[entry("DEF-456"), entry("GHI-789")],
+            ts(100),
+            Some(ts(200)),
+        );
+
+        let abc = projected.get("ABC-123").expect("ABC should exist");
+        let def = projected.get("DEF-456").expect("DEF should exist");
+        let ghi = projected.get("GHI-789").expect("GHI should exist");
+        assert!(!abc.missing_from_latest_snapshot);
+        assert!(
+            def.missing_from_latest_snapshot,
+            "the newer snapshot left DEF out"
+        );
+        assert_eq!(def.last_observed_at, ts(100));
+        assert!(
+            ghi.missing_from_latest_snapshot,
+            "ids first seen in an old paste are not in the latest snapshot"
+        );
+    }
+
+    #[test]
+    fn merge_many_matches_per_snapshot_merge() {
+        let entry = |signature_id: &str, scan_percent: f32| SignatureEntry {
+            signature_id: signature_id.to_owned(),
+            group: "Cosmic Signature".to_owned(),
+            site_type: None,
+            name: Some(format!("{signature_id} site")),
+            scan_percent: Some(scan_percent),
+        };
+        let mut initial = HashMap::new();
+        merge_signature_snapshot(
+            &mut initial,
+            &[entry("AAA-111", 10.0), entry("BBB-222", 20.0)],
+            ts(50),
+            None,
+        );
+        // Includes an out-of-order paste, a repeat timestamp and an empty
+        // snapshot so stale and tied observations are exercised.
+        let snapshots: Vec<(Vec<SignatureEntry>, Timestamp)> = vec![
+            (vec![entry("AAA-111", 30.0), entry("CCC-333", 5.0)], ts(100)),
+            (vec![entry("DDD-444", 40.0)], ts(300)),
+            (
+                vec![entry("BBB-222", 90.0), entry("CCC-333", 60.0)],
+                ts(200),
+            ),
+            (vec![entry("DDD-444", 45.0), entry("EEE-555", 1.0)], ts(300)),
+            (vec![entry("AAA-111", 99.0)], ts(150)),
+            (Vec::new(), ts(120)),
+            (vec![entry("FFF-666", 15.0)], ts(250)),
+        ];
+
+        let mut naive = initial.clone();
+        let mut latest = ts(50);
+        for (entries, observed_at) in &snapshots {
+            merge_signature_snapshot(&mut naive, entries, *observed_at, Some(latest));
+            latest = latest.max(*observed_at);
+        }
+        let mut batched = initial;
+        merge_signature_snapshots(
+            &mut batched,
+            snapshots
+                .iter()
+                .map(|(entries, observed_at)| (entries.as_slice(), *observed_at)),
+            Some(ts(50)),
+        );
+
+        assert_eq!(batched, naive);
+        assert!(
+            naive
+                .values()
+                .any(|signature| signature.missing_from_latest_snapshot)
+        );
+        assert!(
+            naive
+                .values()
+                .any(|signature| !signature.missing_from_latest_snapshot)
+        );
+    }
+
+    fn ts(epoch_secs: i64) -> Timestamp {
+        Timestamp::from_epoch_secs(epoch_secs).expect("valid epoch seconds")
+    }
 }
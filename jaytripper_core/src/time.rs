@@ -3,12 +3,16 @@ use std::{fmt, time::Duration};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct Timestamp(DateTime<Utc>);
 
 impl Timestamp {
+    pub const UNIX_EPOCH: Self = Self(DateTime::UNIX_EPOCH);
+
+    /// Current time truncated to whole milliseconds, the precision the event
+    /// store persists, so a value compares equal after a round trip.
     pub fn now() -> Self {
-        Self(Utc::now())
+        Self(Utc::now()).truncate_to_millis()
     }
 
     pub fn from_epoch_secs(epoch_secs: i64) -> Option<Self> {
@@ -35,6 +39,40 @@ impl Timestamp {
     pub fn signed_duration_since(self, earlier: Self) -> chrono::Duration {
         self.0.signed_duration_since(earlier.0)
     }
+
+    /// Drops sub-millisecond precision.
+    pub fn truncate_to_millis(self) -> Self {
+        Self::from_epoch_millis(self.as_epoch_millis()).unwrap_or(self)
+    }
+
+    /// Floors to a multiple of `granularity` since the Unix epoch (e.g. the
+    /// start of the minute or hour), at millisecond resolution. A granularity
+    /// below one millisecond returns the timestamp unchanged.
+    pub fn truncate_to(self, granularity: Duration) -> Self {
+        let granularity_millis = i64::try_from(granularity.as_millis()).unwrap_or(i64::MAX);
+        if granularity_millis == 0 {
+            return self;
+        }
+
+        let epoch_millis = self.as_epoch_millis();
+        let floored = epoch_millis - epoch_millis.rem_euclid(granularity_millis);
+        Self::from_epoch_millis(floored).unwrap_or(self)
+    }
+}
+
+/// Source of the current time, so callers can substitute a fixed clock in
+/// tests.
+pub trait Clock {
+    fn now(&self) -> Timestamp;
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Timestamp {
+        Timestamp::now()
+    }
 }
 
 impl std::fmt::Debug for Timestamp {
@@ -42,3 +80,76 @@ impl std::fmt::Debug for Timestamp {
         write!(f, "{:?}", self.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Timestamp;
+
+    #[test]
+    fn truncate_to_floors_to_minute_boundary() {
+        let ts = Timestamp::from_epoch_millis(1_700_000_059_999).expect("timestamp");
+
+        assert_eq!(
+            ts.truncate_to(Duration::from_secs(60)),
+            Timestamp::from_epoch_secs(1_700_000_040).expect("timestamp")
+        );
+    }
+
+    #[test]
+    fn truncate_to_floors_to_hour_boundary() {
+        let ts = Timestamp::from_epoch_secs(1_700_003_599).expect("timestamp");
+
+        assert_eq!(
+            ts.truncate_to(Duration::from_secs(3600)),
+            Timestamp::from_epoch_secs(1_700_002_800).expect("timestamp")
+        );
+    }
+
+    #[test]
+    fn truncate_to_keeps_values_already_on_boundary() {
+        let ts = Timestamp::from_epoch_secs(1_699_999_200).expect("timestamp");
+
+        assert_eq!(ts.truncate_to(Duration::from_secs(3600)), ts);
+    }
+
+    #[test]
+    fn truncate_to_floors_pre_epoch_values_downward() {
+        let ts = Timestamp::from_epoch_secs(-30).expect("timestamp");
+
+        assert_eq!(
+            ts.truncate_to(Duration::from_secs(60)),
+            Timestamp::from_epoch_secs(-60).expect("timestamp")
+        );
+    }
+
+    #[test]
+    fn now_survives_millis_round_trip() {
+        let now = Timestamp::now();
+
+        assert_eq!(
+            Timestamp::from_epoch_millis(now.as_epoch_millis()).expect("timestamp"),
+            now
+        );
+    }
+
+    #[test]
+    fn truncate_to_millis_drops_sub_millisecond_precision() {
+        let ts = Timestamp(
+            chrono::DateTime::from_timestamp(1_700_000_000, 123_456_789).expect("timestamp"),
+        );
+
+        assert_eq!(
+            ts.truncate_to_millis(),
+            Timestamp::from_epoch_millis(1_700_000_000_123).expect("timestamp")
+        );
+    }
+
+    #[test]
+    fn truncate_to_zero_granularity_is_identity() {
+        let ts = Timestamp::from_epoch_millis(1_700_000_000_123).expect("timestamp");
+
+        assert_eq!(ts.truncate_to(Duration::ZERO), ts);
+    }
+}
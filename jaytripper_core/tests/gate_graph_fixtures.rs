@@ -0,0 +1,26 @@
+use std::{fs, path::PathBuf};
+
+use jaytripper_core::{GateGraph, ids::SolarSystemId};
+
+fn read_fixture(name: &str) -> String {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("gate_graph")
+        .join(name);
+    fs::read_to_string(path).expect("fixture should be readable")
+}
+
+#[test]
+fn sde_jumps_fixture_flags_non_adjacent_jump() {
+    let graph = GateGraph::parse_sde_jumps_csv(&read_fixture("jumps.csv"))
+        .expect("jumps fixture should parse");
+
+    assert!(graph.are_adjacent(SolarSystemId(30000142), SolarSystemId(30000144)));
+    assert!(graph.are_adjacent(SolarSystemId(30000140), SolarSystemId(30000139)));
+    assert!(
+        !graph.are_adjacent(SolarSystemId(30000142), SolarSystemId(30000145)),
+        "two-hop transition should not be treated as adjacent"
+    );
+    assert!(!graph.contains_system(SolarSystemId(31000001)));
+}
@@ -110,3 +110,64 @@ fn parses_all_blocks_in_multi_snapshot_fixture() {
         );
     }
 }
+
+#[test]
+fn trailing_tab_padding_parses_like_unpadded_rows() {
+    let unpadded = parse_signature_snapshot(&read_fixture("padded_5_columns.txt"))
+        .expect("5-column snapshot should parse");
+    let padded = parse_signature_snapshot(&read_fixture("padded_8_columns.txt"))
+        .expect("8-column snapshot should parse");
+
+    assert_eq!(unpadded.len(), 11);
+    assert_eq!(padded, unpadded);
+}
+
+#[test]
+fn any_number_of_trailing_empty_columns_is_trimmed() {
+    let row = "CWT-368\tCosmic Signature\tGas Site\tReservoir\t42.5%";
+    let expected = parse_signature_snapshot(row).expect("unpadded row should parse");
+
+    for padding in 1..=8 {
+        let padded = format!("{row}{}", "\t".repeat(padding));
+        assert_eq!(
+            parse_signature_snapshot(&padded).expect("padded row should parse"),
+            expected,
+            "{padding} trailing tabs"
+        );
+    }
+}
+
+#[test]
+fn anomaly_rows_without_percent_column_parse_as_fully_scanned() {
+    let entries = parse_signature_snapshot(&read_fixture("anomalies_4_columns.txt"))
+        .expect("anomaly snapshot should parse");
+
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0].signature_id, "DVQ-434");
+    assert_eq!(entries[0].name.as_deref(), Some("Anomaly Training Site"));
+    assert_eq!(entries[0].scan_percent, Some(100.0));
+    assert_eq!(entries[1].scan_percent, Some(100.0));
+    assert_eq!(entries[2].scan_percent, Some(42.5));
+}
+
+#[test]
+fn padded_anomaly_rows_without_percent_column_parse_as_fully_scanned() {
+    let entries = parse_signature_snapshot(
+        "DVQ-434\tCosmic Anomaly\tCombat Site\tAnomaly Training Site\t\t\t",
+    )
+    .expect("padded anomaly row should parse");
+
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].scan_percent, Some(100.0));
+}
+
+#[test]
+fn signature_rows_still_require_percent_column() {
+    let err = parse_signature_snapshot("CWT-368\tCosmic Signature\tGas Site\tReservoir")
+        .expect_err("4-column signature should fail");
+
+    assert!(matches!(
+        err,
+        SignatureParseError::InvalidColumnCount { actual: 4, .. }
+    ));
+}
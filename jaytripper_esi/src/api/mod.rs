@@ -1,3 +1,3 @@
 pub mod types;
 
-pub use types::CharacterLocation;
+pub use types::{CharacterLocation, DockLocation, TokenVerification};
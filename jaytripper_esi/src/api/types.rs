@@ -1,8 +1,85 @@
-use jaytripper_core::ids::{SolarSystemId, StationId, StructureId};
+use jaytripper_core::ids::{CharacterId, SolarSystemId, StationId, StructureId};
 
+/// ESI sets at most one of `station_id` and `structure_id`; neither means the
+/// character is in space. Read them through [`Self::docked_at`].
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct CharacterLocation {
     pub solar_system_id: SolarSystemId,
     pub station_id: Option<StationId>,
     pub structure_id: Option<StructureId>,
 }
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DockLocation {
+    Station(StationId),
+    Structure(StructureId),
+}
+
+impl CharacterLocation {
+    /// Where the character is docked, or `None` in space. If ESI reports both
+    /// ids, the structure wins: structures are the newer, more specific
+    /// field, and the pair is logged since it should not happen.
+    pub fn docked_at(&self) -> Option<DockLocation> {
+        match (self.station_id, self.structure_id) {
+            (None, None) => None,
+            (Some(station_id), None) => Some(DockLocation::Station(station_id)),
+            (None, Some(structure_id)) => Some(DockLocation::Structure(structure_id)),
+            (Some(station_id), Some(structure_id)) => {
+                log::warn!(
+                    "ESI reported both station {station_id} and structure {structure_id} in system {}; using the structure",
+                    self.solar_system_id
+                );
+                Some(DockLocation::Structure(structure_id))
+            }
+        }
+    }
+}
+
+/// What ESI reports about the token it was called with, as opposed to the
+/// metadata stored alongside the session.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokenVerification {
+    pub character_id: CharacterId,
+    pub character_name: String,
+    pub scopes: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use jaytripper_core::ids::{SolarSystemId, StationId, StructureId};
+
+    use super::{CharacterLocation, DockLocation};
+
+    fn location(station_id: Option<i32>, structure_id: Option<i64>) -> CharacterLocation {
+        CharacterLocation {
+            solar_system_id: SolarSystemId(30000142),
+            station_id: station_id.map(StationId),
+            structure_id: structure_id.map(StructureId),
+        }
+    }
+
+    #[test]
+    fn no_dock_ids_means_in_space() {
+        assert_eq!(location(None, None).docked_at(), None);
+    }
+
+    #[test]
+    fn single_dock_id_is_returned_typed() {
+        assert_eq!(
+            location(Some(60003760), None).docked_at(),
+            Some(DockLocation::Station(StationId(60003760)))
+        );
+        assert_eq!(
+            location(None, Some(1_000_000_000_001)).docked_at(),
+            Some(DockLocation::Structure(StructureId(1_000_000_000_001)))
+        );
+    }
+
+    #[test]
+    fn both_dock_ids_prefer_the_structure() {
+        assert_eq!(
+            location(Some(60003760), Some(1_000_000_000_001)).docked_at(),
+            Some(DockLocation::Structure(StructureId(1_000_000_000_001)))
+        );
+    }
+}
@@ -1,13 +1,33 @@
-use std::time::Duration;
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock, Mutex},
+    time::Duration,
+};
 
-use jaytripper_core::{ids::CharacterId, time::Timestamp};
+use jaytripper_core::ids::CharacterId;
+pub use jaytripper_core::time::{Clock, SystemClock};
+use tokio::sync::Mutex as AsyncMutex;
 
 use super::types::{AuthSession, LoginRequest};
 use crate::{
-    EsiError, EsiResult, client::SsoAuthClient, esi_client::ManagedEsiClient,
+    EsiError, EsiResult,
+    api::TokenVerification,
+    client::{EsiApiClient, SsoAuthClient, with_optional_request_timeout},
+    esi_client::ManagedEsiClient,
     token_store::TokenStore,
 };
 
+/// One lock per character, shared by every [`AuthService`] in the process, so
+/// services that share a token store (e.g. the tracker and a one-off wallet
+/// read) never spend the same refresh token twice.
+static REFRESH_GUARDS: LazyLock<Mutex<HashMap<CharacterId, Arc<AsyncMutex<()>>>>> =
+    LazyLock::new(Default::default);
+
+fn refresh_guard(character_id: CharacterId) -> Arc<AsyncMutex<()>> {
+    let mut guards = REFRESH_GUARDS.lock().expect("refresh guards lock");
+    Arc::clone(guards.entry(character_id).or_default())
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum EnsureSessionResult {
     Missing,
@@ -15,19 +35,6 @@ pub enum EnsureSessionResult {
     NeedsReauth { reason: String },
 }
 
-pub trait Clock {
-    fn now(&self) -> Timestamp;
-}
-
-#[derive(Clone, Copy, Debug, Default)]
-pub struct SystemClock;
-
-impl Clock for SystemClock {
-    fn now(&self) -> Timestamp {
-        Timestamp::now()
-    }
-}
-
 pub struct AuthService<C, S, T = SystemClock>
 where
     C: SsoAuthClient,
@@ -84,14 +91,6 @@ where
         let now = self.clock.now();
         let tokens = self.client.exchange_code(code, callback_state).await?;
 
-        let missing_scopes = missing_required_scopes(&tokens.scopes, &self.required_scopes);
-        if !missing_scopes.is_empty() {
-            self.store.clear_session(tokens.character_id)?;
-            return Err(EsiError::MissingRequiredScopes {
-                missing: missing_scopes,
-            });
-        }
-
         let session = AuthSession {
             character_id: tokens.character_id,
             character_name: tokens.character_name,
@@ -102,6 +101,14 @@ where
             updated_at: now,
         };
 
+        let missing_scopes = missing_required_scopes(&session, &self.required_scopes);
+        if !missing_scopes.is_empty() {
+            self.store.clear_session(session.character_id)?;
+            return Err(EsiError::MissingRequiredScopes {
+                missing: missing_scopes,
+            });
+        }
+
         self.persist_and_hydrate_session(&session)?;
         Ok(session)
     }
@@ -114,16 +121,28 @@ where
         self.store.clear_session(character_id)
     }
 
+    /// Returns a usable session, refreshing it first if it is about to
+    /// expire.
+    ///
+    /// Only a refresh SSO rejects outright (see [`EsiError::is_retryable`])
+    /// yields [`EnsureSessionResult::NeedsReauth`]. Timeouts, transport
+    /// failures and 5xx responses are returned as errors and leave the stored
+    /// session untouched, so the caller can retry.
+    ///
+    /// Refreshes for a character are serialized across every service in the
+    /// process, and the session is re-read from the store once it is this
+    /// call's turn, so a caller that waited finds the session another service
+    /// just refreshed instead of spending the refresh token again.
     pub async fn ensure_valid_session(
         &mut self,
         character_id: CharacterId,
     ) -> EsiResult<EnsureSessionResult> {
         let now = self.clock.now();
-        let Some(mut session) = self.store.load_session(character_id)? else {
+        let Some(session) = self.store.load_session(character_id)? else {
             return Ok(EnsureSessionResult::Missing);
         };
 
-        let missing_scopes = missing_required_scopes(&session.scopes, &self.required_scopes);
+        let missing_scopes = missing_required_scopes(&session, &self.required_scopes);
         if !missing_scopes.is_empty() {
             self.store.clear_session(character_id)?;
             return Ok(EnsureSessionResult::NeedsReauth {
@@ -139,15 +158,31 @@ where
             return Ok(EnsureSessionResult::Ready(session));
         }
 
-        match self.client.refresh(&session.refresh_token).await {
+        let guard = refresh_guard(character_id);
+        let _refreshing = guard.lock().await;
+        let Some(mut session) = self.store.load_session(character_id)? else {
+            return Ok(EnsureSessionResult::Missing);
+        };
+        if !session.should_refresh(now, self.refresh_skew) {
+            self.hydrate_session(&session)?;
+            return Ok(EnsureSessionResult::Ready(session));
+        }
+
+        let request_timeout = self.client.request_timeout();
+        let refreshed = with_optional_request_timeout(
+            "refresh",
+            request_timeout,
+            self.client.refresh(&session.refresh_token),
+        )
+        .await;
+        match refreshed {
             Ok(tokens) => {
                 session.access_token = tokens.access_token;
                 session.access_expires_at = tokens.access_expires_at;
                 session.refresh_token = tokens.refresh_token;
                 session.updated_at = now;
 
-                let missing_scopes =
-                    missing_required_scopes(&session.scopes, &self.required_scopes);
+                let missing_scopes = missing_required_scopes(&session, &self.required_scopes);
                 if !missing_scopes.is_empty() {
                     self.store.clear_session(character_id)?;
                     return Ok(EnsureSessionResult::NeedsReauth {
@@ -161,8 +196,9 @@ where
                 self.persist_and_hydrate_session(&session)?;
                 Ok(EnsureSessionResult::Ready(session))
             }
+            Err(err) if err.is_retryable() => Err(err),
             Err(err) => Ok(EnsureSessionResult::NeedsReauth {
-                reason: err.to_string(),
+                reason: err.redacted_display(),
             }),
         }
     }
@@ -178,7 +214,7 @@ where
             });
         };
 
-        let missing_scopes = missing_required_scopes(&session.scopes, &self.required_scopes);
+        let missing_scopes = missing_required_scopes(&session, &self.required_scopes);
         if !missing_scopes.is_empty() {
             self.store.clear_session(character_id)?;
             return Ok(NextRefreshDelay::NeedsReauth {
@@ -234,18 +270,55 @@ where
         character_id: CharacterId,
     ) -> EsiResult<ManagedEsiClient<C, S, T>>
     where
-        C: crate::client::EsiApiClient,
+        C: EsiApiClient,
     {
         ManagedEsiClient::connect(self, character_id).await
     }
 }
 
+impl<C, S, T> AuthService<C, S, T>
+where
+    C: SsoAuthClient + EsiApiClient,
+    S: TokenStore,
+    T: Clock,
+{
+    /// Ensures the session is valid, then asks ESI which character and scopes
+    /// the token really grants, to catch drift from the stored metadata.
+    pub async fn verify_session(
+        &mut self,
+        character_id: CharacterId,
+    ) -> EsiResult<TokenVerification> {
+        match self.ensure_valid_session(character_id).await? {
+            EnsureSessionResult::Ready(_) => {}
+            EnsureSessionResult::Missing => {
+                return Err(EsiError::NeedsReauth {
+                    reason: "session missing for selected character".to_string(),
+                });
+            }
+            EnsureSessionResult::NeedsReauth { reason } => {
+                return Err(EsiError::NeedsReauth { reason });
+            }
+        }
+
+        let request_timeout = self.client.request_timeout();
+        with_optional_request_timeout("verify_token", request_timeout, self.client.verify_token())
+            .await
+    }
+}
+
 impl<C, S, T> AuthService<C, S, T>
 where
     C: SsoAuthClient,
     S: TokenStore,
     T: Clock,
 {
+    /// The token store this service was configured with, e.g. to enumerate
+    /// sessions. Writes made through it bypass the scope checks applied by
+    /// [`Self::complete_login`] and [`Self::ensure_valid_session`].
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
     pub(crate) fn client_mut(&mut self) -> &mut C {
         &mut self.client
     }
@@ -258,17 +331,18 @@ pub enum NextRefreshDelay {
     NeedsReauth { reason: String },
 }
 
-fn missing_required_scopes(granted_scopes: &[String], required_scopes: &[String]) -> Vec<String> {
+fn missing_required_scopes(session: &AuthSession, required_scopes: &[String]) -> Vec<String> {
+    let granted_scopes = session.scope_set();
     required_scopes
         .iter()
-        .filter(|required| !granted_scopes.iter().any(|granted| granted == *required))
+        .filter(|required| !granted_scopes.contains(required.as_str()))
         .cloned()
         .collect()
 }
 
 #[cfg(test)]
 mod tests {
-    use std::{collections::HashMap, sync::Mutex, time::Duration};
+    use std::time::Duration;
 
     use async_trait::async_trait;
     use jaytripper_core::{ids::CharacterId, time::Timestamp};
@@ -276,51 +350,12 @@ mod tests {
     use super::AuthSession;
     use crate::{
         EsiError, EsiResult,
-        auth::{AuthService, Clock, EnsureSessionResult},
+        auth::{AuthService, EnsureSessionResult},
         client::{InitialAuthTokens, RefreshTokens, SsoAuthClient},
+        test_util::{FixedClock, MemoryTokenStore, StubEsiApiClient},
         token_store::TokenStore,
     };
 
-    #[derive(Clone, Copy)]
-    struct FixedClock {
-        now: Timestamp,
-    }
-
-    impl Clock for FixedClock {
-        fn now(&self) -> Timestamp {
-            self.now
-        }
-    }
-
-    #[derive(Default)]
-    struct MemoryStore {
-        sessions: Mutex<HashMap<CharacterId, AuthSession>>,
-    }
-
-    impl TokenStore for MemoryStore {
-        fn load_session(&self, character_id: CharacterId) -> EsiResult<Option<AuthSession>> {
-            Ok(self
-                .sessions
-                .lock()
-                .expect("lock")
-                .get(&character_id)
-                .cloned())
-        }
-
-        fn save_session(&self, session: &AuthSession) -> EsiResult<()> {
-            self.sessions
-                .lock()
-                .expect("lock")
-                .insert(session.character_id, session.clone());
-            Ok(())
-        }
-
-        fn clear_session(&self, character_id: CharacterId) -> EsiResult<()> {
-            self.sessions.lock().expect("lock").remove(&character_id);
-            Ok(())
-        }
-    }
-
     struct MockClient {
         login_request: Option<crate::auth::LoginRequest>,
         initial_tokens: Option<InitialAuthTokens>,
@@ -390,12 +425,12 @@ mod tests {
             refresh_result: None,
             hydrated_access_tokens: Vec::new(),
         };
-        let store = MemoryStore::default();
+        let store = MemoryTokenStore::default();
         let mut service = AuthService::with_clock(
             client,
             store,
             vec!["esi-location.read_location.v1".to_string()],
-            FixedClock { now: ts(777) },
+            FixedClock(ts(777)),
         );
 
         let session = service
@@ -429,7 +464,7 @@ mod tests {
             refresh_result: None,
             hydrated_access_tokens: Vec::new(),
         };
-        let store = MemoryStore::default();
+        let store = MemoryTokenStore::default();
         store
             .save_session(&sample_session(ts(10_000)))
             .expect("save should work");
@@ -437,7 +472,7 @@ mod tests {
             client,
             store,
             vec!["esi-location.read_location.v1".to_string()],
-            FixedClock { now: ts(777) },
+            FixedClock(ts(777)),
         );
 
         let err = service
@@ -455,6 +490,46 @@ mod tests {
         assert!(service.client.hydrated_access_tokens.is_empty());
     }
 
+    #[tokio::test]
+    async fn store_accessor_reads_sessions_saved_through_the_service() {
+        let client = MockClient {
+            login_request: None,
+            initial_tokens: Some(InitialAuthTokens {
+                character_id: CharacterId(9001),
+                character_name: Some("Pilot".to_string()),
+                scopes: vec!["esi-location.read_location.v1".to_string()],
+                access_token: "new-access".to_string(),
+                access_expires_at: ts(1000),
+                refresh_token: "new-refresh".to_string(),
+            }),
+            refresh_result: None,
+            hydrated_access_tokens: Vec::new(),
+        };
+        let mut service = AuthService::with_clock(
+            client,
+            MemoryTokenStore::default(),
+            vec!["esi-location.read_location.v1".to_string()],
+            FixedClock(ts(777)),
+        );
+
+        let session = service
+            .complete_login("code", "state")
+            .await
+            .expect("complete login should succeed");
+
+        assert_eq!(
+            service
+                .store()
+                .load_session(CharacterId(9001))
+                .expect("load should work"),
+            Some(session.clone())
+        );
+        assert_eq!(
+            service.store().list_sessions().expect("list should work"),
+            vec![session]
+        );
+    }
+
     #[tokio::test]
     async fn ensure_valid_session_returns_existing_without_refresh() {
         let client = MockClient {
@@ -463,7 +538,7 @@ mod tests {
             refresh_result: None,
             hydrated_access_tokens: Vec::new(),
         };
-        let store = MemoryStore::default();
+        let store = MemoryTokenStore::default();
         store
             .save_session(&sample_session(ts(10_000)))
             .expect("save should work");
@@ -471,7 +546,7 @@ mod tests {
             client,
             store,
             vec!["esi-location.read_location.v1".to_string()],
-            FixedClock { now: ts(500) },
+            FixedClock(ts(500)),
         )
         .with_refresh_skew(Duration::from_secs(60));
 
@@ -496,7 +571,7 @@ mod tests {
             })),
             hydrated_access_tokens: Vec::new(),
         };
-        let store = MemoryStore::default();
+        let store = MemoryTokenStore::default();
         store
             .save_session(&sample_session(ts(510)))
             .expect("save should work");
@@ -504,7 +579,7 @@ mod tests {
             client,
             store,
             vec!["esi-location.read_location.v1".to_string()],
-            FixedClock { now: ts(500) },
+            FixedClock(ts(500)),
         )
         .with_refresh_skew(Duration::from_secs(60));
 
@@ -524,15 +599,63 @@ mod tests {
         );
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn separate_services_sharing_a_store_refresh_once() {
+        let character_id = CharacterId(9101);
+        let stub = StubEsiApiClient::new();
+        // Long enough that the second service asks while the first refresh is
+        // still in flight.
+        stub.set_refresh_delay(Duration::from_secs(1));
+        stub.push_refresh(Ok(RefreshTokens {
+            access_token: "refreshed-access".to_string(),
+            access_expires_at: ts(10_000),
+            refresh_token: "refreshed-refresh".to_string(),
+        }));
+        let store = MemoryTokenStore::default();
+        store
+            .save_session(&AuthSession {
+                character_id,
+                ..sample_session(ts(510))
+            })
+            .expect("save should work");
+        let service = || {
+            AuthService::with_clock(
+                stub.clone(),
+                store.clone(),
+                vec!["esi-location.read_location.v1".to_string()],
+                FixedClock(ts(500)),
+            )
+        };
+        let ensure = |mut service: AuthService<StubEsiApiClient, MemoryTokenStore, FixedClock>| {
+            tokio::spawn(async move { service.ensure_valid_session(character_id).await })
+        };
+
+        let (first, second) = tokio::join!(ensure(service()), ensure(service()));
+
+        // Only one refresh is scripted; a second one would fail.
+        for result in [first, second] {
+            let EnsureSessionResult::Ready(session) = result
+                .expect("join ensure task")
+                .expect("ensure should succeed")
+            else {
+                panic!("expected ready session");
+            };
+            assert_eq!(session.access_token, "refreshed-access");
+        }
+        assert_eq!(stub.refresh_calls(), 1);
+    }
+
     #[tokio::test]
     async fn ensure_valid_session_requests_reauth_when_refresh_fails() {
         let client = MockClient {
             login_request: None,
             initial_tokens: None,
-            refresh_result: Some(Err(EsiError::message("refresh token rejected"))),
+            refresh_result: Some(Err(EsiError::from(
+                rfesi::prelude::EsiError::InvalidStatusCode(400),
+            ))),
             hydrated_access_tokens: Vec::new(),
         };
-        let store = MemoryStore::default();
+        let store = MemoryTokenStore::default();
         store
             .save_session(&sample_session(ts(510)))
             .expect("save should work");
@@ -540,7 +663,7 @@ mod tests {
             client,
             store,
             vec!["esi-location.read_location.v1".to_string()],
-            FixedClock { now: ts(500) },
+            FixedClock(ts(500)),
         )
         .with_refresh_skew(Duration::from_secs(60));
 
@@ -552,7 +675,7 @@ mod tests {
         let EnsureSessionResult::NeedsReauth { reason } = result else {
             panic!("expected needs reauth");
         };
-        assert!(reason.contains("refresh token rejected"));
+        assert!(reason.contains("400"), "{reason}");
         assert!(
             service
                 .load_session(CharacterId(9001))
@@ -569,7 +692,7 @@ mod tests {
             refresh_result: None,
             hydrated_access_tokens: Vec::new(),
         };
-        let store = MemoryStore::default();
+        let store = MemoryTokenStore::default();
         store
             .save_session(&sample_session(ts(10_000)))
             .expect("save should work");
@@ -577,7 +700,7 @@ mod tests {
             client,
             store,
             vec!["esi-location.read_ship_type.v1".to_string()],
-            FixedClock { now: ts(500) },
+            FixedClock(ts(500)),
         )
         .with_refresh_skew(Duration::from_secs(60));
 
@@ -1,4 +1,4 @@
-use std::time::Duration;
+use std::{collections::HashSet, fmt, time::Duration};
 
 use jaytripper_core::{ids::CharacterId, time::Timestamp};
 use serde::{Deserialize, Serialize};
@@ -9,7 +9,8 @@ pub struct LoginRequest {
     pub state: String,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+/// Persisted SSO session. `Debug` redacts both tokens; serde keeps them.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct AuthSession {
     pub character_id: CharacterId,
     pub character_name: Option<String>,
@@ -27,4 +28,80 @@ impl AuthSession {
             None => true,
         }
     }
+
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|granted| granted == scope)
+    }
+
+    /// Granted scopes as a set, for checking several scopes at once.
+    pub fn scope_set(&self) -> HashSet<&str> {
+        self.scopes.iter().map(String::as_str).collect()
+    }
+}
+
+const REDACTED: &str = "<redacted>";
+
+impl fmt::Debug for AuthSession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuthSession")
+            .field("character_id", &self.character_id)
+            .field("character_name", &self.character_name)
+            .field("scopes", &self.scopes)
+            .field("access_token", &REDACTED)
+            .field("access_expires_at", &self.access_expires_at)
+            .field("refresh_token", &REDACTED)
+            .field("updated_at", &self.updated_at)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jaytripper_core::{ids::CharacterId, time::Timestamp};
+
+    use super::AuthSession;
+
+    fn session() -> AuthSession {
+        AuthSession {
+            character_id: CharacterId(90000001),
+            character_name: Some("Pilot".to_owned()),
+            scopes: vec!["esi-location.read_location.v1".to_owned()],
+            access_token: "access-secret".to_owned(),
+            access_expires_at: Timestamp::from_epoch_secs(1_700_000_000).expect("timestamp"),
+            refresh_token: "refresh-secret".to_owned(),
+            updated_at: Timestamp::from_epoch_secs(1_700_000_000).expect("timestamp"),
+        }
+    }
+
+    #[test]
+    fn debug_output_redacts_tokens() {
+        let rendered = format!("{:?}", session());
+
+        assert!(!rendered.contains("access-secret"));
+        assert!(!rendered.contains("refresh-secret"));
+        assert!(rendered.contains("access_token: \"<redacted>\""));
+        assert!(rendered.contains("Pilot"));
+    }
+
+    #[test]
+    fn scope_helpers_report_granted_scopes_only() {
+        let session = session();
+
+        assert!(session.has_scope("esi-location.read_location.v1"));
+        assert!(!session.has_scope("esi-location.read_online.v1"));
+        assert!(
+            session
+                .scope_set()
+                .contains("esi-location.read_location.v1")
+        );
+        assert!(!session.scope_set().contains("publicData"));
+    }
+
+    #[test]
+    fn serde_round_trip_keeps_tokens() {
+        let json = serde_json::to_string(&session()).expect("serialize");
+        let decoded: AuthSession = serde_json::from_str(&json).expect("deserialize");
+
+        assert_eq!(decoded, session());
+    }
 }
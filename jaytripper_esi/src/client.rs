@@ -1,12 +1,29 @@
+use std::{collections::VecDeque, future::Future, time::Duration};
+
 use async_trait::async_trait;
 use jaytripper_core::{
     ids::{CharacterId, SolarSystemId, StationId, StructureId},
     time::Timestamp,
 };
-use rfesi::prelude::{Esi, EsiBuilder, PkceVerifier, TokenClaims};
+use rfesi::prelude::{Esi, EsiBuilder, PkceVerifier, RequestType, TokenClaims};
 use serde::Deserialize;
 
-use crate::{EsiError, EsiResult, api::CharacterLocation, auth::LoginRequest, config::EsiConfig};
+use crate::{
+    EsiError, EsiResult,
+    api::{CharacterLocation, TokenVerification},
+    auth::{Clock, LoginRequest, SystemClock},
+    config::EsiConfig,
+};
+
+/// How long a `begin_login` attempt stays valid for `exchange_code`.
+pub const DEFAULT_LOGIN_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Scope ESI requires for reading a character's wallet balance. It is not
+/// part of the default scope set; wallet reads are opt-in.
+pub const WALLET_READ_SCOPE: &str = "esi-wallet.read_character_wallet.v1";
+
+/// How many concurrent `begin_login` attempts (e.g. browser tabs) are kept.
+const MAX_PENDING_LOGINS: usize = 4;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct InitialAuthTokens {
@@ -40,6 +57,13 @@ pub trait SsoAuthClient {
         callback_state: &str,
     ) -> EsiResult<InitialAuthTokens>;
     async fn refresh(&mut self, refresh_token: &str) -> EsiResult<RefreshTokens>;
+
+    /// Upper bound for each network call made through this client. Callers
+    /// in this crate apply it around refreshes and API calls; `None` leaves
+    /// them unbounded.
+    fn request_timeout(&self) -> Option<Duration> {
+        None
+    }
 }
 
 #[async_trait]
@@ -55,32 +79,110 @@ pub trait EsiApiClient {
         &mut self,
         character_id: CharacterId,
     ) -> EsiResult<CharacterLocation>;
+
+    /// Asks ESI which character and scopes the current access token grants.
+    async fn verify_token(&mut self) -> EsiResult<TokenVerification>;
+
+    /// Reads the character's wallet balance in ISK. The token must carry
+    /// [`WALLET_READ_SCOPE`].
+    async fn get_wallet_balance(&mut self, character_id: CharacterId) -> EsiResult<f64>;
+
+    /// Public station lookup.
+    async fn get_station_name(&mut self, station_id: StationId) -> EsiResult<String>;
+
+    /// Structure lookup; ESI answers 403 unless the character is on the
+    /// structure's access list.
+    async fn get_structure_name(&mut self, structure_id: StructureId) -> EsiResult<String>;
 }
 
 #[derive(Debug)]
 struct PendingPkceState {
     state: String,
     verifier: Option<PkceVerifier>,
+    expires_at: Timestamp,
+}
+
+impl PendingPkceState {
+    fn is_expired(&self, now: Timestamp) -> bool {
+        now >= self.expires_at
+    }
 }
 
 pub struct RfesiSsoClient {
     esi: Esi,
-    pending: Option<PendingPkceState>,
+    pending: VecDeque<PendingPkceState>,
+    clock: Box<dyn Clock + Send + Sync>,
+    login_ttl: Duration,
+    request_timeout: Duration,
+}
+
+/// Builder for the rfesi client described by `config`, pointed at any
+/// overridden API base URL.
+pub(crate) fn esi_builder(config: &EsiConfig) -> EsiBuilder {
+    let mut builder = EsiBuilder::new()
+        .user_agent(&config.user_agent)
+        .client_id(&config.client_id)
+        .callback_url(&config.callback_url)
+        .enable_application_authentication(true)
+        .scope(&config.scopes_for_esi());
+    if let Some(base_url) = &config.base_url {
+        builder = builder.base_api_url(base_url);
+    }
+    builder
 }
 
 impl RfesiSsoClient {
     pub fn new(config: &EsiConfig) -> EsiResult<Self> {
         config.validate()?;
 
-        let esi = EsiBuilder::new()
-            .user_agent(&config.user_agent)
-            .client_id(&config.client_id)
-            .callback_url(&config.callback_url)
-            .enable_application_authentication(true)
-            .scope(&config.scopes_for_esi())
-            .build()?;
+        let esi = esi_builder(config).build()?;
+
+        Ok(Self {
+            esi,
+            pending: VecDeque::new(),
+            clock: Box::new(SystemClock),
+            login_ttl: DEFAULT_LOGIN_TTL,
+            request_timeout: config.request_timeout,
+        })
+    }
+
+    pub fn with_clock(mut self, clock: impl Clock + Send + Sync + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
 
-        Ok(Self { esi, pending: None })
+    pub fn with_login_ttl(mut self, login_ttl: Duration) -> Self {
+        self.login_ttl = login_ttl;
+        self
+    }
+
+    /// Removes and returns the pending login whose state matches the
+    /// callback. Unmatched attempts stay pending so another tab can finish.
+    fn take_pending(&mut self, callback_state: &str) -> EsiResult<PendingPkceState> {
+        if self.pending.is_empty() {
+            return Err(EsiError::LoginNotStarted);
+        }
+
+        let index = self
+            .pending
+            .iter()
+            .position(|pending| pending.state == callback_state)
+            .ok_or_else(|| EsiError::UnknownLoginState {
+                got: callback_state.to_owned(),
+                pending: self.pending.len(),
+            })?;
+        let pending = self
+            .pending
+            .remove(index)
+            .expect("index comes from position");
+
+        if pending.is_expired(self.clock.now()) {
+            return Err(EsiError::LoginExpired {
+                expired_at: pending.expires_at,
+            });
+        }
+
+        Ok(pending)
     }
 
     fn read_access_expiry(&self) -> EsiResult<Timestamp> {
@@ -120,10 +222,19 @@ impl RfesiSsoClient {
 impl SsoAuthClient for RfesiSsoClient {
     fn begin_login(&mut self) -> EsiResult<LoginRequest> {
         let auth_info = self.esi.get_authorize_url()?;
-
-        self.pending = Some(PendingPkceState {
+        let now = self.clock.now();
+        let expires_at = now
+            .checked_add(self.login_ttl)
+            .ok_or(EsiError::InvalidConfig("login ttl overflows timestamp"))?;
+
+        self.pending.retain(|pending| !pending.is_expired(now));
+        if self.pending.len() == MAX_PENDING_LOGINS {
+            self.pending.pop_front();
+        }
+        self.pending.push_back(PendingPkceState {
             state: auth_info.state.clone(),
             verifier: auth_info.pkce_verifier,
+            expires_at,
         });
 
         Ok(LoginRequest {
@@ -149,14 +260,7 @@ impl SsoAuthClient for RfesiSsoClient {
         code: &str,
         callback_state: &str,
     ) -> EsiResult<InitialAuthTokens> {
-        let pending = self.pending.take().ok_or(EsiError::LoginNotStarted)?;
-
-        if callback_state != pending.state {
-            return Err(EsiError::StateMismatch {
-                expected: pending.state,
-                got: callback_state.to_string(),
-            });
-        }
+        let pending = self.take_pending(callback_state)?;
 
         let claims = self
             .esi
@@ -186,6 +290,10 @@ impl SsoAuthClient for RfesiSsoClient {
             refresh_token: self.read_refresh_token()?,
         })
     }
+
+    fn request_timeout(&self) -> Option<Duration> {
+        Some(self.request_timeout)
+    }
 }
 
 #[async_trait]
@@ -210,6 +318,90 @@ impl EsiApiClient for RfesiSsoClient {
             structure_id: location.structure_id.map(StructureId),
         })
     }
+
+    async fn verify_token(&mut self) -> EsiResult<TokenVerification> {
+        let response: VerifyResponse = self
+            .esi
+            .query("GET", RequestType::Authenticated, "verify/", None, None)
+            .await?;
+        Ok(response.into())
+    }
+
+    async fn get_wallet_balance(&mut self, character_id: CharacterId) -> EsiResult<f64> {
+        self.ensure_spec_loaded().await?;
+
+        let character_id = i32::try_from(character_id.0)
+            .map_err(|_| EsiError::InvalidCharacterId(character_id))?;
+        Ok(self.esi.group_wallet().get_wallet(character_id).await?)
+    }
+
+    async fn get_station_name(&mut self, station_id: StationId) -> EsiResult<String> {
+        self.ensure_spec_loaded().await?;
+
+        let station = self.esi.group_universe().get_station(station_id.0).await?;
+        Ok(station.name)
+    }
+
+    async fn get_structure_name(&mut self, structure_id: StructureId) -> EsiResult<String> {
+        self.ensure_spec_loaded().await?;
+
+        let structure = self
+            .esi
+            .group_universe()
+            .get_structure(structure_id.0)
+            .await?;
+        Ok(structure.name)
+    }
+}
+
+/// Body of ESI's `GET /verify/`.
+#[derive(Debug, Deserialize)]
+struct VerifyResponse {
+    #[serde(rename = "CharacterID")]
+    character_id: u64,
+    #[serde(rename = "CharacterName")]
+    character_name: String,
+    /// Space-separated scope list.
+    #[serde(rename = "Scopes", default)]
+    scopes: String,
+}
+
+impl From<VerifyResponse> for TokenVerification {
+    fn from(response: VerifyResponse) -> Self {
+        Self {
+            character_id: CharacterId(response.character_id),
+            character_name: response.character_name,
+            scopes: response
+                .scopes
+                .split_whitespace()
+                .map(ToOwned::to_owned)
+                .collect(),
+        }
+    }
+}
+
+/// Fails with [`EsiError::Timeout`] if `request` does not finish within
+/// `after`.
+pub(crate) async fn with_request_timeout<T>(
+    operation: &'static str,
+    after: Duration,
+    request: impl Future<Output = EsiResult<T>>,
+) -> EsiResult<T> {
+    tokio::time::timeout(after, request)
+        .await
+        .map_err(|_| EsiError::Timeout { operation, after })?
+}
+
+/// [`with_request_timeout`] when `after` is set; unbounded otherwise.
+pub(crate) async fn with_optional_request_timeout<T>(
+    operation: &'static str,
+    after: Option<Duration>,
+    request: impl Future<Output = EsiResult<T>>,
+) -> EsiResult<T> {
+    match after {
+        Some(after) => with_request_timeout(operation, after, request).await,
+        None => request.await,
+    }
 }
 
 fn parse_character_id(claims: &TokenClaims) -> EsiResult<CharacterId> {
@@ -247,11 +439,295 @@ enum ScopeClaim {
 
 #[cfg(test)]
 mod tests {
-    use jaytripper_core::ids::CharacterId;
-    use rfesi::prelude::TokenClaims;
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicI64, Ordering},
+        },
+        time::Duration,
+    };
+
+    use jaytripper_core::{ids::CharacterId, time::Timestamp};
+    use rfesi::prelude::{EsiBuilder, TokenClaims};
     use serde_json::json;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+        task::JoinHandle,
+    };
+
+    use super::{
+        EsiApiClient, MAX_PENDING_LOGINS, RfesiSsoClient, SsoAuthClient, VerifyResponse,
+        esi_builder, parse_character_id, parse_scopes,
+    };
+    use crate::{DEFAULT_REQUEST_TIMEOUT, EsiConfig, EsiError, TokenVerification, auth::Clock};
+
+    #[derive(Clone, Default)]
+    struct ManualClock {
+        epoch_secs: Arc<AtomicI64>,
+    }
+
+    impl ManualClock {
+        fn set(&self, epoch_secs: i64) {
+            self.epoch_secs.store(epoch_secs, Ordering::SeqCst);
+        }
+    }
+
+    impl Clock for ManualClock {
+        fn now(&self) -> Timestamp {
+            Timestamp::from_epoch_secs(self.epoch_secs.load(Ordering::SeqCst))
+                .expect("valid epoch seconds")
+        }
+    }
+
+    fn test_config() -> EsiConfig {
+        EsiConfig {
+            client_id: "client".to_owned(),
+            callback_url: "http://localhost/callback".to_owned(),
+            scopes: vec!["esi-location.read_location.v1".to_owned()],
+            user_agent: "jaytripper-tests".to_owned(),
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            base_url: None,
+        }
+    }
+
+    fn test_client(clock: &ManualClock) -> RfesiSsoClient {
+        RfesiSsoClient::new(&test_config())
+            .expect("client should build")
+            .with_clock(clock.clone())
+    }
+
+    #[test]
+    fn esi_builder_carries_base_url_override() {
+        let config = EsiConfig {
+            base_url: Some("http://localhost:8080/".to_owned()),
+            ..test_config()
+        };
+
+        assert_eq!(
+            esi_builder(&config),
+            EsiBuilder::new()
+                .user_agent("jaytripper-tests")
+                .client_id("client")
+                .callback_url("http://localhost/callback")
+                .enable_application_authentication(true)
+                .scope("esi-location.read_location.v1")
+                .base_api_url("http://localhost:8080/")
+        );
+        assert_eq!(
+            esi_builder(&test_config()),
+            EsiBuilder::new()
+                .user_agent("jaytripper-tests")
+                .client_id("client")
+                .callback_url("http://localhost/callback")
+                .enable_application_authentication(true)
+                .scope("esi-location.read_location.v1")
+        );
+    }
+
+    #[test]
+    fn config_rejects_malformed_base_url() {
+        for base_url in ["esi.example.com/", "https://esi.example.com"] {
+            let config = EsiConfig {
+                base_url: Some(base_url.to_owned()),
+                ..test_config()
+            };
+            assert!(matches!(config.validate(), Err(EsiError::InvalidConfig(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn exchange_code_rejects_expired_login() {
+        let clock = ManualClock::default();
+        clock.set(1_000);
+        let mut client = test_client(&clock).with_login_ttl(Duration::from_secs(600));
+
+        let login = client.begin_login().expect("begin login");
+        clock.set(1_600);
+
+        let err = client
+            .exchange_code("code", &login.state)
+            .await
+            .expect_err("expired login should be rejected");
+
+        assert!(matches!(err, EsiError::LoginExpired { .. }), "{err:?}");
+    }
 
-    use super::{parse_character_id, parse_scopes};
+    #[test]
+    fn second_login_attempt_state_is_matched() {
+        let clock = ManualClock::default();
+        let mut client = test_client(&clock);
+
+        let first = client.begin_login().expect("first login");
+        let second = client.begin_login().expect("second login");
+        assert_ne!(first.state, second.state);
+
+        let pending = client.take_pending(&second.state).expect("second state");
+        assert_eq!(pending.state, second.state);
+
+        let pending = client.take_pending(&first.state).expect("first state");
+        assert_eq!(pending.state, first.state);
+    }
+
+    #[test]
+    fn unknown_login_state_is_rejected_without_dropping_attempts() {
+        let clock = ManualClock::default();
+        let mut client = test_client(&clock);
+        let login = client.begin_login().expect("begin login");
+
+        let err = client
+            .take_pending("not-a-real-state")
+            .expect_err("unknown state should fail");
+
+        assert!(
+            matches!(&err, EsiError::UnknownLoginState { got, pending: 1 } if got == "not-a-real-state"),
+            "{err:?}"
+        );
+        assert!(client.take_pending(&login.state).is_ok());
+    }
+
+    #[test]
+    fn oldest_login_attempt_is_evicted_when_ring_is_full() {
+        let clock = ManualClock::default();
+        let mut client = test_client(&clock);
+
+        let first = client.begin_login().expect("first login");
+        for _ in 0..MAX_PENDING_LOGINS {
+            client.begin_login().expect("later login");
+        }
+
+        assert!(matches!(
+            client.take_pending(&first.state),
+            Err(EsiError::UnknownLoginState { .. })
+        ));
+    }
+
+    /// Serves one HTTP response per `(path, body)` pair, in order, and
+    /// returns the request lines it saw.
+    async fn serve_json(
+        responses: Vec<(&'static str, &'static str)>,
+    ) -> (String, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind mock esi");
+        let base_url = format!("http://{}", listener.local_addr().expect("mock addr"));
+        let server = tokio::spawn(async move {
+            let mut request_lines = Vec::new();
+            for (path, body) in responses {
+                let (mut socket, _) = listener.accept().await.expect("accept");
+                let mut request = Vec::new();
+                let mut buf = [0; 1024];
+                while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                    let read = socket.read(&mut buf).await.expect("read request");
+                    assert_ne!(read, 0, "request ended before headers");
+                    request.extend_from_slice(&buf[..read]);
+                }
+                let request = String::from_utf8_lossy(&request);
+                let request_line = request.lines().next().unwrap_or_default().to_owned();
+                assert!(
+                    request_line.starts_with(&format!("GET {path} ")),
+                    "{request_line}"
+                );
+                request_lines.push(request_line);
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                socket
+                    .write_all(response.as_bytes())
+                    .await
+                    .expect("write response");
+            }
+            request_lines
+        });
+        (base_url, server)
+    }
+
+    #[tokio::test]
+    async fn wallet_balance_is_parsed_from_esi_response() {
+        let (base_url, server) = serve_json(vec![
+            (
+                "/spec.json",
+                r#"{"paths":{"/characters/{character_id}/wallet/":{"get":{"operationId":"get_characters_character_id_wallet"}}}}"#,
+            ),
+            ("/characters/90000001/wallet/", "1234567.89"),
+        ])
+        .await;
+        let config = EsiConfig {
+            base_url: Some(format!("{base_url}/")),
+            ..test_config()
+        };
+        let mut client = RfesiSsoClient::new(&config).expect("client should build");
+        client.esi = esi_builder(&config)
+            .spec_url(&format!("{base_url}/spec.json"))
+            .build()
+            .expect("esi should build");
+        client
+            .hydrate_session_tokens(
+                "access",
+                Timestamp::from_epoch_secs(4_000_000_000).expect("timestamp"),
+                "refresh",
+            )
+            .expect("hydrate tokens");
+
+        let balance = client
+            .get_wallet_balance(CharacterId(90000001))
+            .await
+            .expect("wallet balance");
+
+        assert_eq!(balance, 1234567.89);
+        assert_eq!(server.await.expect("mock esi").len(), 2);
+    }
+
+    #[tokio::test]
+    async fn exchange_code_without_login_is_rejected() {
+        let clock = ManualClock::default();
+        let mut client = test_client(&clock);
+
+        let err = client
+            .exchange_code("code", "state")
+            .await
+            .expect_err("exchange without login should fail");
+
+        assert!(matches!(err, EsiError::LoginNotStarted));
+    }
+
+    #[test]
+    fn verify_response_maps_character_and_scopes() {
+        let response: VerifyResponse = serde_json::from_value(json!({
+            "CharacterID": 95465499,
+            "CharacterName": "CCP Bartender",
+            "ExpiresOn": "2026-10-16T12:00:00",
+            "Scopes": "publicData esi-location.read_location.v1",
+            "TokenType": "Character",
+            "CharacterOwnerHash": "abc",
+            "IntellectualProperty": "EVE"
+        }))
+        .expect("verify body should parse");
+
+        assert_eq!(
+            TokenVerification::from(response),
+            TokenVerification {
+                character_id: CharacterId(95465499),
+                character_name: "CCP Bartender".to_owned(),
+                scopes: vec![
+                    "publicData".to_owned(),
+                    "esi-location.read_location.v1".to_owned()
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn verify_response_without_scopes_has_none() {
+        let response: VerifyResponse = serde_json::from_value(json!({
+            "CharacterID": 1,
+            "CharacterName": "Pilot"
+        }))
+        .expect("verify body should parse");
+
+        assert!(TokenVerification::from(response).scopes.is_empty());
+    }
 
     #[test]
     fn parses_character_id_from_subject() {
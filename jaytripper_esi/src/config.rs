@@ -1,11 +1,22 @@
+use std::time::Duration;
+
 use crate::{EsiError, EsiResult};
 
+/// Upper bound on a single ESI or SSO HTTP call.
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct EsiConfig {
     pub client_id: String,
     pub callback_url: String,
     pub scopes: Vec<String>,
     pub user_agent: String,
+    /// Applied to location fetches and token refreshes; expiry surfaces as
+    /// the retryable [`EsiError::Timeout`].
+    pub request_timeout: Duration,
+    /// Overrides the ESI API base URL (rfesi defaults to Tranquility's). Must
+    /// end with `/`, since endpoint paths are appended to it.
+    pub base_url: Option<String>,
 }
 
 impl EsiConfig {
@@ -24,6 +35,17 @@ impl EsiConfig {
         if self.user_agent.trim().is_empty() {
             return Err(EsiError::InvalidConfig("user_agent must be set"));
         }
+        if self.request_timeout.is_zero() {
+            return Err(EsiError::InvalidConfig("request_timeout must be non-zero"));
+        }
+        if let Some(base_url) = &self.base_url
+            && (!(base_url.starts_with("https://") || base_url.starts_with("http://"))
+                || !base_url.ends_with('/'))
+        {
+            return Err(EsiError::InvalidConfig(
+                "base_url must be an http(s) URL ending in `/`",
+            ));
+        }
         Ok(())
     }
 
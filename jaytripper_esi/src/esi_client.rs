@@ -1,13 +1,13 @@
 use std::{
     sync::{
-        Arc, Mutex,
+        Arc,
         atomic::{AtomicBool, Ordering},
     },
     time::Duration,
 };
 
 use async_trait::async_trait;
-use jaytripper_core::ids::CharacterId;
+use jaytripper_core::ids::{CharacterId, StationId, StructureId};
 use tokio::{
     sync::{Mutex as AsyncMutex, watch},
     task::JoinHandle,
@@ -18,11 +18,12 @@ use crate::{
     AuthService, EnsureSessionResult, EsiError, EsiResult,
     api::CharacterLocation,
     auth::{Clock, NextRefreshDelay},
-    client::{EsiApiClient, SsoAuthClient},
+    client::{EsiApiClient, SsoAuthClient, WALLET_READ_SCOPE, with_request_timeout},
     token_store::TokenStore,
 };
 
 const DEFAULT_REFRESH_FLOOR: Duration = Duration::from_secs(5);
+const DEFAULT_LOCATION_TIMEOUT: Duration = Duration::from_secs(15);
 
 #[async_trait]
 pub trait EsiClient {
@@ -30,6 +31,21 @@ pub trait EsiClient {
     fn requires_reauth(&self) -> bool;
     fn reauth_reason(&self) -> Option<String>;
     async fn get_current_location(&self) -> EsiResult<CharacterLocation>;
+
+    /// Feed of the reauth reason, set once the client stops being able to
+    /// refresh its session and reset to `None` if it recovers. `None` for
+    /// clients that cannot flag reauth on their own.
+    fn subscribe_reauth(&self) -> Option<watch::Receiver<Option<String>>> {
+        None
+    }
+
+    async fn get_station_name(&self, _station_id: StationId) -> EsiResult<String> {
+        Err(EsiError::message("client does not resolve station names"))
+    }
+
+    async fn get_structure_name(&self, _structure_id: StructureId) -> EsiResult<String> {
+        Err(EsiError::message("client does not resolve structure names"))
+    }
 }
 
 struct ManagedState<C, S, T>
@@ -50,9 +66,10 @@ where
     character_id: CharacterId,
     state: Arc<AsyncMutex<ManagedState<C, S, T>>>,
     needs_reauth: Arc<AtomicBool>,
-    reauth_reason: Arc<Mutex<Option<String>>>,
+    reauth_reason: Arc<watch::Sender<Option<String>>>,
+    location_timeout: Duration,
     shutdown_tx: watch::Sender<bool>,
-    refresh_task: JoinHandle<()>,
+    refresh_task: Option<JoinHandle<()>>,
 }
 
 impl<C, S, T> ManagedEsiClient<C, S, T>
@@ -61,9 +78,35 @@ where
     S: TokenStore + Send + Sync + 'static,
     T: Clock + Send + Sync + 'static,
 {
+    /// Validates the stored session and starts the background refresh loop.
+    ///
+    /// If the session later needs reauth, the loop keeps polling the token
+    /// store and resumes once a session with a new refresh token refreshes
+    /// cleanly, e.g. after a login from another process.
     pub async fn connect(
+        auth_service: AuthService<C, S, T>,
+        character_id: CharacterId,
+    ) -> EsiResult<Self> {
+        Self::connect_inner(auth_service, character_id, false).await
+    }
+
+    /// Like [`Self::connect`], but when SSO rejects the stored refresh token
+    /// the session is also cleared, so the next login starts fresh instead of
+    /// retrying the same dead token. Retryable failures (timeouts, 5xx, see
+    /// [`EsiError::is_retryable`]) are returned as errors and leave the
+    /// session in place. The rejected session would be unusable anyway, but
+    /// clearing it is destructive, which is why this is not the default.
+    pub async fn connect_or_clear(
+        auth_service: AuthService<C, S, T>,
+        character_id: CharacterId,
+    ) -> EsiResult<Self> {
+        Self::connect_inner(auth_service, character_id, true).await
+    }
+
+    async fn connect_inner(
         mut auth_service: AuthService<C, S, T>,
         character_id: CharacterId,
+        clear_on_reauth: bool,
     ) -> EsiResult<Self> {
         match auth_service.ensure_valid_session(character_id).await? {
             EnsureSessionResult::Ready(_) => {}
@@ -72,16 +115,25 @@ where
                     reason: "session missing for selected character".to_string(),
                 });
             }
+            // Only reached for a rejected refresh token; retryable refresh
+            // failures already returned above.
             EnsureSessionResult::NeedsReauth { reason } => {
+                if clear_on_reauth {
+                    auth_service.logout(character_id)?;
+                }
                 return Err(EsiError::NeedsReauth { reason });
             }
         }
 
         auth_service.client_mut().ensure_api_ready().await?;
+        let location_timeout = auth_service
+            .client_mut()
+            .request_timeout()
+            .unwrap_or(DEFAULT_LOCATION_TIMEOUT);
 
         let state = Arc::new(AsyncMutex::new(ManagedState { auth: auth_service }));
         let needs_reauth = Arc::new(AtomicBool::new(false));
-        let reauth_reason = Arc::new(Mutex::new(None));
+        let reauth_reason = Arc::new(watch::Sender::new(None));
         let (shutdown_tx, shutdown_rx) = watch::channel(false);
 
         let refresh_task = tokio::spawn(refresh_loop(
@@ -98,10 +150,79 @@ where
             state,
             needs_reauth,
             reauth_reason,
+            location_timeout,
             shutdown_tx,
-            refresh_task,
+            refresh_task: Some(refresh_task),
         })
     }
+
+    /// Stops the refresh task and returns the inner auth service, e.g. to log
+    /// out or inspect the stored session after tracking ends.
+    pub async fn shutdown(mut self) -> AuthService<C, S, T> {
+        let _ = self.shutdown_tx.send(true);
+        if let Some(refresh_task) = self.refresh_task.take() {
+            refresh_task.abort();
+            let _ = refresh_task.await;
+        }
+
+        let state = Arc::clone(&self.state);
+        drop(self);
+
+        match Arc::try_unwrap(state) {
+            Ok(state) => state.into_inner().auth,
+            Err(_) => unreachable!("refresh task has exited, so no other state handles remain"),
+        }
+    }
+
+    /// Caps how long an API call (location, wallet, names) may hold the
+    /// client lock, and with it how long the refresh loop can be blocked
+    /// behind a slow ESI call. Defaults to the client's
+    /// [`SsoAuthClient::request_timeout`]; this is the only timeout applied to
+    /// those calls.
+    pub fn with_location_timeout(mut self, location_timeout: Duration) -> Self {
+        self.location_timeout = location_timeout;
+        self
+    }
+
+    /// Reads the character's wallet balance in ISK.
+    ///
+    /// Fails with [`EsiError::MissingRequiredScopes`] without calling ESI if
+    /// the stored session was not granted [`WALLET_READ_SCOPE`]; log in again
+    /// with that scope configured to enable it.
+    pub async fn get_wallet_balance(&self) -> EsiResult<f64> {
+        self.check_reauth()?;
+
+        let mut state = self.state.lock().await;
+        let has_wallet_scope = state
+            .auth
+            .load_session(self.character_id)?
+            .is_some_and(|session| session.has_scope(WALLET_READ_SCOPE));
+        if !has_wallet_scope {
+            return Err(EsiError::MissingRequiredScopes {
+                missing: vec![WALLET_READ_SCOPE.to_owned()],
+            });
+        }
+
+        with_request_timeout(
+            "get_wallet_balance",
+            self.location_timeout,
+            state
+                .auth
+                .client_mut()
+                .get_wallet_balance(self.character_id),
+        )
+        .await
+    }
+
+    fn check_reauth(&self) -> EsiResult<()> {
+        if self.requires_reauth() {
+            let reason = self
+                .reauth_reason()
+                .unwrap_or_else(|| "reauthentication required".to_string());
+            return Err(EsiError::NeedsReauth { reason });
+        }
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -120,26 +241,53 @@ where
     }
 
     fn reauth_reason(&self) -> Option<String> {
-        match self.reauth_reason.lock() {
-            Ok(reason) => reason.clone(),
-            Err(poisoned) => poisoned.into_inner().clone(),
-        }
+        self.reauth_reason.borrow().clone()
     }
 
     async fn get_current_location(&self) -> EsiResult<CharacterLocation> {
-        if self.requires_reauth() {
-            let reason = self
-                .reauth_reason()
-                .unwrap_or_else(|| "reauthentication required".to_string());
-            return Err(EsiError::NeedsReauth { reason });
-        }
+        self.check_reauth()?;
 
+        // The rfesi client keeps the hydrated tokens next to its HTTP state,
+        // so a fetch and a token refresh cannot run against it at once: the
+        // refresh would swap tokens under the in-flight request. The fetch
+        // therefore holds the same lock as the refresh loop, bounded by
+        // `location_timeout` so a stalled request cannot starve refreshes.
         let mut state = self.state.lock().await;
-        state
-            .auth
-            .client_mut()
-            .get_current_location(self.character_id)
-            .await
+        with_request_timeout(
+            "get_current_location",
+            self.location_timeout,
+            state
+                .auth
+                .client_mut()
+                .get_current_location(self.character_id),
+        )
+        .await
+    }
+
+    fn subscribe_reauth(&self) -> Option<watch::Receiver<Option<String>>> {
+        Some(self.reauth_reason.subscribe())
+    }
+
+    async fn get_station_name(&self, station_id: StationId) -> EsiResult<String> {
+        self.check_reauth()?;
+        let mut state = self.state.lock().await;
+        with_request_timeout(
+            "get_station_name",
+            self.location_timeout,
+            state.auth.client_mut().get_station_name(station_id),
+        )
+        .await
+    }
+
+    async fn get_structure_name(&self, structure_id: StructureId) -> EsiResult<String> {
+        self.check_reauth()?;
+        let mut state = self.state.lock().await;
+        with_request_timeout(
+            "get_structure_name",
+            self.location_timeout,
+            state.auth.client_mut().get_structure_name(structure_id),
+        )
+        .await
     }
 }
 
@@ -151,7 +299,9 @@ where
 {
     fn drop(&mut self) {
         let _ = self.shutdown_tx.send(true);
-        self.refresh_task.abort();
+        if let Some(refresh_task) = self.refresh_task.take() {
+            refresh_task.abort();
+        }
     }
 }
 
@@ -160,16 +310,40 @@ async fn refresh_loop<C, S, T>(
     character_id: CharacterId,
     refresh_floor: Duration,
     needs_reauth: Arc<AtomicBool>,
-    reauth_reason: Arc<Mutex<Option<String>>>,
+    reauth_reason: Arc<watch::Sender<Option<String>>>,
     mut shutdown_rx: watch::Receiver<bool>,
 ) where
     C: SsoAuthClient + EsiApiClient + Send + 'static,
     S: TokenStore + Send + Sync + 'static,
     T: Clock + Send + Sync + 'static,
+{
+    while let Some(reason) =
+        refresh_until_reauth(&state, character_id, refresh_floor, &mut shutdown_rx).await
+    {
+        mark_needs_reauth(&needs_reauth, &reauth_reason, reason);
+        if !wait_for_new_session(&state, character_id, refresh_floor, &mut shutdown_rx).await {
+            break;
+        }
+        clear_needs_reauth(&needs_reauth, &reauth_reason);
+    }
+}
+
+/// Keeps the session refreshed until it needs reauth, returning the reason,
+/// or until shutdown, returning `None`.
+async fn refresh_until_reauth<C, S, T>(
+    state: &AsyncMutex<ManagedState<C, S, T>>,
+    character_id: CharacterId,
+    refresh_floor: Duration,
+    shutdown_rx: &mut watch::Receiver<bool>,
+) -> Option<String>
+where
+    C: SsoAuthClient + EsiApiClient + Send + 'static,
+    S: TokenStore + Send + Sync + 'static,
+    T: Clock + Send + Sync + 'static,
 {
     loop {
         if *shutdown_rx.borrow() {
-            break;
+            return None;
         }
 
         let next_delay = {
@@ -180,10 +354,7 @@ async fn refresh_loop<C, S, T>(
         let wait_duration = match next_delay {
             Ok(NextRefreshDelay::ReadyNow) => Duration::from_secs(0),
             Ok(NextRefreshDelay::Wait(duration)) => duration,
-            Ok(NextRefreshDelay::NeedsReauth { reason }) => {
-                mark_needs_reauth(&needs_reauth, &reauth_reason, reason);
-                break;
-            }
+            Ok(NextRefreshDelay::NeedsReauth { reason }) => return Some(reason),
             Err(_) => refresh_floor,
         };
 
@@ -192,7 +363,7 @@ async fn refresh_loop<C, S, T>(
                 _ = sleep(wait_duration) => {}
                 changed = shutdown_rx.changed() => {
                     if changed.is_ok() && *shutdown_rx.borrow() {
-                        break;
+                        return None;
                     }
                 }
             }
@@ -206,33 +377,301 @@ async fn refresh_loop<C, S, T>(
         match refresh_outcome {
             Ok(EnsureSessionResult::Ready(_)) => {}
             Ok(EnsureSessionResult::Missing) => {
-                mark_needs_reauth(
-                    &needs_reauth,
-                    &reauth_reason,
-                    "session missing for selected character".to_string(),
-                );
-                break;
-            }
-            Ok(EnsureSessionResult::NeedsReauth { reason }) => {
-                mark_needs_reauth(&needs_reauth, &reauth_reason, reason);
-                break;
+                return Some("session missing for selected character".to_string());
             }
+            Ok(EnsureSessionResult::NeedsReauth { reason }) => return Some(reason),
             Err(_) => sleep(refresh_floor).await,
         }
     }
 }
 
+/// Polls the store every `interval` until it holds a session for
+/// `character_id` that refreshes cleanly, e.g. after the operator logs in
+/// again from another process. The session that needed reauth is only
+/// retried once its refresh token changes, so a rejected token is not spent
+/// against SSO on every poll. Returns `false` if shutdown was signaled first.
+async fn wait_for_new_session<C, S, T>(
+    state: &AsyncMutex<ManagedState<C, S, T>>,
+    character_id: CharacterId,
+    interval: Duration,
+    shutdown_rx: &mut watch::Receiver<bool>,
+) -> bool
+where
+    C: SsoAuthClient + EsiApiClient + Send + 'static,
+    S: TokenStore + Send + Sync + 'static,
+    T: Clock + Send + Sync + 'static,
+{
+    let mut rejected_refresh_token = {
+        let state = state.lock().await;
+        stored_refresh_token(&state.auth, character_id)
+    };
+
+    loop {
+        if *shutdown_rx.borrow() {
+            return false;
+        }
+        tokio::select! {
+            _ = sleep(interval) => {}
+            changed = shutdown_rx.changed() => {
+                if changed.is_err() || *shutdown_rx.borrow() {
+                    return false;
+                }
+            }
+        }
+
+        let mut state = state.lock().await;
+        let refresh_token = stored_refresh_token(&state.auth, character_id);
+        if refresh_token.is_none() || refresh_token == rejected_refresh_token {
+            continue;
+        }
+
+        match state.auth.ensure_valid_session(character_id).await {
+            Ok(EnsureSessionResult::Ready(_)) => return true,
+            Ok(EnsureSessionResult::Missing | EnsureSessionResult::NeedsReauth { .. }) => {
+                rejected_refresh_token = refresh_token;
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+fn stored_refresh_token<C, S, T>(
+    auth: &AuthService<C, S, T>,
+    character_id: CharacterId,
+) -> Option<String>
+where
+    C: SsoAuthClient,
+    S: TokenStore,
+    T: Clock,
+{
+    auth.load_session(character_id)
+        .ok()
+        .flatten()
+        .map(|session| session.refresh_token)
+}
+
 fn mark_needs_reauth(
     needs_reauth: &AtomicBool,
-    reauth_reason: &Mutex<Option<String>>,
+    reauth_reason: &watch::Sender<Option<String>>,
     reason: String,
 ) {
     needs_reauth.store(true, Ordering::Relaxed);
-    match reauth_reason.lock() {
-        Ok(mut guard) => *guard = Some(reason),
-        Err(poisoned) => {
-            let mut guard = poisoned.into_inner();
-            *guard = Some(reason);
+    reauth_reason.send_replace(Some(reason));
+}
+
+fn clear_needs_reauth(needs_reauth: &AtomicBool, reauth_reason: &watch::Sender<Option<String>>) {
+    needs_reauth.store(false, Ordering::Relaxed);
+    reauth_reason.send_replace(None);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use async_trait::async_trait;
+    use jaytripper_core::{
+        ids::{CharacterId, SolarSystemId, StationId, StructureId},
+        time::Timestamp,
+    };
+    use tokio::sync::oneshot;
+
+    use super::{EsiClient, ManagedEsiClient};
+    use crate::{
+        AuthService, AuthSession, EsiError, EsiResult,
+        api::{CharacterLocation, TokenVerification},
+        auth::LoginRequest,
+        client::{EsiApiClient, InitialAuthTokens, RefreshTokens, SsoAuthClient},
+        test_util::{FixedClock, MemoryTokenStore},
+        token_store::TokenStore,
+    };
+
+    struct SlowLocationClient {
+        location_delay: Duration,
+        location_gate: Option<LocationGate>,
+    }
+
+    /// Holds the first location fetch open: `started` fires once the fetch
+    /// is in flight, and the fetch returns after `release` does.
+    struct LocationGate {
+        started: oneshot::Sender<()>,
+        release: oneshot::Receiver<()>,
+    }
+
+    #[async_trait]
+    impl SsoAuthClient for SlowLocationClient {
+        fn begin_login(&mut self) -> EsiResult<LoginRequest> {
+            Err(EsiError::message("login not supported"))
+        }
+
+        fn hydrate_session_tokens(
+            &mut self,
+            _access_token: &str,
+            _access_expires_at: Timestamp,
+            _refresh_token: &str,
+        ) -> EsiResult<()> {
+            Ok(())
+        }
+
+        async fn exchange_code(
+            &mut self,
+            _code: &str,
+            _callback_state: &str,
+        ) -> EsiResult<InitialAuthTokens> {
+            Err(EsiError::message("login not supported"))
+        }
+
+        async fn refresh(&mut self, _refresh_token: &str) -> EsiResult<RefreshTokens> {
+            Err(EsiError::message("refresh not supported"))
         }
     }
+
+    #[async_trait]
+    impl EsiApiClient for SlowLocationClient {
+        async fn ensure_api_ready(&mut self) -> EsiResult<()> {
+            Ok(())
+        }
+
+        async fn get_current_location(
+            &mut self,
+            _character_id: CharacterId,
+        ) -> EsiResult<CharacterLocation> {
+            if let Some(gate) = self.location_gate.take() {
+                let _ = gate.started.send(());
+                let _ = gate.release.await;
+            }
+            tokio::time::sleep(self.location_delay).await;
+            Ok(CharacterLocation {
+                solar_system_id: SolarSystemId(30000142),
+                station_id: None,
+                structure_id: None,
+            })
+        }
+
+        async fn verify_token(&mut self) -> EsiResult<TokenVerification> {
+            Err(EsiError::message("verify not supported"))
+        }
+
+        async fn get_wallet_balance(&mut self, _character_id: CharacterId) -> EsiResult<f64> {
+            Err(EsiError::message("wallet not supported"))
+        }
+
+        async fn get_station_name(&mut self, _station_id: StationId) -> EsiResult<String> {
+            Err(EsiError::message("station names not supported"))
+        }
+
+        async fn get_structure_name(&mut self, _structure_id: StructureId) -> EsiResult<String> {
+            Err(EsiError::message("structure names not supported"))
+        }
+    }
+
+    async fn connect_client(
+        location_delay: Duration,
+    ) -> ManagedEsiClient<SlowLocationClient, MemoryTokenStore, FixedClock> {
+        connect_with(SlowLocationClient {
+            location_delay,
+            location_gate: None,
+        })
+        .await
+    }
+
+    async fn connect_with(
+        api: SlowLocationClient,
+    ) -> ManagedEsiClient<SlowLocationClient, MemoryTokenStore, FixedClock> {
+        let store = MemoryTokenStore::default();
+        store
+            .save_session(&AuthSession {
+                character_id: CharacterId(9001),
+                character_name: Some("Pilot".to_owned()),
+                scopes: vec!["esi-location.read_location.v1".to_owned()],
+                access_token: "access".to_owned(),
+                access_expires_at: ts(1_000_000),
+                refresh_token: "refresh".to_owned(),
+                updated_at: ts(100),
+            })
+            .expect("save session");
+        let auth = AuthService::with_clock(
+            api,
+            store,
+            vec!["esi-location.read_location.v1".to_owned()],
+            FixedClock(ts(500)),
+        );
+
+        ManagedEsiClient::connect(auth, CharacterId(9001))
+            .await
+            .expect("connect managed client")
+    }
+
+    #[tokio::test]
+    async fn stalled_location_fetch_times_out_and_releases_lock() {
+        let client = connect_client(Duration::from_secs(30))
+            .await
+            .with_location_timeout(Duration::from_millis(20));
+
+        let err = client
+            .get_current_location()
+            .await
+            .expect_err("stalled fetch should time out");
+
+        assert!(matches!(err, EsiError::Timeout { .. }), "{err:?}");
+        assert!(err.is_retryable());
+        assert!(
+            tokio::time::timeout(Duration::from_millis(100), client.state.lock())
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn refresh_waits_for_in_flight_location_fetch() {
+        let (started_tx, started_rx) = oneshot::channel();
+        let (release_tx, release_rx) = oneshot::channel();
+        let client = Arc::new(
+            connect_with(SlowLocationClient {
+                location_delay: Duration::ZERO,
+                location_gate: Some(LocationGate {
+                    started: started_tx,
+                    release: release_rx,
+                }),
+            })
+            .await,
+        );
+
+        let fetch = tokio::spawn({
+            let client = Arc::clone(&client);
+            async move { client.get_current_location().await }
+        });
+        started_rx.await.expect("fetch should start");
+        assert!(client.state.try_lock().is_err());
+
+        let refresh = tokio::spawn({
+            let client = Arc::clone(&client);
+            async move {
+                let mut state = client.state.lock().await;
+                state.auth.ensure_valid_session(CharacterId(9001)).await
+            }
+        });
+        tokio::task::yield_now().await;
+        assert!(!refresh.is_finished());
+
+        release_tx.send(()).expect("release fetch");
+        assert!(fetch.await.expect("join fetch").is_ok());
+        assert!(refresh.await.expect("join refresh").is_ok());
+    }
+
+    #[tokio::test]
+    async fn shutdown_returns_usable_auth_service() {
+        let client = connect_client(Duration::ZERO).await;
+
+        let auth = client.shutdown().await;
+        let session = auth
+            .load_session(CharacterId(9001))
+            .expect("load session")
+            .expect("session should still be stored");
+
+        assert_eq!(session.character_id, CharacterId(9001));
+    }
+
+    fn ts(epoch_secs: i64) -> Timestamp {
+        Timestamp::from_epoch_secs(epoch_secs).expect("valid epoch seconds")
+    }
 }
@@ -5,14 +5,21 @@ pub mod config;
 pub mod errors;
 pub mod esi_client;
 pub mod location_ingestor;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
 pub mod token_store;
 
-pub use api::CharacterLocation;
+pub use api::{CharacterLocation, DockLocation, TokenVerification};
 pub use auth::{AuthService, AuthSession, EnsureSessionResult, LoginRequest, NextRefreshDelay};
-pub use client::{EsiApiClient, InitialAuthTokens, RefreshTokens, RfesiSsoClient, SsoAuthClient};
-pub use config::EsiConfig;
-pub use errors::{EsiError, EsiResult};
+pub use client::{
+    DEFAULT_LOGIN_TTL, EsiApiClient, InitialAuthTokens, RefreshTokens, RfesiSsoClient,
+    SsoAuthClient, WALLET_READ_SCOPE,
+};
+pub use config::{DEFAULT_REQUEST_TIMEOUT, EsiConfig};
+pub use errors::{ESI_ERROR_LIMITED_STATUS, EsiError, EsiResult};
 pub use esi_client::{EsiClient, ManagedEsiClient};
 pub use jaytripper_core::ids::{CharacterId, SolarSystemId, StationId, StructureId};
-pub use location_ingestor::{LocationIngestor, LocationPollConfig, PollMetrics};
+pub use location_ingestor::{
+    LocationIngestor, LocationPollConfig, PollMetrics, PollObserver, PollOutcomeSummary, PollResult,
+};
 pub use token_store::{KeyringTokenStore, TokenStore};
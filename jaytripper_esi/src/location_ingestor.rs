@@ -1,10 +1,17 @@
-use std::time::{Duration, Instant};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use jaytripper_core::{
-    events::{MovementEvent, MovementEventSink, MovementEventSource},
+    events::{
+        MovementEvent, MovementEventSink, MovementEventSource, MovementReason,
+        StructureEnteredEvent,
+    },
+    gate_graph::GateGraph,
     time::Timestamp,
 };
-use rand::{Rng, SeedableRng, rngs::SmallRng};
+use rand::{Rng, RngCore, SeedableRng, rngs::SmallRng};
 use tokio::{sync::watch, time::sleep};
 
 use crate::{
@@ -14,23 +21,92 @@ use crate::{
     esi_client::EsiClient,
 };
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct LocationPollConfig {
     pub base_interval: Duration,
     pub jitter_factor: f32,
     pub api_failure_backoff_initial: Duration,
     pub api_failure_backoff_max: Duration,
+    /// Stop with the last API error after this many consecutive failures.
+    /// `None` keeps retrying forever.
+    pub max_consecutive_failures: Option<u32>,
 }
 
-impl Default for LocationPollConfig {
-    fn default() -> Self {
+impl LocationPollConfig {
+    /// Polls every 2s. ESI caches character location for about 5s, so most
+    /// polls return cached data; this trades request volume (and error-limit
+    /// headroom if ESI degrades) for noticing a jump as soon as the cache
+    /// expires.
+    pub fn aggressive() -> Self {
+        Self {
+            base_interval: Duration::from_secs(2),
+            jitter_factor: 0.1,
+            api_failure_backoff_initial: Duration::from_millis(500),
+            api_failure_backoff_max: Duration::from_secs(15),
+            max_consecutive_failures: None,
+        }
+    }
+
+    /// Polls every 5s, roughly matching the ESI location cache, so each
+    /// request usually sees fresh data without wasting budget. This is the
+    /// default.
+    pub fn balanced() -> Self {
         Self {
             base_interval: Duration::from_secs(5),
             jitter_factor: 0.2,
             api_failure_backoff_initial: Duration::from_secs(1),
             api_failure_backoff_max: Duration::from_secs(30),
+            max_consecutive_failures: None,
+        }
+    }
+
+    /// Polls every 15s with long backoff. Uses a fraction of the request
+    /// budget, which suits tracking many characters at once, at the cost of
+    /// missing systems passed through quickly.
+    pub fn conservative() -> Self {
+        Self {
+            base_interval: Duration::from_secs(15),
+            jitter_factor: 0.2,
+            api_failure_backoff_initial: Duration::from_secs(5),
+            api_failure_backoff_max: Duration::from_secs(120),
+            max_consecutive_failures: None,
         }
     }
+
+    pub fn validate(&self) -> EsiResult<()> {
+        if self.base_interval.is_zero() {
+            return Err(EsiError::InvalidConfig(
+                "poll base_interval must be non-zero",
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.jitter_factor) {
+            return Err(EsiError::InvalidConfig(
+                "poll jitter_factor must be between 0 and 1",
+            ));
+        }
+        if self.api_failure_backoff_initial.is_zero() {
+            return Err(EsiError::InvalidConfig(
+                "poll api_failure_backoff_initial must be non-zero",
+            ));
+        }
+        if self.api_failure_backoff_initial > self.api_failure_backoff_max {
+            return Err(EsiError::InvalidConfig(
+                "poll api_failure_backoff_initial must not exceed api_failure_backoff_max",
+            ));
+        }
+        if self.max_consecutive_failures == Some(0) {
+            return Err(EsiError::InvalidConfig(
+                "poll max_consecutive_failures must be non-zero when set",
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Default for LocationPollConfig {
+    fn default() -> Self {
+        Self::balanced()
+    }
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
@@ -39,6 +115,32 @@ pub struct PollMetrics {
     pub last_poll_latency: Option<Duration>,
 }
 
+/// Public view of a single poll attempt, handed to the `on_poll` hook.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PollOutcomeSummary {
+    pub success: bool,
+    pub emitted: bool,
+    pub latency: Duration,
+    pub consecutive_failures: u32,
+    /// The poll stopped because the client needs reauthentication.
+    pub needs_reauth: bool,
+}
+
+pub type PollObserver = Box<dyn Fn(&PollOutcomeSummary) + Send>;
+
+/// Result of a single [`LocationIngestor::poll_once`] call.
+#[derive(Debug)]
+pub enum PollResult {
+    /// The location was fetched; `emitted` is set when it produced a
+    /// movement event.
+    Success { emitted: bool },
+    /// ESI failed in a way that may recover; poll again after a backoff.
+    ApiFailure(EsiError),
+    /// Polling cannot continue (reauth required, ESI rejected the request
+    /// in a way retrying will not fix, or the sink failed).
+    Terminal(EsiError),
+}
+
 pub struct LocationIngestor<C, S, T = SystemClock>
 where
     C: EsiClient + Send + Sync,
@@ -53,7 +155,10 @@ where
     last_location: Option<CharacterLocation>,
     api_consecutive_failures: u32,
     metrics: PollMetrics,
-    rng: SmallRng,
+    rng: Box<dyn RngCore + Send>,
+    on_poll: Option<PollObserver>,
+    gate_graph: Option<Arc<GateGraph>>,
+    reauth_retry_interval: Option<Duration>,
 }
 
 impl<C, S> LocationIngestor<C, S, SystemClock>
@@ -85,10 +190,42 @@ where
             last_location: None,
             api_consecutive_failures: 0,
             metrics: PollMetrics::default(),
-            rng: SmallRng::seed_from_u64(seed),
+            rng: Box::new(SmallRng::seed_from_u64(seed)),
+            on_poll: None,
+            gate_graph: None,
+            reauth_retry_interval: None,
         }
     }
 
+    pub fn with_on_poll(mut self, on_poll: impl Fn(&PollOutcomeSummary) + Send + 'static) -> Self {
+        self.on_poll = Some(Box::new(on_poll));
+        self
+    }
+
+    /// Draws poll jitter from `rng` instead of the default per-character
+    /// seeded `SmallRng`.
+    pub fn with_rng(mut self, rng: impl RngCore + Send + 'static) -> Self {
+        self.rng = Box::new(rng);
+        self
+    }
+
+    /// Enables gap warnings for transitions between systems that are not
+    /// stargate-adjacent (a missed intermediate poll or a wormhole jump).
+    pub fn with_gate_graph(mut self, gate_graph: Arc<GateGraph>) -> Self {
+        self.gate_graph = Some(gate_graph);
+        self
+    }
+
+    /// Instead of exiting when the client needs reauthentication, pause and
+    /// check [`EsiClient::requires_reauth`] every `interval`, resuming polling
+    /// once it clears. [`ManagedEsiClient`](crate::ManagedEsiClient) clears it
+    /// when a new session for the character lands in its token store (e.g.
+    /// after the operator logs in from another terminal).
+    pub fn with_reauth_retry(mut self, interval: Duration) -> Self {
+        self.reauth_retry_interval = Some(interval);
+        self
+    }
+
     pub fn metrics(&self) -> PollMetrics {
         self.metrics.clone()
     }
@@ -124,21 +261,50 @@ where
                 }
             };
 
-            let wait = match outcome {
-                PollOutcome::Success => self.next_success_delay(),
-                PollOutcome::ApiFailure(err) => {
-                    let wait = self.next_api_failure_delay();
+            let Some(wait) = self.next_poll_delay(&outcome) else {
+                let PollResult::Terminal(err) = outcome else {
+                    unreachable!("only terminal results have no next poll delay");
+                };
+                if let (EsiError::NeedsReauth { reason }, Some(interval)) =
+                    (&err, self.reauth_retry_interval)
+                {
+                    log::warn!(
+                        "polling paused for character {} until reauth ({reason}); checking every {interval:?}",
+                        self.client.character_id()
+                    );
+                    if !Self::wait_for_reauth(&self.client, interval, &mut shutdown_rx).await {
+                        return Ok(());
+                    }
+                    log::info!(
+                        "reauth detected for character {}; resuming polling",
+                        self.client.character_id()
+                    );
+                    continue;
+                }
+                return Err(err);
+            };
+            if let PollResult::ApiFailure(err) = outcome {
+                if self
+                    .config
+                    .max_consecutive_failures
+                    .is_some_and(|max| self.api_consecutive_failures >= max)
+                {
                     log::error!(
-                        "poll API failure for character {} (consecutive failures: {}, retry in {:?}): {:?}",
+                        "giving up on character {} after {} consecutive API failures: {}",
                         self.client.character_id(),
                         self.api_consecutive_failures,
-                        wait,
-                        err.display_chain()
+                        err.redacted_display()
                     );
-                    wait
+                    return Err(err);
                 }
-                PollOutcome::Terminal(err) => return Err(err),
-            };
+                log::error!(
+                    "poll API failure for character {} (consecutive failures: {}, retry in {:?}): {}",
+                    self.client.character_id(),
+                    self.api_consecutive_failures,
+                    wait,
+                    err.redacted_display()
+                );
+            }
 
             tokio::select! {
                 _ = sleep(wait) => {}
@@ -151,7 +317,75 @@ where
         }
     }
 
-    async fn poll_once(&mut self) -> PollOutcome {
+    /// Waits until the client no longer requires reauth. Returns `false` if
+    /// shutdown was signaled first.
+    async fn wait_for_reauth(
+        client: &C,
+        interval: Duration,
+        shutdown_rx: &mut watch::Receiver<bool>,
+    ) -> bool {
+        loop {
+            tokio::select! {
+                _ = sleep(interval) => {}
+                changed = shutdown_rx.changed() => {
+                    if shutdown_signaled(changed, shutdown_rx) {
+                        return false;
+                    }
+                }
+            }
+            if !client.requires_reauth() {
+                return true;
+            }
+        }
+    }
+
+    /// Polls ESI once and emits a movement event if the system changed.
+    ///
+    /// [`Self::run_until_shutdown`] drives this on a timer; embedders with
+    /// their own scheduler can call it directly and use
+    /// [`Self::next_poll_delay`] to pace the next attempt:
+    ///
+    /// ```
+    /// use jaytripper_core::MovementEventSink;
+    /// use jaytripper_esi::{EsiClient, EsiResult, LocationIngestor, PollResult};
+    ///
+    /// async fn drive<C, S>(mut ingestor: LocationIngestor<C, S>) -> EsiResult<()>
+    /// where
+    ///     C: EsiClient + Send + Sync,
+    ///     S: MovementEventSink + Send + Sync,
+    ///     S::Error: std::fmt::Display,
+    /// {
+    ///     loop {
+    ///         let result = ingestor.poll_once().await;
+    ///         match ingestor.next_poll_delay(&result) {
+    ///             Some(delay) => tokio::time::sleep(delay).await,
+    ///             None => {
+    ///                 if let PollResult::Terminal(err) = result {
+    ///                     return Err(err);
+    ///                 }
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub async fn poll_once(&mut self) -> PollResult {
+        let started = Instant::now();
+        let outcome = self.poll_location().await;
+
+        if let Some(on_poll) = &self.on_poll {
+            on_poll(&PollOutcomeSummary {
+                success: matches!(outcome, PollResult::Success { .. }),
+                emitted: matches!(outcome, PollResult::Success { emitted: true }),
+                latency: started.elapsed(),
+                consecutive_failures: self.api_consecutive_failures,
+                needs_reauth: matches!(outcome, PollResult::Terminal(EsiError::NeedsReauth { .. })),
+            });
+        }
+
+        outcome
+    }
+
+    async fn poll_location(&mut self) -> PollResult {
         log::trace!(
             "polling current location for character {}",
             self.client.character_id()
@@ -165,16 +399,17 @@ where
 
         let observed_at = self.clock.now();
 
-        if let Err(outcome) = self.observe_location(location, observed_at).await {
-            return outcome;
-        }
+        let emitted = match self.observe_location(location, observed_at).await {
+            Ok(emitted) => emitted,
+            Err(outcome) => return outcome,
+        };
 
         self.record_success(started.elapsed(), observed_at);
         log::trace!("poll success for character {}", self.client.character_id());
-        PollOutcome::Success
+        PollResult::Success { emitted }
     }
 
-    async fn fetch_location(&mut self) -> Result<CharacterLocation, PollOutcome> {
+    async fn fetch_location(&mut self) -> Result<CharacterLocation, PollResult> {
         match self.client.get_current_location().await {
             Ok(location) => {
                 log::trace!(
@@ -189,7 +424,15 @@ where
                     "poll terminal: reauth required for character {} ({reason})",
                     self.client.character_id()
                 );
-                Err(PollOutcome::Terminal(EsiError::NeedsReauth { reason }))
+                Err(PollResult::Terminal(EsiError::NeedsReauth { reason }))
+            }
+            Err(err) if !err.is_retryable() => {
+                log::debug!(
+                    "poll terminal: non-retryable ESI error for character {}: {}",
+                    self.client.character_id(),
+                    err.redacted_display()
+                );
+                Err(PollResult::Terminal(err))
             }
             Err(err) => {
                 self.record_api_failure();
@@ -198,7 +441,7 @@ where
                     self.client.character_id(),
                     self.api_consecutive_failures
                 );
-                Err(PollOutcome::ApiFailure(err))
+                Err(PollResult::ApiFailure(err))
             }
         }
     }
@@ -207,7 +450,7 @@ where
         &mut self,
         location: CharacterLocation,
         observed_at: Timestamp,
-    ) -> Result<(), PollOutcome> {
+    ) -> Result<bool, PollResult> {
         let should_emit_event = self
             .last_location
             .as_ref()
@@ -215,19 +458,40 @@ where
             .unwrap_or(true);
 
         if should_emit_event {
-            let event = MovementEvent {
-                character_id: self.client.character_id(),
-                from_system_id: self
-                    .last_location
-                    .as_ref()
-                    .map(|previous| previous.solar_system_id),
-                to_system_id: location.solar_system_id,
-                observed_at,
-                source: MovementEventSource::Esi,
-            };
+            let from_system_id = self
+                .last_location
+                .as_ref()
+                .map(|previous| previous.solar_system_id);
+            let event =
+                MovementEvent::builder(self.client.character_id(), location.solar_system_id)
+                    .from(from_system_id)
+                    .reason(MovementReason::classify(
+                        from_system_id,
+                        location.solar_system_id,
+                        self.gate_graph.as_deref(),
+                    ))
+                    .observed_at(observed_at)
+                    .source(MovementEventSource::Esi)
+                    .build()
+                    .map_err(|err| {
+                        PollResult::Terminal(EsiError::message(format!(
+                            "invalid movement event: {err}"
+                        )))
+                    })?;
+
+            if event.reason == Some(MovementReason::Wormhole)
+                && let Some(from_system_id) = event.from_system_id
+            {
+                log::warn!(
+                    "non-adjacent movement for character {}: {} -> {} (wormhole jump or missed intermediate system)",
+                    event.character_id,
+                    from_system_id,
+                    event.to_system_id
+                );
+            }
 
             if let Err(err) = self.sink.emit_movement(event).await {
-                return Err(PollOutcome::Terminal(EsiError::message(format!(
+                return Err(PollResult::Terminal(EsiError::message(format!(
                     "failed to emit movement event: {err}"
                 ))));
             }
@@ -236,10 +500,46 @@ where
                 "emitted movement event for character {}",
                 self.client.character_id()
             );
+        } else if let Some(event) = self.structure_entered(&location, observed_at) {
+            if let Err(err) = self.sink.emit_structure_entered(event).await {
+                return Err(PollResult::Terminal(EsiError::message(format!(
+                    "failed to emit structure entered event: {err}"
+                ))));
+            }
+
+            log::debug!(
+                "emitted structure entered event for character {}",
+                self.client.character_id()
+            );
         }
 
         self.last_location = Some(location);
-        Ok(())
+        Ok(should_emit_event)
+    }
+
+    /// A same-system transition into a structure other than the previous
+    /// one. Leaving a structure for space is not reported.
+    fn structure_entered(
+        &self,
+        location: &CharacterLocation,
+        observed_at: Timestamp,
+    ) -> Option<StructureEnteredEvent> {
+        let previous = self.last_location.as_ref()?;
+        let structure_id = location.structure_id?;
+        if previous.solar_system_id != location.solar_system_id
+            || previous.structure_id == Some(structure_id)
+        {
+            return None;
+        }
+
+        Some(StructureEnteredEvent {
+            character_id: self.client.character_id(),
+            system_id: location.solar_system_id,
+            from_structure_id: previous.structure_id,
+            structure_id,
+            observed_at,
+            source: MovementEventSource::Esi,
+        })
     }
 
     fn record_success(&mut self, latency: Duration, observed_at: Timestamp) {
@@ -252,6 +552,19 @@ where
         self.api_consecutive_failures = self.api_consecutive_failures.saturating_add(1);
     }
 
+    /// How long to wait before polling again after `result`, using the
+    /// configured jitter and failure backoff. `None` means stop polling.
+    pub fn next_poll_delay(&mut self, result: &PollResult) -> Option<Duration> {
+        match result {
+            PollResult::Success { .. } => Some(self.next_success_delay()),
+            PollResult::ApiFailure(err) if err.is_error_limited() => {
+                Some(self.config.api_failure_backoff_max)
+            }
+            PollResult::ApiFailure(_) => Some(self.next_api_failure_delay()),
+            PollResult::Terminal(_) => None,
+        }
+    }
+
     fn next_success_delay(&mut self) -> Duration {
         self.jittered_duration(self.config.base_interval)
     }
@@ -293,41 +606,38 @@ fn exponential_backoff(attempts: u32, initial: Duration, max: Duration) -> Durat
     Duration::from_millis(backoff_ms as u64)
 }
 
-enum PollOutcome {
-    Success,
-    ApiFailure(EsiError),
-    Terminal(EsiError),
-}
-
 #[cfg(test)]
 mod tests {
     use std::{
         collections::VecDeque,
-        sync::{Arc, Mutex},
+        sync::{
+            Arc, Mutex,
+            atomic::{AtomicUsize, Ordering},
+        },
         time::Duration,
     };
 
     use async_trait::async_trait;
     use jaytripper_core::{
-        MovementEvent, MovementEventSink, MovementEventSource,
-        ids::{CharacterId, SolarSystemId, StationId},
+        MovementEvent, MovementEventSink, MovementEventSource, MovementReason,
+        StructureEnteredEvent,
+        gate_graph::GateGraph,
+        ids::{CharacterId, SolarSystemId, StationId, StructureId},
         time::Timestamp,
     };
+    use rand::rngs::mock::StepRng;
     use tokio::sync::watch;
 
-    use super::{LocationIngestor, LocationPollConfig, PollOutcome};
-    use crate::{EsiError, EsiResult, api::CharacterLocation, auth::Clock, esi_client::EsiClient};
-
-    #[derive(Clone, Copy)]
-    struct FixedClock {
-        now: Timestamp,
-    }
-
-    impl Clock for FixedClock {
-        fn now(&self) -> Timestamp {
-            self.now
-        }
-    }
+    use super::{LocationIngestor, LocationPollConfig, PollOutcomeSummary, PollResult};
+    use crate::{
+        AuthService, AuthSession, EsiError, EsiResult, ManagedEsiClient, RefreshTokens, TokenStore,
+        api::CharacterLocation,
+        esi_client::EsiClient,
+        test_util::{
+            FixedClock, MemoryTokenStore, StubEsiApiClient, rejected_refresh_error,
+            unavailable_error,
+        },
+    };
 
     struct MockEsiClient {
         character_id: CharacterId,
@@ -353,13 +663,14 @@ mod tests {
                 .lock()
                 .expect("responses lock")
                 .pop_front()
-                .unwrap_or_else(|| Err(EsiError::message("no response configured")))
+                .unwrap_or_else(|| Err(unavailable_error()))
         }
     }
 
     #[derive(Default)]
     struct RecordingSink {
         events: Mutex<Vec<MovementEvent>>,
+        structure_events: Mutex<Vec<StructureEnteredEvent>>,
         fail_emits: Mutex<usize>,
     }
 
@@ -382,6 +693,18 @@ mod tests {
             self.0.events.lock().expect("events lock").push(event);
             Ok(())
         }
+
+        async fn emit_structure_entered(
+            &self,
+            event: StructureEnteredEvent,
+        ) -> Result<(), Self::Error> {
+            self.0
+                .structure_events
+                .lock()
+                .expect("structure events lock")
+                .push(event);
+            Ok(())
+        }
     }
 
     fn location(system: i32, station: Option<i32>) -> CharacterLocation {
@@ -392,15 +715,230 @@ mod tests {
         }
     }
 
+    fn structure_location(system: i32, structure: i64) -> CharacterLocation {
+        CharacterLocation {
+            solar_system_id: SolarSystemId(system),
+            station_id: None,
+            structure_id: Some(StructureId(structure)),
+        }
+    }
+
+    #[test]
+    fn poll_config_presets_are_valid() {
+        for config in [
+            LocationPollConfig::aggressive(),
+            LocationPollConfig::balanced(),
+            LocationPollConfig::conservative(),
+        ] {
+            config.validate().expect("preset should validate");
+        }
+        assert_eq!(
+            LocationPollConfig::default(),
+            LocationPollConfig::balanced()
+        );
+    }
+
+    #[test]
+    fn poll_config_presets_are_ordered_by_interval() {
+        let aggressive = LocationPollConfig::aggressive();
+        let balanced = LocationPollConfig::balanced();
+        let conservative = LocationPollConfig::conservative();
+
+        assert!(aggressive.base_interval < balanced.base_interval);
+        assert!(balanced.base_interval < conservative.base_interval);
+        assert!(aggressive.api_failure_backoff_max < balanced.api_failure_backoff_max);
+        assert!(balanced.api_failure_backoff_max < conservative.api_failure_backoff_max);
+    }
+
+    #[test]
+    fn poll_config_validate_rejects_inverted_backoff() {
+        let config = LocationPollConfig {
+            api_failure_backoff_initial: Duration::from_secs(60),
+            api_failure_backoff_max: Duration::from_secs(30),
+            ..LocationPollConfig::balanced()
+        };
+
+        assert!(matches!(config.validate(), Err(EsiError::InvalidConfig(_))));
+    }
+
     fn config_for_tests() -> LocationPollConfig {
         LocationPollConfig {
             base_interval: Duration::from_secs(5),
             jitter_factor: 0.0,
             api_failure_backoff_initial: Duration::from_secs(1),
             api_failure_backoff_max: Duration::from_secs(30),
+            max_consecutive_failures: None,
         }
     }
 
+    #[tokio::test]
+    async fn run_until_shutdown_gives_up_after_max_consecutive_failures() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let attempts_for_hook = Arc::clone(&attempts);
+        let mut ingestor = LocationIngestor::with_clock(
+            MockEsiClient {
+                character_id: CharacterId(42),
+                responses: Mutex::new(VecDeque::new()),
+            },
+            SharedRecordingSink(Arc::new(RecordingSink::default())),
+            LocationPollConfig {
+                api_failure_backoff_initial: Duration::from_millis(1),
+                api_failure_backoff_max: Duration::from_millis(5),
+                max_consecutive_failures: Some(3),
+                ..config_for_tests()
+            },
+            FixedClock(ts(1_700_000_001)),
+        )
+        .with_on_poll(move |_| {
+            attempts_for_hook.fetch_add(1, Ordering::SeqCst);
+        });
+        let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+
+        let result = tokio::time::timeout(
+            Duration::from_secs(1),
+            ingestor.run_until_shutdown(shutdown_rx),
+        )
+        .await
+        .expect("ingestor should give up instead of looping");
+
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn injected_rng_controls_jitter_exactly() {
+        let mut ingestor = LocationIngestor::with_clock(
+            MockEsiClient {
+                character_id: CharacterId(42),
+                responses: Mutex::new(VecDeque::new()),
+            },
+            SharedRecordingSink(Arc::new(RecordingSink::default())),
+            LocationPollConfig {
+                jitter_factor: 0.5,
+                ..config_for_tests()
+            },
+            FixedClock(ts(1_700_000_000)),
+        )
+        .with_rng(StepRng::new(0, 0));
+
+        for _ in 0..3 {
+            assert_eq!(
+                ingestor.next_poll_delay(&PollResult::Success { emitted: false }),
+                Some(Duration::from_millis(2_500))
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn citadel_to_citadel_in_same_system_emits_structure_entered() {
+        let client = MockEsiClient {
+            character_id: CharacterId(42),
+            responses: Mutex::new(VecDeque::from(vec![
+                Ok(structure_location(31000005, 1_000_000_000_001)),
+                Ok(structure_location(31000005, 1_000_000_000_001)),
+                Ok(structure_location(31000005, 1_000_000_000_002)),
+            ])),
+        };
+        let sink = Arc::new(RecordingSink::default());
+        let mut ingestor = LocationIngestor::with_clock(
+            client,
+            SharedRecordingSink(Arc::clone(&sink)),
+            config_for_tests(),
+            FixedClock(ts(1_700_000_000)),
+        );
+
+        for _ in 0..3 {
+            assert!(matches!(
+                ingestor.poll_once().await,
+                PollResult::Success { .. }
+            ));
+        }
+
+        assert_eq!(sink.events.lock().expect("events lock").len(), 1);
+        assert_eq!(
+            *sink.structure_events.lock().expect("structure events lock"),
+            vec![StructureEnteredEvent {
+                character_id: CharacterId(42),
+                system_id: SolarSystemId(31000005),
+                from_structure_id: Some(StructureId(1_000_000_000_001)),
+                structure_id: StructureId(1_000_000_000_002),
+                observed_at: ts(1_700_000_000),
+                source: MovementEventSource::Esi,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn structure_change_across_systems_is_only_a_movement() {
+        let client = MockEsiClient {
+            character_id: CharacterId(42),
+            responses: Mutex::new(VecDeque::from(vec![
+                Ok(structure_location(31000005, 1_000_000_000_001)),
+                Ok(structure_location(31000006, 1_000_000_000_002)),
+            ])),
+        };
+        let sink = Arc::new(RecordingSink::default());
+        let mut ingestor = LocationIngestor::with_clock(
+            client,
+            SharedRecordingSink(Arc::clone(&sink)),
+            config_for_tests(),
+            FixedClock(ts(1_700_000_000)),
+        );
+
+        ingestor.poll_once().await;
+        ingestor.poll_once().await;
+
+        assert_eq!(sink.events.lock().expect("events lock").len(), 2);
+        assert!(
+            sink.structure_events
+                .lock()
+                .expect("structure events lock")
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn gate_graph_flags_non_adjacent_movement() {
+        let client = MockEsiClient {
+            character_id: CharacterId(42),
+            responses: Mutex::new(VecDeque::from(vec![
+                Ok(location(30000142, None)),
+                Ok(location(30000144, None)),
+                Ok(location(31000005, None)),
+            ])),
+        };
+        let gate_graph =
+            GateGraph::from_jumps([(SolarSystemId(30000142), SolarSystemId(30000144))]);
+        let sink = Arc::new(RecordingSink::default());
+        let mut ingestor = LocationIngestor::with_clock(
+            client,
+            SharedRecordingSink(Arc::clone(&sink)),
+            config_for_tests(),
+            FixedClock(ts(1_700_000_000)),
+        )
+        .with_gate_graph(Arc::new(gate_graph));
+
+        for _ in 0..3 {
+            ingestor.poll_once().await;
+        }
+
+        let reasons: Vec<_> = sink
+            .events
+            .lock()
+            .expect("events lock")
+            .iter()
+            .map(|event| event.reason)
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                Some(MovementReason::Login),
+                Some(MovementReason::Gate),
+                Some(MovementReason::Wormhole),
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn emits_event_on_first_poll_and_transition_only() {
         let client = MockEsiClient {
@@ -416,14 +954,21 @@ mod tests {
             client,
             SharedRecordingSink(Arc::clone(&sink)),
             config_for_tests(),
-            FixedClock {
-                now: ts(1_700_000_000),
-            },
+            FixedClock(ts(1_700_000_000)),
         );
 
-        assert!(matches!(ingestor.poll_once().await, PollOutcome::Success));
-        assert!(matches!(ingestor.poll_once().await, PollOutcome::Success));
-        assert!(matches!(ingestor.poll_once().await, PollOutcome::Success));
+        assert!(matches!(
+            ingestor.poll_once().await,
+            PollResult::Success { .. }
+        ));
+        assert!(matches!(
+            ingestor.poll_once().await,
+            PollResult::Success { .. }
+        ));
+        assert!(matches!(
+            ingestor.poll_once().await,
+            PollResult::Success { .. }
+        ));
 
         let events = sink.events.lock().expect("events lock");
         assert_eq!(events.len(), 2);
@@ -449,22 +994,18 @@ mod tests {
             client,
             SharedRecordingSink(Arc::clone(&sink)),
             config_for_tests(),
-            FixedClock {
-                now: ts(1_700_000_001),
-            },
+            FixedClock(ts(1_700_000_001)),
         );
 
         let outcome = ingestor.poll_once().await;
-        assert!(matches!(outcome, PollOutcome::Terminal(_)));
+        assert!(matches!(outcome, PollResult::Terminal(_)));
     }
 
     #[tokio::test]
     async fn api_failures_backoff() {
         let client = MockEsiClient {
             character_id: CharacterId(42),
-            responses: Mutex::new(VecDeque::from(vec![Err(EsiError::message(
-                "esi unavailable",
-            ))])),
+            responses: Mutex::new(VecDeque::from(vec![Err(unavailable_error())])),
         };
         let sink = Arc::new(RecordingSink::default());
 
@@ -472,19 +1013,40 @@ mod tests {
             client,
             SharedRecordingSink(Arc::clone(&sink)),
             config_for_tests(),
-            FixedClock {
-                now: ts(1_700_000_001),
-            },
+            FixedClock(ts(1_700_000_001)),
         );
 
         assert!(matches!(
             ingestor.poll_once().await,
-            PollOutcome::ApiFailure(_)
+            PollResult::ApiFailure(_)
         ));
         assert_eq!(ingestor.api_consecutive_failures(), 1);
         assert_eq!(ingestor.next_api_failure_delay(), Duration::from_secs(1));
     }
 
+    #[tokio::test]
+    async fn non_retryable_api_error_is_terminal() {
+        let client = MockEsiClient {
+            character_id: CharacterId(42),
+            responses: Mutex::new(VecDeque::from(vec![Err(EsiError::from(
+                rfesi::prelude::EsiError::InvalidStatusCode(403),
+            ))])),
+        };
+        let sink = Arc::new(RecordingSink::default());
+
+        let mut ingestor = LocationIngestor::with_clock(
+            client,
+            SharedRecordingSink(Arc::clone(&sink)),
+            config_for_tests(),
+            FixedClock(ts(1_700_000_001)),
+        );
+
+        let outcome = ingestor.poll_once().await;
+        assert!(matches!(outcome, PollResult::Terminal(_)));
+        assert_eq!(ingestor.next_poll_delay(&outcome), None);
+        assert_eq!(ingestor.api_consecutive_failures(), 0);
+    }
+
     #[tokio::test]
     async fn records_poll_metrics_after_success() {
         let client = MockEsiClient {
@@ -496,17 +1058,57 @@ mod tests {
             client,
             SharedRecordingSink(Arc::clone(&sink)),
             config_for_tests(),
-            FixedClock {
-                now: ts(1_700_000_100),
-            },
+            FixedClock(ts(1_700_000_100)),
         );
 
-        assert!(matches!(ingestor.poll_once().await, PollOutcome::Success));
+        assert!(matches!(
+            ingestor.poll_once().await,
+            PollResult::Success { .. }
+        ));
         let metrics = ingestor.metrics();
         assert_eq!(metrics.last_success_at, Some(ts(1_700_000_100)));
         assert!(metrics.last_poll_latency.is_some());
     }
 
+    #[tokio::test]
+    async fn on_poll_hook_fires_once_per_poll_with_outcome_flags() {
+        let client = MockEsiClient {
+            character_id: CharacterId(42),
+            responses: Mutex::new(VecDeque::from(vec![
+                Ok(location(30000142, None)),
+                Ok(location(30000142, None)),
+                Err(unavailable_error()),
+            ])),
+        };
+        let sink = Arc::new(RecordingSink::default());
+        let summaries = Arc::new(Mutex::new(Vec::<PollOutcomeSummary>::new()));
+        let summaries_for_hook = Arc::clone(&summaries);
+        let mut ingestor = LocationIngestor::with_clock(
+            client,
+            SharedRecordingSink(Arc::clone(&sink)),
+            config_for_tests(),
+            FixedClock(ts(1_700_000_150)),
+        )
+        .with_on_poll(move |summary| {
+            summaries_for_hook
+                .lock()
+                .expect("summaries lock")
+                .push(summary.clone());
+        });
+
+        ingestor.poll_once().await;
+        ingestor.poll_once().await;
+        ingestor.poll_once().await;
+
+        let summaries = summaries.lock().expect("summaries lock");
+        assert_eq!(summaries.len(), 3);
+        assert!(summaries[0].success && summaries[0].emitted);
+        assert!(summaries[1].success && !summaries[1].emitted);
+        assert!(!summaries[2].success && !summaries[2].emitted);
+        assert_eq!(summaries[0].consecutive_failures, 0);
+        assert_eq!(summaries[2].consecutive_failures, 1);
+    }
+
     #[tokio::test]
     async fn exits_cleanly_when_shutdown_signal_is_set() {
         let client = MockEsiClient {
@@ -518,9 +1120,7 @@ mod tests {
             client,
             SharedRecordingSink(Arc::clone(&sink)),
             config_for_tests(),
-            FixedClock {
-                now: ts(1_700_000_200),
-            },
+            FixedClock(ts(1_700_000_200)),
         );
         let (shutdown_tx, shutdown_rx) = watch::channel(true);
         drop(shutdown_tx);
@@ -531,6 +1131,126 @@ mod tests {
             .expect("shutdown path should succeed");
     }
 
+    /// Connects a [`ManagedEsiClient`] whose first refresh succeeds and whose
+    /// second is rejected by SSO, then waits for the refresh loop to flag
+    /// reauth.
+    async fn managed_client_needing_reauth(
+        stub: &StubEsiApiClient,
+        store: &MemoryTokenStore,
+    ) -> ManagedEsiClient<StubEsiApiClient, MemoryTokenStore, FixedClock> {
+        store
+            .save_session(&managed_session("refresh", ts(510)))
+            .expect("save session");
+        stub.push_refresh(Ok(RefreshTokens {
+            access_token: "access".to_owned(),
+            access_expires_at: ts(520),
+            refresh_token: "doomed-refresh".to_owned(),
+        }));
+        stub.push_refresh(Err(rejected_refresh_error()));
+
+        let client = AuthService::with_clock(
+            stub.clone(),
+            store.clone(),
+            vec!["esi-location.read_location.v1".to_owned()],
+            FixedClock(ts(500)),
+        )
+        .connect_character(CharacterId(42))
+        .await
+        .expect("connect against stub");
+        client
+            .subscribe_reauth()
+            .expect("managed client has a reauth feed")
+            .wait_for(Option::is_some)
+            .await
+            .expect("reauth feed open");
+        client
+    }
+
+    fn managed_session(refresh_token: &str, access_expires_at: Timestamp) -> AuthSession {
+        AuthSession {
+            character_id: CharacterId(42),
+            character_name: Some("Pilot".to_owned()),
+            scopes: vec!["esi-location.read_location.v1".to_owned()],
+            access_token: format!("access-for-{refresh_token}"),
+            access_expires_at,
+            refresh_token: refresh_token.to_owned(),
+            updated_at: ts(500),
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn reauth_retry_resumes_polling_once_session_returns() {
+        let stub = StubEsiApiClient::new();
+        let store = MemoryTokenStore::default();
+        let client = managed_client_needing_reauth(&stub, &store).await;
+        let mut reauth_rx = client.subscribe_reauth().expect("reauth feed");
+        let sink = Arc::new(RecordingSink::default());
+        let mut ingestor = LocationIngestor::with_clock(
+            client,
+            SharedRecordingSink(Arc::clone(&sink)),
+            LocationPollConfig {
+                base_interval: Duration::from_secs(5),
+                ..config_for_tests()
+            },
+            FixedClock(ts(500)),
+        )
+        .with_reauth_retry(Duration::from_secs(1));
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let run = tokio::spawn(async move { ingestor.run_until_shutdown(shutdown_rx).await });
+
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        assert!(!run.is_finished(), "loop should pause, not exit");
+        assert_eq!(stub.location_calls(), 0);
+        assert_eq!(
+            stub.refresh_calls(),
+            2,
+            "the rejected refresh token must not be retried"
+        );
+
+        store
+            .save_session(&managed_session("relogin-refresh", ts(10_000)))
+            .expect("save new login");
+        stub.push_location(Ok(location(30000142, None)));
+        reauth_rx
+            .wait_for(Option::is_none)
+            .await
+            .expect("reauth should clear once the new session is stored");
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        shutdown_tx.send(true).expect("signal shutdown");
+        run.await
+            .expect("join ingestor")
+            .expect("ingestor should shut down cleanly");
+
+        let events = sink.events.lock().expect("events lock");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].to_system_id, SolarSystemId(30000142));
+        assert_eq!(stub.refresh_calls(), 2);
+        assert_eq!(
+            stub.hydrated_access_token().as_deref(),
+            Some("access-for-relogin-refresh")
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn needs_reauth_is_terminal_without_retry() {
+        let stub = StubEsiApiClient::new();
+        let store = MemoryTokenStore::default();
+        let client = managed_client_needing_reauth(&stub, &store).await;
+        let mut ingestor = LocationIngestor::with_clock(
+            client,
+            SharedRecordingSink(Arc::new(RecordingSink::default())),
+            config_for_tests(),
+            FixedClock(ts(500)),
+        );
+        let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+
+        let err = ingestor
+            .run_until_shutdown(shutdown_rx)
+            .await
+            .expect_err("reauth should stop the loop");
+        assert!(matches!(err, EsiError::NeedsReauth { .. }), "{err:?}");
+    }
+
     fn ts(epoch_secs: i64) -> Timestamp {
         Timestamp::from_epoch_secs(epoch_secs).expect("valid epoch seconds")
     }
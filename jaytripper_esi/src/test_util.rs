@@ -0,0 +1,509 @@
+//! In-memory stand-ins for the rfesi client and keyring, for driving
+//! [`ManagedEsiClient`](crate::ManagedEsiClient) end-to-end in tests.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use jaytripper_core::{
+    ids::{CharacterId, StationId, StructureId},
+    time::{Clock, Timestamp},
+};
+
+use crate::{
+    EsiError, EsiResult,
+    api::{CharacterLocation, TokenVerification},
+    auth::{AuthSession, LoginRequest},
+    client::{EsiApiClient, InitialAuthTokens, RefreshTokens, SsoAuthClient},
+    token_store::TokenStore,
+};
+
+#[derive(Default)]
+struct StubState {
+    locations: VecDeque<EsiResult<CharacterLocation>>,
+    refreshes: VecDeque<EsiResult<RefreshTokens>>,
+    wallet_balances: VecDeque<EsiResult<f64>>,
+    location_calls: usize,
+    wallet_calls: usize,
+    refresh_calls: usize,
+    hydrated_access_token: Option<String>,
+    refresh_delay: Duration,
+    request_timeout: Option<Duration>,
+}
+
+/// Scriptable [`SsoAuthClient`] + [`EsiApiClient`]. Clones share the script,
+/// so a test can keep a handle after moving the client into an
+/// [`AuthService`](crate::AuthService). Unscripted calls fail.
+#[derive(Clone, Default)]
+pub struct StubEsiApiClient {
+    state: Arc<Mutex<StubState>>,
+}
+
+impl StubEsiApiClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push_location(&self, result: EsiResult<CharacterLocation>) {
+        self.lock().locations.push_back(result);
+    }
+
+    pub fn push_refresh(&self, result: EsiResult<RefreshTokens>) {
+        self.lock().refreshes.push_back(result);
+    }
+
+    /// Makes every refresh stall for `delay` before answering.
+    pub fn set_refresh_delay(&self, delay: Duration) {
+        self.lock().refresh_delay = delay;
+    }
+
+    /// Reported as [`SsoAuthClient::request_timeout`].
+    pub fn set_request_timeout(&self, request_timeout: Option<Duration>) {
+        self.lock().request_timeout = request_timeout;
+    }
+
+    pub fn push_wallet_balance(&self, result: EsiResult<f64>) {
+        self.lock().wallet_balances.push_back(result);
+    }
+
+    pub fn location_calls(&self) -> usize {
+        self.lock().location_calls
+    }
+
+    pub fn refresh_calls(&self) -> usize {
+        self.lock().refresh_calls
+    }
+
+    pub fn wallet_calls(&self) -> usize {
+        self.lock().wallet_calls
+    }
+
+    /// The access token most recently hydrated by the auth service.
+    pub fn hydrated_access_token(&self) -> Option<String> {
+        self.lock().hydrated_access_token.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, StubState> {
+        self.state.lock().expect("stub state lock")
+    }
+}
+
+#[async_trait]
+impl SsoAuthClient for StubEsiApiClient {
+    fn begin_login(&mut self) -> EsiResult<LoginRequest> {
+        Err(EsiError::message("stub client does not support login"))
+    }
+
+    fn hydrate_session_tokens(
+        &mut self,
+        access_token: &str,
+        _access_expires_at: Timestamp,
+        _refresh_token: &str,
+    ) -> EsiResult<()> {
+        self.lock().hydrated_access_token = Some(access_token.to_owned());
+        Ok(())
+    }
+
+    async fn exchange_code(
+        &mut self,
+        _code: &str,
+        _callback_state: &str,
+    ) -> EsiResult<InitialAuthTokens> {
+        Err(EsiError::message("stub client does not support login"))
+    }
+
+    async fn refresh(&mut self, _refresh_token: &str) -> EsiResult<RefreshTokens> {
+        let delay = {
+            let mut state = self.lock();
+            state.refresh_calls += 1;
+            state.refresh_delay
+        };
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+        self.lock()
+            .refreshes
+            .pop_front()
+            .unwrap_or_else(|| Err(EsiError::message("no stubbed refresh response")))
+    }
+
+    fn request_timeout(&self) -> Option<Duration> {
+        self.lock().request_timeout
+    }
+}
+
+/// What rfesi returns when SSO rejects a refresh token (HTTP 400, e.g.
+/// `invalid_grant`).
+pub fn rejected_refresh_error() -> EsiError {
+    EsiError::from(rfesi::prelude::EsiError::InvalidStatusCode(400))
+}
+
+/// What rfesi returns when ESI is down (HTTP 503); retryable.
+pub fn unavailable_error() -> EsiError {
+    EsiError::from(rfesi::prelude::EsiError::InvalidStatusCode(503))
+}
+
+#[async_trait]
+impl EsiApiClient for StubEsiApiClient {
+    async fn ensure_api_ready(&mut self) -> EsiResult<()> {
+        Ok(())
+    }
+
+    async fn get_current_location(
+        &mut self,
+        _character_id: CharacterId,
+    ) -> EsiResult<CharacterLocation> {
+        let mut state = self.lock();
+        state.location_calls += 1;
+        state
+            .locations
+            .pop_front()
+            .unwrap_or_else(|| Err(unavailable_error()))
+    }
+
+    async fn verify_token(&mut self) -> EsiResult<TokenVerification> {
+        Err(EsiError::message("stub client does not support verify"))
+    }
+
+    async fn get_wallet_balance(&mut self, _character_id: CharacterId) -> EsiResult<f64> {
+        let mut state = self.lock();
+        state.wallet_calls += 1;
+        state
+            .wallet_balances
+            .pop_front()
+            .unwrap_or_else(|| Err(EsiError::message("no stubbed wallet response")))
+    }
+
+    async fn get_station_name(&mut self, _station_id: StationId) -> EsiResult<String> {
+        Err(EsiError::message(
+            "stub client does not support station names",
+        ))
+    }
+
+    async fn get_structure_name(&mut self, _structure_id: StructureId) -> EsiResult<String> {
+        Err(EsiError::message(
+            "stub client does not support structure names",
+        ))
+    }
+}
+
+/// [`TokenStore`] backed by a shared map instead of the system keyring.
+#[derive(Clone, Default)]
+pub struct MemoryTokenStore {
+    sessions: Arc<Mutex<HashMap<CharacterId, AuthSession>>>,
+}
+
+impl TokenStore for MemoryTokenStore {
+    fn load_session(&self, character_id: CharacterId) -> EsiResult<Option<AuthSession>> {
+        Ok(self
+            .sessions
+            .lock()
+            .expect("sessions lock")
+            .get(&character_id)
+            .cloned())
+    }
+
+    fn save_session(&self, session: &AuthSession) -> EsiResult<()> {
+        self.sessions
+            .lock()
+            .expect("sessions lock")
+            .insert(session.character_id, session.clone());
+        Ok(())
+    }
+
+    fn clear_session(&self, character_id: CharacterId) -> EsiResult<()> {
+        self.sessions
+            .lock()
+            .expect("sessions lock")
+            .remove(&character_id);
+        Ok(())
+    }
+
+    fn list_sessions(&self) -> EsiResult<Vec<AuthSession>> {
+        Ok(self
+            .sessions
+            .lock()
+            .expect("sessions lock")
+            .values()
+            .cloned()
+            .collect())
+    }
+}
+
+/// [`Clock`] that always reports the same instant.
+#[derive(Clone, Copy, Debug)]
+pub struct FixedClock(pub Timestamp);
+
+impl Clock for FixedClock {
+    fn now(&self) -> Timestamp {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use jaytripper_core::{
+        ids::{CharacterId, SolarSystemId},
+        time::Timestamp,
+    };
+
+    use super::{FixedClock, MemoryTokenStore, StubEsiApiClient, rejected_refresh_error};
+    use crate::{
+        AuthService, AuthSession, CharacterLocation, EsiClient, EsiError, ManagedEsiClient,
+        RefreshTokens, TokenStore, WALLET_READ_SCOPE,
+    };
+
+    #[tokio::test]
+    async fn managed_client_connects_refreshes_and_polls_against_stub() {
+        let stub = StubEsiApiClient::new();
+        let store = MemoryTokenStore::default();
+        store
+            .save_session(&AuthSession {
+                character_id: CharacterId(9001),
+                character_name: Some("Pilot".to_owned()),
+                scopes: vec!["esi-location.read_location.v1".to_owned()],
+                access_token: "stale-access".to_owned(),
+                access_expires_at: ts(510),
+                refresh_token: "refresh".to_owned(),
+                updated_at: ts(100),
+            })
+            .expect("save session");
+        stub.push_refresh(Ok(RefreshTokens {
+            access_token: "fresh-access".to_owned(),
+            access_expires_at: ts(10_000),
+            refresh_token: "fresh-refresh".to_owned(),
+        }));
+        stub.push_location(Ok(CharacterLocation {
+            solar_system_id: SolarSystemId(30000142),
+            station_id: None,
+            structure_id: None,
+        }));
+
+        let client = AuthService::with_clock(
+            stub.clone(),
+            store.clone(),
+            vec!["esi-location.read_location.v1".to_owned()],
+            FixedClock(ts(500)),
+        )
+        .connect_character(CharacterId(9001))
+        .await
+        .expect("connect against stub");
+
+        let location = client.get_current_location().await.expect("poll location");
+        assert_eq!(location.solar_system_id, SolarSystemId(30000142));
+        assert_eq!(stub.refresh_calls(), 1);
+        assert_eq!(stub.location_calls(), 1);
+        assert_eq!(
+            stub.hydrated_access_token().as_deref(),
+            Some("fresh-access")
+        );
+        assert_eq!(
+            store
+                .load_session(CharacterId(9001))
+                .expect("load session")
+                .expect("session stored")
+                .refresh_token,
+            "fresh-refresh"
+        );
+
+        client
+            .get_current_location()
+            .await
+            .expect_err("unscripted poll should fail");
+    }
+
+    #[tokio::test]
+    async fn slow_refresh_times_out_as_retryable_and_keeps_session() {
+        let stub = StubEsiApiClient::new();
+        stub.set_refresh_delay(Duration::from_secs(30));
+        stub.set_request_timeout(Some(Duration::from_millis(20)));
+        let store = MemoryTokenStore::default();
+        let session = AuthSession {
+            character_id: CharacterId(9001),
+            character_name: Some("Pilot".to_owned()),
+            scopes: vec!["esi-location.read_location.v1".to_owned()],
+            access_token: "stale-access".to_owned(),
+            access_expires_at: ts(510),
+            refresh_token: "refresh".to_owned(),
+            updated_at: ts(100),
+        };
+        store.save_session(&session).expect("save session");
+
+        let err = AuthService::with_clock(
+            stub.clone(),
+            store.clone(),
+            vec!["esi-location.read_location.v1".to_owned()],
+            FixedClock(ts(500)),
+        )
+        .connect_character(CharacterId(9001))
+        .await
+        .err()
+        .expect("stalled refresh should fail the connect");
+
+        assert!(
+            matches!(
+                err,
+                EsiError::Timeout {
+                    operation: "refresh",
+                    ..
+                }
+            ),
+            "{err:?}"
+        );
+        assert!(err.is_retryable());
+        assert_eq!(stub.refresh_calls(), 1);
+        assert_eq!(
+            store.load_session(CharacterId(9001)).expect("load session"),
+            Some(session),
+            "a timed-out refresh must not cost the session"
+        );
+    }
+
+    #[tokio::test]
+    async fn connect_or_clear_removes_session_when_refresh_is_rejected() {
+        let stub = StubEsiApiClient::new();
+        let store = MemoryTokenStore::default();
+        let session = AuthSession {
+            character_id: CharacterId(9001),
+            character_name: Some("Pilot".to_owned()),
+            scopes: vec!["esi-location.read_location.v1".to_owned()],
+            access_token: "stale-access".to_owned(),
+            access_expires_at: ts(510),
+            refresh_token: "revoked-refresh".to_owned(),
+            updated_at: ts(100),
+        };
+        store.save_session(&session).expect("save session");
+        let auth = || {
+            AuthService::with_clock(
+                stub.clone(),
+                store.clone(),
+                vec!["esi-location.read_location.v1".to_owned()],
+                FixedClock(ts(500)),
+            )
+        };
+
+        stub.push_refresh(Err(rejected_refresh_error()));
+        let err = ManagedEsiClient::connect(auth(), CharacterId(9001))
+            .await
+            .err()
+            .expect("plain connect should need reauth");
+        assert!(matches!(err, EsiError::NeedsReauth { .. }), "{err:?}");
+        assert_eq!(
+            store.load_session(CharacterId(9001)).expect("load session"),
+            Some(session),
+            "plain connect must keep the session"
+        );
+
+        stub.push_refresh(Err(rejected_refresh_error()));
+        let err = ManagedEsiClient::connect_or_clear(auth(), CharacterId(9001))
+            .await
+            .err()
+            .expect("connect_or_clear should need reauth");
+        assert!(matches!(err, EsiError::NeedsReauth { .. }), "{err:?}");
+        assert_eq!(
+            store.load_session(CharacterId(9001)).expect("load session"),
+            None
+        );
+        assert_eq!(stub.refresh_calls(), 2);
+    }
+
+    #[tokio::test]
+    async fn connect_or_clear_keeps_session_when_refresh_fails_transiently() {
+        let stub = StubEsiApiClient::new();
+        let store = MemoryTokenStore::default();
+        let session = AuthSession {
+            character_id: CharacterId(9001),
+            character_name: Some("Pilot".to_owned()),
+            scopes: vec!["esi-location.read_location.v1".to_owned()],
+            access_token: "stale-access".to_owned(),
+            access_expires_at: ts(510),
+            refresh_token: "refresh".to_owned(),
+            updated_at: ts(100),
+        };
+        store.save_session(&session).expect("save session");
+        stub.push_refresh(Err(EsiError::from(
+            rfesi::prelude::EsiError::InvalidStatusCode(503),
+        )));
+
+        let err = ManagedEsiClient::connect_or_clear(
+            AuthService::with_clock(
+                stub.clone(),
+                store.clone(),
+                vec!["esi-location.read_location.v1".to_owned()],
+                FixedClock(ts(500)),
+            ),
+            CharacterId(9001),
+        )
+        .await
+        .err()
+        .expect("unavailable SSO should fail the connect");
+
+        assert!(err.is_retryable(), "{err:?}");
+        assert!(!matches!(err, EsiError::NeedsReauth { .. }), "{err:?}");
+        assert_eq!(
+            store.load_session(CharacterId(9001)).expect("load session"),
+            Some(session),
+            "a transient refresh failure must not clear the session"
+        );
+    }
+
+    #[tokio::test]
+    async fn wallet_balance_requires_wallet_scope() {
+        let stub = StubEsiApiClient::new();
+        let store = MemoryTokenStore::default();
+        let session = AuthSession {
+            character_id: CharacterId(9001),
+            character_name: Some("Pilot".to_owned()),
+            scopes: vec!["esi-location.read_location.v1".to_owned()],
+            access_token: "access".to_owned(),
+            access_expires_at: ts(10_000),
+            refresh_token: "refresh".to_owned(),
+            updated_at: ts(100),
+        };
+        store.save_session(&session).expect("save session");
+        stub.push_wallet_balance(Ok(1_234_567.89));
+
+        let client = AuthService::with_clock(
+            stub.clone(),
+            store.clone(),
+            vec!["esi-location.read_location.v1".to_owned()],
+            FixedClock(ts(500)),
+        )
+        .connect_character(CharacterId(9001))
+        .await
+        .expect("connect against stub");
+
+        let err = client
+            .get_wallet_balance()
+            .await
+            .expect_err("wallet read without scope should fail");
+        assert!(
+            matches!(&err, EsiError::MissingRequiredScopes { missing } if missing == &[WALLET_READ_SCOPE]),
+            "{err:?}"
+        );
+        assert_eq!(stub.wallet_calls(), 0);
+
+        store
+            .save_session(&AuthSession {
+                scopes: vec![
+                    "esi-location.read_location.v1".to_owned(),
+                    WALLET_READ_SCOPE.to_owned(),
+                ],
+                ..session
+            })
+            .expect("save scoped session");
+
+        let balance = client.get_wallet_balance().await.expect("read wallet");
+        assert_eq!(balance, 1_234_567.89);
+        assert_eq!(stub.wallet_calls(), 1);
+    }
+
+    fn ts(epoch_secs: i64) -> Timestamp {
+        Timestamp::from_epoch_secs(epoch_secs).expect("valid epoch seconds")
+    }
+}
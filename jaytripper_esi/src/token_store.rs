@@ -1,12 +1,18 @@
 use jaytripper_core::ids::CharacterId;
 use keyring::Entry;
 
-use crate::{EsiResult, auth::AuthSession};
+use crate::{EsiError, EsiResult, auth::AuthSession};
 
 pub trait TokenStore {
     fn load_session(&self, character_id: CharacterId) -> EsiResult<Option<AuthSession>>;
     fn save_session(&self, session: &AuthSession) -> EsiResult<()>;
     fn clear_session(&self, character_id: CharacterId) -> EsiResult<()>;
+
+    /// Every stored session. Stores that cannot enumerate their entries fail
+    /// by default.
+    fn list_sessions(&self) -> EsiResult<Vec<AuthSession>> {
+        Err(EsiError::message("token store does not list sessions"))
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -28,10 +34,40 @@ impl KeyringTokenStore {
     }
 
     fn entry_for_character(&self, character_id: CharacterId) -> EsiResult<Entry> {
-        Ok(Entry::new(
-            &self.service,
-            &self.account_for_character(character_id),
-        )?)
+        Entry::new(&self.service, &self.account_for_character(character_id)).map_err(keyring_error)
+    }
+
+    /// Keyrings cannot enumerate entries, so stored character ids are tracked
+    /// in a separate index entry. Sessions saved before the index existed are
+    /// added to it the next time they are loaded or saved by id.
+    fn index_entry(&self) -> EsiResult<Entry> {
+        Entry::new(&self.service, &format!("{}:index", self.account_prefix)).map_err(keyring_error)
+    }
+
+    /// Fails on an unreadable index rather than treating it as empty, which
+    /// would let the next write drop every other indexed character.
+    fn read_index(&self) -> EsiResult<Vec<u64>> {
+        match self.index_entry()?.get_password() {
+            Ok(raw) => parse_index(&raw),
+            Err(keyring::Error::NoEntry) => Ok(Vec::new()),
+            Err(err) => Err(keyring_error(err)),
+        }
+    }
+
+    fn add_to_index(&self, character_id: CharacterId) -> EsiResult<()> {
+        if self.read_index()?.contains(&character_id.0) {
+            return Ok(());
+        }
+        self.update_index(|index| index.push(character_id.0))
+    }
+
+    fn update_index(&self, update: impl FnOnce(&mut Vec<u64>)) -> EsiResult<()> {
+        let mut index = self.read_index()?;
+        update(&mut index);
+        let raw = serde_json::to_string(&index)?;
+        self.index_entry()?
+            .set_password(&raw)
+            .map_err(keyring_error)
     }
 }
 
@@ -40,7 +76,14 @@ impl TokenStore for KeyringTokenStore {
         let entry = self.entry_for_character(character_id)?;
         match entry.get_password() {
             Ok(raw) => match serde_json::from_str(&raw) {
-                Ok(session) => Ok(Some(session)),
+                Ok(session) => {
+                    if let Err(err) = self.add_to_index(character_id) {
+                        log::warn!(
+                            "failed to index keyring session for character {character_id}: {err}"
+                        );
+                    }
+                    Ok(Some(session))
+                }
                 Err(err) => {
                     log::error!(
                         "failed to deserialize keyring session for character {character_id}: {err}"
@@ -49,22 +92,99 @@ impl TokenStore for KeyringTokenStore {
                 }
             },
             Err(keyring::Error::NoEntry) => Ok(None),
-            Err(err) => Err(err.into()),
+            Err(err) => Err(keyring_error(err)),
         }
     }
 
     fn save_session(&self, session: &AuthSession) -> EsiResult<()> {
         let entry = self.entry_for_character(session.character_id)?;
         let raw = serde_json::to_string(session)?;
-        entry.set_password(&raw)?;
-        Ok(())
+        entry.set_password(&raw).map_err(keyring_error)?;
+        self.add_to_index(session.character_id)
     }
 
     fn clear_session(&self, character_id: CharacterId) -> EsiResult<()> {
         let entry = self.entry_for_character(character_id)?;
         match entry.delete_credential() {
-            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
-            Err(err) => Err(err.into()),
+            Ok(()) | Err(keyring::Error::NoEntry) => {}
+            Err(err) => return Err(keyring_error(err)),
+        }
+        self.update_index(|index| index.retain(|id| *id != character_id.0))
+    }
+
+    fn list_sessions(&self) -> EsiResult<Vec<AuthSession>> {
+        let mut sessions = Vec::new();
+        for character_id in self.read_index()? {
+            if let Some(session) = self.load_session(CharacterId(character_id))? {
+                sessions.push(session);
+            }
+        }
+        Ok(sessions)
+    }
+}
+
+fn parse_index(raw: &str) -> EsiResult<Vec<u64>> {
+    serde_json::from_str(raw).map_err(EsiError::CorruptSessionIndex)
+}
+
+/// Maps keyring failures, turning "no usable backend" cases into
+/// [`EsiError::KeyringUnavailable`] so users get an actionable message
+/// instead of an opaque platform error.
+fn keyring_error(err: keyring::Error) -> EsiError {
+    match err {
+        keyring::Error::PlatformFailure(detail) | keyring::Error::NoStorageAccess(detail) => {
+            EsiError::KeyringUnavailable {
+                detail: detail.to_string(),
+            }
         }
+        other => EsiError::Keyring(other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{keyring_error, parse_index};
+    use crate::EsiError;
+
+    #[test]
+    fn corrupt_index_is_an_error_not_an_empty_index() {
+        assert_eq!(
+            parse_index("[90000001,90000002]").expect("valid index"),
+            vec![90000001, 90000002]
+        );
+
+        let err = parse_index("{not json").expect_err("corrupt index");
+        assert!(matches!(err, EsiError::CorruptSessionIndex(_)), "{err:?}");
+    }
+
+    #[test]
+    fn missing_backend_maps_to_actionable_error() {
+        let err = keyring_error(keyring::Error::PlatformFailure(
+            "org.freedesktop.secrets was not provided by any .service files".into(),
+        ));
+
+        assert!(matches!(err, EsiError::KeyringUnavailable { .. }));
+        let message = err.to_string();
+        assert!(message.contains("org.freedesktop.secrets"), "{message}");
+        assert!(message.contains("Secret Service"), "{message}");
+    }
+
+    #[test]
+    fn locked_storage_maps_to_actionable_error() {
+        let err = keyring_error(keyring::Error::NoStorageAccess(
+            "collection is locked".into(),
+        ));
+
+        assert!(matches!(err, EsiError::KeyringUnavailable { .. }));
+    }
+
+    #[test]
+    fn other_keyring_errors_are_wrapped_unchanged() {
+        let err = keyring_error(keyring::Error::TooLong("account".to_owned(), 8));
+
+        assert!(matches!(
+            err,
+            EsiError::Keyring(keyring::Error::TooLong(_, 8))
+        ));
     }
 }
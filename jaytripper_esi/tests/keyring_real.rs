@@ -79,6 +79,17 @@ fn keyring_round_trip_save_load_clear() {
         .expect("loading second session after first clear should succeed");
     assert!(second_loaded_after_first_clear.is_some());
 
+    let listed = store
+        .list_sessions()
+        .expect("listing sessions in keyring should succeed");
+    assert_eq!(
+        listed
+            .iter()
+            .map(|session| session.character_id)
+            .collect::<Vec<_>>(),
+        vec![CharacterId(987_654_321)]
+    );
+
     store
         .clear_session(CharacterId(987_654_321))
         .expect("clearing second session in keyring should succeed");
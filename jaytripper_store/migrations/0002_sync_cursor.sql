@@ -0,0 +1,4 @@
+CREATE TABLE IF NOT EXISTS sync_cursor (
+    peer_id TEXT PRIMARY KEY NOT NULL,
+    last_seq INTEGER NOT NULL
+);
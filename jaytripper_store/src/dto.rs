@@ -0,0 +1,234 @@
+//! Stable, versioned serialization of stored events for external formats
+//! (exports, imports, `--json` output).
+//!
+//! Field names are pinned with explicit `#[serde(rename)]` so refactoring the
+//! internal [`EventEnvelope`]/[`EventRecord`] types cannot silently change the
+//! wire format. Any change to these shapes must bump
+//! [`EVENT_DTO_FORMAT_VERSION`].
+
+use std::str::FromStr;
+
+use jaytripper_core::{Timestamp, ids::CharacterId};
+use serde::{Deserialize, Serialize};
+
+use crate::{EventEnvelope, EventRecord, EventSource, GlobalSeq, StoreError};
+
+pub const EVENT_DTO_FORMAT_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EventEnvelopeDto {
+    #[serde(rename = "format_version")]
+    pub format_version: u32,
+    #[serde(rename = "event_id")]
+    pub event_id: String,
+    #[serde(rename = "event_type")]
+    pub event_type: String,
+    #[serde(rename = "schema_version")]
+    pub schema_version: i64,
+    #[serde(rename = "stream_key")]
+    pub stream_key: String,
+    #[serde(rename = "occurred_at_epoch_millis")]
+    pub occurred_at_epoch_millis: i64,
+    #[serde(rename = "recorded_at_epoch_millis")]
+    pub recorded_at_epoch_millis: i64,
+    #[serde(rename = "attribution_character_id")]
+    pub attribution_character_id: Option<u64>,
+    #[serde(rename = "source")]
+    pub source: String,
+    /// The event payload embedded as JSON rather than as an escaped string.
+    #[serde(rename = "payload")]
+    pub payload: serde_json::Value,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EventRecordDto {
+    #[serde(rename = "global_seq")]
+    pub global_seq: i64,
+    #[serde(flatten)]
+    pub envelope: EventEnvelopeDto,
+}
+
+impl TryFrom<&EventEnvelope> for EventEnvelopeDto {
+    type Error = StoreError;
+
+    fn try_from(envelope: &EventEnvelope) -> Result<Self, Self::Error> {
+        Ok(Self {
+            format_version: EVENT_DTO_FORMAT_VERSION,
+            event_id: envelope.event_id.clone(),
+            event_type: envelope.event_type.clone(),
+            schema_version: envelope.schema_version,
+            stream_key: envelope.stream_key.clone(),
+            occurred_at_epoch_millis: envelope.occurred_at.as_epoch_millis(),
+            recorded_at_epoch_millis: envelope.recorded_at.as_epoch_millis(),
+            attribution_character_id: envelope.attribution_character_id.map(|id| id.0),
+            source: envelope.source.as_str().to_owned(),
+            payload: serde_json::from_str(&envelope.payload_json)?,
+        })
+    }
+}
+
+impl TryFrom<EventEnvelopeDto> for EventEnvelope {
+    type Error = StoreError;
+
+    fn try_from(dto: EventEnvelopeDto) -> Result<Self, Self::Error> {
+        if dto.format_version != EVENT_DTO_FORMAT_VERSION {
+            return Err(StoreError::UnsupportedDtoFormatVersion(dto.format_version));
+        }
+
+        Ok(Self {
+            event_id: dto.event_id,
+            event_type: dto.event_type,
+            schema_version: dto.schema_version,
+            stream_key: dto.stream_key,
+            occurred_at: Timestamp::from_epoch_millis(dto.occurred_at_epoch_millis)
+                .ok_or(StoreError::InvalidEpochMillis(dto.occurred_at_epoch_millis))?,
+            recorded_at: Timestamp::from_epoch_millis(dto.recorded_at_epoch_millis)
+                .ok_or(StoreError::InvalidEpochMillis(dto.recorded_at_epoch_millis))?,
+            attribution_character_id: dto.attribution_character_id.map(CharacterId),
+            source: EventSource::from_str(&dto.source)?,
+            payload_json: serde_json::to_string(&dto.payload)?,
+        })
+    }
+}
+
+impl TryFrom<&EventRecord> for EventRecordDto {
+    type Error = StoreError;
+
+    fn try_from(record: &EventRecord) -> Result<Self, Self::Error> {
+        Ok(Self {
+            global_seq: record.global_seq.0,
+            envelope: EventEnvelopeDto::try_from(&record.envelope)?,
+        })
+    }
+}
+
+impl TryFrom<EventRecordDto> for EventRecord {
+    type Error = StoreError;
+
+    fn try_from(dto: EventRecordDto) -> Result<Self, Self::Error> {
+        Ok(Self {
+            global_seq: GlobalSeq(dto.global_seq),
+            envelope: EventEnvelope::try_from(dto.envelope)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jaytripper_core::{Timestamp, ids::CharacterId};
+
+    use super::{EventEnvelopeDto, EventRecordDto};
+    use crate::{EventEnvelope, EventRecord, EventSource, GlobalSeq, StoreError};
+
+    fn ts_millis(epoch_millis: i64) -> Timestamp {
+        Timestamp::from_epoch_millis(epoch_millis).expect("valid epoch millis")
+    }
+
+    fn movement_record() -> EventRecord {
+        EventRecord {
+            global_seq: GlobalSeq(7),
+            envelope: EventEnvelope {
+                event_id: "evt-move".to_owned(),
+                event_type: "character_moved".to_owned(),
+                schema_version: 1,
+                stream_key: "character:42".to_owned(),
+                occurred_at: ts_millis(1_700_000_000_123),
+                recorded_at: ts_millis(1_700_000_001_123),
+                attribution_character_id: Some(CharacterId(42)),
+                source: EventSource::Esi,
+                payload_json: r#"{"from_system_id":30000142,"to_system_id":30002510}"#.to_owned(),
+            },
+        }
+    }
+
+    fn signature_record() -> EventRecord {
+        EventRecord {
+            global_seq: GlobalSeq(8),
+            envelope: EventEnvelope {
+                event_id: "evt-sigs".to_owned(),
+                event_type: "system_signatures_observed".to_owned(),
+                schema_version: 1,
+                stream_key: "system:31000001".to_owned(),
+                occurred_at: ts_millis(1_700_000_100_000),
+                recorded_at: ts_millis(1_700_000_100_500),
+                attribution_character_id: None,
+                source: EventSource::Manual,
+                payload_json: r#"{"snapshot_id":"snap-1","entries":[{"signature_id":"ABC-123","group":"Cosmic Signature","site_type":"Gas Site","name":null,"scan_percent":10.0}]}"#
+                    .to_owned(),
+            },
+        }
+    }
+
+    #[test]
+    fn movement_record_json_shape_is_pinned() {
+        let dto = EventRecordDto::try_from(&movement_record()).expect("to dto");
+
+        assert_eq!(
+            serde_json::to_string(&dto).expect("serialize"),
+            concat!(
+                r#"{"global_seq":7,"format_version":1,"event_id":"evt-move","#,
+                r#""event_type":"character_moved","schema_version":1,"#,
+                r#""stream_key":"character:42","occurred_at_epoch_millis":1700000000123,"#,
+                r#""recorded_at_epoch_millis":1700000001123,"attribution_character_id":42,"#,
+                r#""source":"esi","payload":{"from_system_id":30000142,"to_system_id":30002510}}"#,
+            )
+        );
+    }
+
+    #[test]
+    fn signature_record_json_shape_is_pinned() {
+        let dto = EventRecordDto::try_from(&signature_record()).expect("to dto");
+
+        assert_eq!(
+            serde_json::to_string(&dto).expect("serialize"),
+            concat!(
+                r#"{"global_seq":8,"format_version":1,"event_id":"evt-sigs","#,
+                r#""event_type":"system_signatures_observed","schema_version":1,"#,
+                r#""stream_key":"system:31000001","occurred_at_epoch_millis":1700000100000,"#,
+                r#""recorded_at_epoch_millis":1700000100500,"attribution_character_id":null,"#,
+                r#""source":"manual","payload":{"entries":[{"group":"Cosmic Signature","#,
+                r#""name":null,"scan_percent":10.0,"signature_id":"ABC-123","#,
+                r#""site_type":"Gas Site"}],"snapshot_id":"snap-1"}}"#,
+            )
+        );
+    }
+
+    #[test]
+    fn records_round_trip_through_json() {
+        for record in [movement_record(), signature_record()] {
+            let json = serde_json::to_string(&EventRecordDto::try_from(&record).expect("to dto"))
+                .expect("serialize");
+            let dto: EventRecordDto = serde_json::from_str(&json).expect("deserialize");
+            let decoded = EventRecord::try_from(dto).expect("from dto");
+
+            assert_eq!(decoded.global_seq, record.global_seq);
+            assert_eq!(
+                serde_json::from_str::<serde_json::Value>(&decoded.envelope.payload_json)
+                    .expect("decoded payload"),
+                serde_json::from_str::<serde_json::Value>(&record.envelope.payload_json)
+                    .expect("original payload"),
+            );
+            assert_eq!(
+                EventEnvelope {
+                    payload_json: String::new(),
+                    ..decoded.envelope
+                },
+                EventEnvelope {
+                    payload_json: String::new(),
+                    ..record.envelope
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_format_version_is_rejected() {
+        let mut dto = EventEnvelopeDto::try_from(&movement_record().envelope).expect("to dto");
+        dto.format_version = 99;
+
+        assert!(matches!(
+            EventEnvelope::try_from(dto),
+            Err(StoreError::UnsupportedDtoFormatVersion(99))
+        ));
+    }
+}
@@ -3,9 +3,28 @@ pub enum StoreError {
     #[error("database error: {0}")]
     Database(#[from] sqlx::Error),
 
+    #[error("failed to create database directory {path}: {source}")]
+    DatabaseDirCreate {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+
     #[error("migration error: {0}")]
     Migration(#[from] sqlx::migrate::MigrateError),
 
+    #[error(
+        "database has migration {found} applied but this build expects up to {expected}; it was likely written by a different jaytripper version"
+    )]
+    MigrationVersionMismatch { found: i64, expected: i64 },
+
+    #[error(
+        "migration {version} in the database differs from the one in this build; the schema may have been edited by hand"
+    )]
+    MigrationChecksumMismatch { version: i64 },
+
+    #[error("migration {version} failed to apply: {source}")]
+    MigrationFailed { version: i64, source: sqlx::Error },
+
     #[error("invalid event source '{0}'")]
     InvalidEventSource(String),
 
@@ -18,6 +37,30 @@ pub enum StoreError {
     #[error("invalid unix epoch milliseconds value: {0}")]
     InvalidEpochMillis(i64),
 
+    #[error(
+        "event occurred_at {occurred_at_epoch_millis}ms is too far ahead of recorded_at {recorded_at_epoch_millis}ms"
+    )]
+    FutureTimestamp {
+        occurred_at_epoch_millis: i64,
+        recorded_at_epoch_millis: i64,
+    },
+
+    #[error("{event_type} event has schema version {actual}, expected {expected}")]
+    UnexpectedSchemaVersion {
+        event_type: String,
+        expected: i64,
+        actual: i64,
+    },
+
+    #[error("unsupported event format version {0}")]
+    UnsupportedDtoFormatVersion(u32),
+
+    #[error("{stream_key} gained events after seq {through_seq} while being compacted")]
+    CompactionConflict {
+        stream_key: String,
+        through_seq: i64,
+    },
+
     #[error("payload serialization failed: {0}")]
     PayloadSerialization(#[from] serde_json::Error),
 }
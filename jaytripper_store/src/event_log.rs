@@ -1,21 +1,72 @@
-use std::{path::Path, str::FromStr, time::Duration};
+use std::{collections::VecDeque, future::Future, path::Path, str::FromStr, time::Duration};
 
 use async_trait::async_trait;
-use futures_util::TryStreamExt;
+use futures_util::{Stream, TryStreamExt};
 use jaytripper_core::{
     CHARACTER_MOVED_EVENT_TYPE, CHARACTER_MOVED_SCHEMA_VERSION, MovementEvent, MovementEventSink,
-    MovementEventSource, SYSTEM_SIGNATURES_OBSERVED_EVENT_TYPE,
-    SYSTEM_SIGNATURES_OBSERVED_SCHEMA_VERSION, SignatureEventSource, SystemSignaturesObservedEvent,
-    Timestamp, character_stream_key, ids::CharacterId, system_stream_key,
+    MovementEventSource, STRUCTURE_ENTERED_EVENT_TYPE, STRUCTURE_ENTERED_SCHEMA_VERSION,
+    SYSTEM_SIGNATURES_BASELINE_EVENT_TYPE, SYSTEM_SIGNATURES_BASELINE_SCHEMA_VERSION,
+    SYSTEM_SIGNATURES_CLEARED_EVENT_TYPE, SYSTEM_SIGNATURES_CLEARED_SCHEMA_VERSION,
+    SYSTEM_SIGNATURES_OBSERVED_EVENT_TYPE, SYSTEM_SIGNATURES_OBSERVED_SCHEMA_VERSION,
+    SignatureEventSource, StructureEnteredEvent, SystemSignaturesBaselinePayload,
+    SystemSignaturesClearedEvent, SystemSignaturesObservedEvent, Timestamp, character_stream_key,
+    ids::{CharacterId, SolarSystemId},
+    system_stream_key,
 };
 use serde::{Deserialize, Serialize};
 use sqlx::{
-    FromRow, SqlitePool,
+    FromRow, SqliteExecutor, SqlitePool,
+    migrate::{Migrate, MigrateError, Migrator},
     sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous},
 };
 
 use crate::StoreError;
 
+static MIGRATOR: Migrator = sqlx::migrate!();
+
+/// Applies pending migrations, reporting an applied version this build does
+/// not know as a version mismatch and an edited one as a checksum mismatch
+/// rather than the raw sqlx error.
+async fn run_migrations(pool: &SqlitePool) -> Result<(), StoreError> {
+    match MIGRATOR.run(pool).await {
+        Ok(()) => Ok(()),
+        Err(MigrateError::VersionMismatch(version)) => {
+            Err(StoreError::MigrationChecksumMismatch { version })
+        }
+        Err(MigrateError::ExecuteMigration(source, version)) => {
+            Err(StoreError::MigrationFailed { version, source })
+        }
+        Err(MigrateError::Execute(err)) => Err(StoreError::Database(err)),
+        Err(MigrateError::VersionMissing(version)) => {
+            let mut conn = pool.acquire().await?;
+            let found = conn
+                .list_applied_migrations()
+                .await?
+                .iter()
+                .map(|migration| migration.version)
+                .max()
+                .unwrap_or(version);
+            Err(StoreError::MigrationVersionMismatch {
+                found,
+                expected: latest_migration_version(),
+            })
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn latest_migration_version() -> i64 {
+    MIGRATOR
+        .iter()
+        .map(|migration| migration.version)
+        .max()
+        .unwrap_or_default()
+}
+
+/// Extra attempts after the busy timeout has already expired once.
+const BUSY_RETRY_ATTEMPTS: u32 = 3;
+const BUSY_RETRY_BASE_DELAY: Duration = Duration::from_millis(50);
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum EventSource {
@@ -80,13 +131,77 @@ impl std::fmt::Display for GlobalSeq {
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MigrationStatus {
+    pub applied_versions: Vec<i64>,
+    pub pending_versions: Vec<i64>,
+}
+
+impl MigrationStatus {
+    pub fn is_up_to_date(&self) -> bool {
+        self.pending_versions.is_empty()
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StoreOptions {
+    /// When set, appends whose `occurred_at` is further than this ahead of
+    /// `recorded_at` are rejected with [`StoreError::FutureTimestamp`]. Guards
+    /// the timeline against clients with a badly skewed clock.
+    pub max_future_skew: Option<Duration>,
+    /// How appends of a known event type with an unexpected schema version are
+    /// handled. Event types the store does not know are never checked.
+    pub schema_version_policy: SchemaVersionPolicy,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SchemaVersionPolicy {
+    /// Log a warning and store the event anyway.
+    #[default]
+    Warn,
+    /// Reject the append with [`StoreError::UnexpectedSchemaVersion`].
+    Reject,
+}
+
+/// Schema version this build writes for `event_type`, if it is a known type.
+fn known_schema_version(event_type: &str) -> Option<i64> {
+    match event_type {
+        CHARACTER_MOVED_EVENT_TYPE => Some(CHARACTER_MOVED_SCHEMA_VERSION),
+        SYSTEM_SIGNATURES_OBSERVED_EVENT_TYPE => Some(SYSTEM_SIGNATURES_OBSERVED_SCHEMA_VERSION),
+        SYSTEM_SIGNATURES_CLEARED_EVENT_TYPE => Some(SYSTEM_SIGNATURES_CLEARED_SCHEMA_VERSION),
+        SYSTEM_SIGNATURES_BASELINE_EVENT_TYPE => Some(SYSTEM_SIGNATURES_BASELINE_SCHEMA_VERSION),
+        STRUCTURE_ENTERED_EVENT_TYPE => Some(STRUCTURE_ENTERED_SCHEMA_VERSION),
+        _ => None,
+    }
+}
+
 #[derive(Clone)]
 pub struct EventLogStore {
     pool: SqlitePool,
+    options: StoreOptions,
 }
 
 impl EventLogStore {
+    /// Opens (creating if needed) the database, including any missing parent
+    /// directories, and applies pending migrations.
     pub async fn connect(database_path: impl AsRef<Path>) -> Result<Self, StoreError> {
+        Self::connect_with_options(database_path, StoreOptions::default()).await
+    }
+
+    pub async fn connect_with_options(
+        database_path: impl AsRef<Path>,
+        options: StoreOptions,
+    ) -> Result<Self, StoreError> {
+        let database_path = database_path.as_ref();
+        if let Some(parent) = database_path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent).map_err(|source| StoreError::DatabaseDirCreate {
+                path: parent.to_path_buf(),
+                source,
+            })?;
+        }
+
         let connect_options = SqliteConnectOptions::new()
             .filename(database_path)
             .create_if_missing(true)
@@ -100,12 +215,79 @@ impl EventLogStore {
             .connect_with(connect_options)
             .await?;
 
-        sqlx::migrate!().run(&pool).await?;
+        run_migrations(&pool).await?;
 
-        Ok(Self { pool })
+        Ok(Self { pool, options })
     }
 
     pub async fn append_event(&self, event: &NewEvent) -> Result<GlobalSeq, StoreError> {
+        self.check_future_skew(event)?;
+        self.check_schema_version(event)?;
+        retry_on_busy(|| Self::insert_event(&self.pool, event)).await
+    }
+
+    /// Appends all events in a single transaction, so either every event is
+    /// stored or none are.
+    pub async fn append_events(&self, events: &[NewEvent]) -> Result<Vec<GlobalSeq>, StoreError> {
+        for event in events {
+            self.check_future_skew(event)?;
+            self.check_schema_version(event)?;
+        }
+
+        let mut tx = self.pool.begin().await?;
+        let mut seqs = Vec::with_capacity(events.len());
+        for event in events {
+            seqs.push(Self::insert_event(&mut *tx, event).await?);
+        }
+        tx.commit().await?;
+
+        Ok(seqs)
+    }
+
+    fn check_future_skew(&self, event: &NewEvent) -> Result<(), StoreError> {
+        let Some(max_skew) = self.options.max_future_skew else {
+            return Ok(());
+        };
+        let ahead = event.occurred_at.signed_duration_since(event.recorded_at);
+        if ahead.to_std().is_ok_and(|ahead| ahead > max_skew) {
+            return Err(StoreError::FutureTimestamp {
+                occurred_at_epoch_millis: event.occurred_at.as_epoch_millis(),
+                recorded_at_epoch_millis: event.recorded_at.as_epoch_millis(),
+            });
+        }
+        Ok(())
+    }
+
+    fn check_schema_version(&self, event: &NewEvent) -> Result<(), StoreError> {
+        let Some(expected) = known_schema_version(&event.event_type) else {
+            return Ok(());
+        };
+        if event.schema_version == expected {
+            return Ok(());
+        }
+
+        match self.options.schema_version_policy {
+            SchemaVersionPolicy::Warn => {
+                log::warn!(
+                    "storing {} event {} with schema version {} (expected {expected})",
+                    event.event_type,
+                    event.event_id,
+                    event.schema_version
+                );
+                Ok(())
+            }
+            SchemaVersionPolicy::Reject => Err(StoreError::UnexpectedSchemaVersion {
+                event_type: event.event_type.clone(),
+                expected,
+                actual: event.schema_version,
+            }),
+        }
+    }
+
+    async fn insert_event<'e>(
+        executor: impl SqliteExecutor<'e>,
+        event: &NewEvent,
+    ) -> Result<GlobalSeq, StoreError> {
         let attribution_character_id = event
             .attribution_character_id
             .map(character_id_to_sqlite)
@@ -144,7 +326,7 @@ impl EventLogStore {
             source,
             payload_json,
         )
-        .fetch_one(&self.pool)
+        .fetch_one(executor)
         .await?;
 
         Ok(GlobalSeq(inserted.global_seq))
@@ -177,6 +359,26 @@ impl EventLogStore {
         self.append_event(&new_event).await
     }
 
+    pub async fn append_structure_entered_event_at(
+        &self,
+        event: &StructureEnteredEvent,
+        recorded_at: Timestamp,
+    ) -> Result<GlobalSeq, StoreError> {
+        let new_event = NewEvent {
+            event_id: uuid::Uuid::now_v7().to_string(),
+            event_type: STRUCTURE_ENTERED_EVENT_TYPE.to_owned(),
+            schema_version: STRUCTURE_ENTERED_SCHEMA_VERSION,
+            stream_key: character_stream_key(event.character_id),
+            occurred_at: event.observed_at,
+            recorded_at,
+            attribution_character_id: Some(event.character_id),
+            source: map_movement_source(event.source),
+            payload_json: serde_json::to_string(&event.as_payload())?,
+        };
+
+        self.append_event(&new_event).await
+    }
+
     pub async fn append_system_signatures_observed_event(
         &self,
         event: &SystemSignaturesObservedEvent,
@@ -189,11 +391,20 @@ impl EventLogStore {
         &self,
         event: &SystemSignaturesObservedEvent,
         recorded_at: Timestamp,
+    ) -> Result<GlobalSeq, StoreError> {
+        self.append_event(&signatures_observed_new_event(event, recorded_at)?)
+            .await
+    }
+
+    pub async fn append_system_signatures_cleared_event_at(
+        &self,
+        event: &SystemSignaturesClearedEvent,
+        recorded_at: Timestamp,
     ) -> Result<GlobalSeq, StoreError> {
         let new_event = NewEvent {
             event_id: uuid::Uuid::now_v7().to_string(),
-            event_type: SYSTEM_SIGNATURES_OBSERVED_EVENT_TYPE.to_owned(),
-            schema_version: SYSTEM_SIGNATURES_OBSERVED_SCHEMA_VERSION,
+            event_type: SYSTEM_SIGNATURES_CLEARED_EVENT_TYPE.to_owned(),
+            schema_version: SYSTEM_SIGNATURES_CLEARED_SCHEMA_VERSION,
             stream_key: system_stream_key(event.system_id),
             occurred_at: event.observed_at,
             recorded_at,
@@ -205,8 +416,31 @@ impl EventLogStore {
         self.append_event(&new_event).await
     }
 
+    /// Appends several signature snapshots in a single transaction.
+    pub async fn append_system_signatures_observed_events_at(
+        &self,
+        events: &[SystemSignaturesObservedEvent],
+        recorded_at: Timestamp,
+    ) -> Result<Vec<GlobalSeq>, StoreError> {
+        let new_events = events
+            .iter()
+            .map(|event| signatures_observed_new_event(event, recorded_at))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.append_events(&new_events).await
+    }
+
+    /// Collects the whole log; prefer [`Self::stream_ordered_events`] when
+    /// the log may be large.
     pub async fn read_ordered_events(&self) -> Result<Vec<EventRecord>, StoreError> {
-        let mut rows = sqlx::query_as!(
+        self.stream_ordered_events().try_collect().await
+    }
+
+    /// Yields every event in sequence order, decoding rows as they are read
+    /// instead of collecting the log first.
+    pub fn stream_ordered_events(
+        &self,
+    ) -> impl Stream<Item = Result<EventRecord, StoreError>> + Send + '_ {
+        sqlx::query_as!(
             DbEventRecord,
             r#"
             SELECT
@@ -224,6 +458,35 @@ impl EventLogStore {
             ORDER BY global_seq ASC
             "#,
         )
+        .fetch(&self.pool)
+        .map_err(StoreError::from)
+        .and_then(|row| async move { EventRecord::try_from(row) })
+    }
+
+    /// Returns the newest `limit` events, newest first, without reading the
+    /// rest of the log.
+    pub async fn read_recent_events(&self, limit: usize) -> Result<Vec<EventRecord>, StoreError> {
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        let mut rows = sqlx::query_as!(
+            DbEventRecord,
+            r#"
+            SELECT
+                global_seq AS "global_seq!",
+                event_id AS "event_id!",
+                event_type AS "event_type!",
+                schema_version AS "schema_version!",
+                stream_key AS "stream_key!",
+                occurred_at_epoch_millis AS "occurred_at_epoch_millis!",
+                recorded_at_epoch_millis AS "recorded_at_epoch_millis!",
+                attribution_character_id AS "attribution_character_id?",
+                source AS "source!",
+                payload_json AS "payload_json!"
+            FROM event_log
+            ORDER BY global_seq DESC
+            LIMIT ?1
+            "#,
+            limit,
+        )
         .fetch(&self.pool);
 
         let mut records = Vec::new();
@@ -234,6 +497,40 @@ impl EventLogStore {
         Ok(records)
     }
 
+    /// Yields every event appended after `since_seq`, in sequence order,
+    /// checking for new rows every `poll_interval` once caught up. A failed
+    /// read is yielded as an error and retried on the next poll.
+    pub fn subscribe_new_events(
+        &self,
+        since_seq: GlobalSeq,
+        poll_interval: Duration,
+    ) -> impl Stream<Item = Result<EventRecord, StoreError>> + Send + 'static {
+        let store = self.clone();
+        futures_util::stream::unfold(
+            (store, since_seq, VecDeque::new()),
+            move |(store, mut last_seq, mut pending)| async move {
+                loop {
+                    if let Some(record) = pending.pop_front() {
+                        let record: EventRecord = record;
+                        last_seq = record.global_seq;
+                        return Some((Ok(record), (store, last_seq, pending)));
+                    }
+
+                    match store.read_events_since(last_seq).await {
+                        Ok(records) if records.is_empty() => {
+                            tokio::time::sleep(poll_interval).await;
+                        }
+                        Ok(records) => pending.extend(records),
+                        Err(err) => {
+                            tokio::time::sleep(poll_interval).await;
+                            return Some((Err(err), (store, last_seq, pending)));
+                        }
+                    }
+                }
+            },
+        )
+    }
+
     pub async fn read_events_since(
         &self,
         since_seq: GlobalSeq,
@@ -268,6 +565,8 @@ impl EventLogStore {
         Ok(records)
     }
 
+    /// Served by the `(stream_key, global_seq)` index from migration 0001,
+    /// so neither the filter nor the ordering needs a table scan.
     pub async fn read_events_by_stream(
         &self,
         stream_key: &str,
@@ -302,11 +601,249 @@ impl EventLogStore {
         Ok(records)
     }
 
+    pub async fn read_events_by_stream_since(
+        &self,
+        stream_key: &str,
+        since_seq: GlobalSeq,
+    ) -> Result<Vec<EventRecord>, StoreError> {
+        let mut rows = sqlx::query_as!(
+            DbEventRecord,
+            r#"
+            SELECT
+                global_seq AS "global_seq!",
+                event_id AS "event_id!",
+                event_type AS "event_type!",
+                schema_version AS "schema_version!",
+                stream_key AS "stream_key!",
+                occurred_at_epoch_millis AS "occurred_at_epoch_millis!",
+                recorded_at_epoch_millis AS "recorded_at_epoch_millis!",
+                attribution_character_id AS "attribution_character_id?",
+                source AS "source!",
+                payload_json AS "payload_json!"
+            FROM event_log
+            WHERE stream_key = ?1 AND global_seq > ?2
+            ORDER BY global_seq ASC
+            "#,
+            stream_key,
+            since_seq.0,
+        )
+        .fetch(&self.pool);
+
+        let mut records = Vec::new();
+        while let Some(row) = rows.try_next().await? {
+            records.push(EventRecord::try_from(row)?);
+        }
+
+        Ok(records)
+    }
+
+    /// Deletes all but the newest `keep` signature snapshots for a system.
+    ///
+    /// At least one snapshot is always kept. Replay after pruning reproduces
+    /// the latest snapshot's view; cumulative fields such as the highest scan
+    /// percent seen, or signatures absent from the kept snapshots, are lost.
+    pub async fn prune_signature_history(
+        &self,
+        system_id: SolarSystemId,
+        keep: usize,
+    ) -> Result<u64, StoreError> {
+        let stream_key = system_stream_key(system_id);
+        let event_type = SYSTEM_SIGNATURES_OBSERVED_EVENT_TYPE;
+        let keep = i64::try_from(keep.max(1)).unwrap_or(i64::MAX);
+
+        let deleted = sqlx::query!(
+            r#"
+            DELETE FROM event_log
+            WHERE stream_key = ?1
+                AND event_type = ?2
+                AND global_seq NOT IN (
+                    SELECT global_seq
+                    FROM event_log
+                    WHERE stream_key = ?1 AND event_type = ?2
+                    ORDER BY global_seq DESC
+                    LIMIT ?3
+                )
+            "#,
+            stream_key,
+            event_type,
+            keep,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(deleted.rows_affected())
+    }
+
+    /// Appends `baseline` for its system and deletes the snapshots and
+    /// earlier baselines in that stream up to and including `through_seq`,
+    /// the last event the baseline was built from, in one transaction.
+    /// Returns the number of events deleted.
+    ///
+    /// Fails with [`StoreError::CompactionConflict`], leaving the stream
+    /// untouched, if the stream gained an event after `through_seq`: the
+    /// baseline would replay after it and hide it.
+    pub async fn compact_signature_history_at(
+        &self,
+        baseline: &SystemSignaturesBaselinePayload,
+        through_seq: GlobalSeq,
+        observed_at: Timestamp,
+        recorded_at: Timestamp,
+    ) -> Result<u64, StoreError> {
+        let stream_key = system_stream_key(baseline.system_id);
+        let new_event = NewEvent {
+            event_id: uuid::Uuid::now_v7().to_string(),
+            event_type: SYSTEM_SIGNATURES_BASELINE_EVENT_TYPE.to_owned(),
+            schema_version: SYSTEM_SIGNATURES_BASELINE_SCHEMA_VERSION,
+            stream_key: stream_key.clone(),
+            occurred_at: observed_at,
+            recorded_at,
+            attribution_character_id: None,
+            source: EventSource::Manual,
+            payload_json: serde_json::to_string(baseline)?,
+        };
+        self.check_future_skew(&new_event)?;
+
+        let mut tx = self.pool.begin().await?;
+        let newer = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "count!: i64"
+            FROM event_log
+            WHERE stream_key = ?1 AND global_seq > ?2
+            "#,
+            stream_key,
+            through_seq.0,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        if newer > 0 {
+            return Err(StoreError::CompactionConflict {
+                stream_key,
+                through_seq: through_seq.0,
+            });
+        }
+
+        let observed_type = SYSTEM_SIGNATURES_OBSERVED_EVENT_TYPE;
+        let baseline_type = SYSTEM_SIGNATURES_BASELINE_EVENT_TYPE;
+        let deleted = sqlx::query!(
+            r#"
+            DELETE FROM event_log
+            WHERE stream_key = ?1
+                AND event_type IN (?2, ?3)
+                AND global_seq <= ?4
+            "#,
+            stream_key,
+            observed_type,
+            baseline_type,
+            through_seq.0,
+        )
+        .execute(&mut *tx)
+        .await?;
+        Self::insert_event(&mut *tx, &new_event).await?;
+        tx.commit().await?;
+
+        Ok(deleted.rows_affected())
+    }
+
+    pub async fn migration_status(&self) -> Result<MigrationStatus, StoreError> {
+        let mut conn = self.pool.acquire().await?;
+        let mut applied_versions: Vec<i64> = conn
+            .list_applied_migrations()
+            .await?
+            .into_iter()
+            .map(|migration| migration.version)
+            .collect();
+        applied_versions.sort_unstable();
+
+        let pending_versions = MIGRATOR
+            .iter()
+            .map(|migration| migration.version)
+            .filter(|version| applied_versions.binary_search(version).is_err())
+            .collect();
+
+        Ok(MigrationStatus {
+            applied_versions,
+            pending_versions,
+        })
+    }
+
+    /// Deletes every event and sync cursor in a single transaction, leaving
+    /// an empty but migrated store. Unlike deleting the file, this is safe
+    /// while other connections hold the WAL open.
+    #[cfg(any(test, feature = "dev"))]
+    pub async fn reset(&self) -> Result<(), StoreError> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!("DELETE FROM event_log")
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!("DELETE FROM sync_cursor")
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// The last of `peer_id`'s sequence numbers pulled into this store, or
+    /// `None` if nothing has been pulled from it yet.
+    pub async fn get_sync_cursor(&self, peer_id: &str) -> Result<Option<GlobalSeq>, StoreError> {
+        let last_seq = sqlx::query_scalar!(
+            r#"SELECT last_seq AS "last_seq!" FROM sync_cursor WHERE peer_id = ?1"#,
+            peer_id,
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(last_seq.map(GlobalSeq))
+    }
+
+    /// Records `seq` as the last sequence pulled from `peer_id`, replacing any
+    /// earlier cursor for that peer.
+    pub async fn set_sync_cursor(&self, peer_id: &str, seq: GlobalSeq) -> Result<(), StoreError> {
+        sqlx::query!(
+            r#"
+            INSERT INTO sync_cursor (peer_id, last_seq)
+            VALUES (?1, ?2)
+            ON CONFLICT(peer_id) DO UPDATE SET last_seq = excluded.last_seq
+            "#,
+            peer_id,
+            seq.0,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Runs a trivial query to confirm the pool can still serve connections.
+    /// Fails once the pool has been closed.
+    pub async fn health_check(&self) -> Result<(), StoreError> {
+        sqlx::query!("SELECT 1 AS alive")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(())
+    }
+
     pub fn pool(&self) -> &SqlitePool {
         &self.pool
     }
 }
 
+fn signatures_observed_new_event(
+    event: &SystemSignaturesObservedEvent,
+    recorded_at: Timestamp,
+) -> Result<NewEvent, StoreError> {
+    Ok(NewEvent {
+        event_id: uuid::Uuid::now_v7().to_string(),
+        event_type: SYSTEM_SIGNATURES_OBSERVED_EVENT_TYPE.to_owned(),
+        schema_version: SYSTEM_SIGNATURES_OBSERVED_SCHEMA_VERSION,
+        stream_key: system_stream_key(event.system_id),
+        occurred_at: event.observed_at,
+        recorded_at,
+        attribution_character_id: event.attribution_character_id,
+        source: map_signature_source(event.source),
+        payload_json: serde_json::to_string(&event.as_payload())?,
+    })
+}
+
 #[async_trait]
 impl MovementEventSink for EventLogStore {
     type Error = StoreError;
@@ -315,6 +852,15 @@ impl MovementEventSink for EventLogStore {
         self.append_movement_event(&event).await?;
         Ok(())
     }
+
+    async fn emit_structure_entered(
+        &self,
+        event: StructureEnteredEvent,
+    ) -> Result<(), Self::Error> {
+        self.append_structure_entered_event_at(&event, Timestamp::now())
+            .await?;
+        Ok(())
+    }
 }
 
 #[derive(Debug, FromRow)]
@@ -379,21 +925,146 @@ fn map_movement_source(source: MovementEventSource) -> EventSource {
 fn map_signature_source(source: SignatureEventSource) -> EventSource {
     match source {
         SignatureEventSource::Manual => EventSource::Manual,
+        SignatureEventSource::Import => EventSource::Import,
     }
 }
 
+/// Retries `op` with exponential backoff while SQLite reports the database as
+/// busy. Any other error, or a busy error after the final attempt, is
+/// returned as-is.
+async fn retry_on_busy<T, F, Fut>(mut op: F) -> Result<T, StoreError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, StoreError>>,
+{
+    let mut delay = BUSY_RETRY_BASE_DELAY;
+    let mut retries = 0;
+    loop {
+        match op().await {
+            Err(StoreError::Database(err)) if retries < BUSY_RETRY_ATTEMPTS && is_busy(&err) => {
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+                retries += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Matches `SQLITE_BUSY` and its extended codes (e.g. `SQLITE_BUSY_SNAPSHOT`).
+fn is_busy(err: &sqlx::Error) -> bool {
+    const SQLITE_BUSY: i32 = 5;
+
+    let sqlx::Error::Database(db_err) = err else {
+        return false;
+    };
+    db_err
+        .code()
+        .and_then(|code| code.parse::<i32>().ok())
+        .is_some_and(|code| code & 0xff == SQLITE_BUSY)
+}
+
 #[cfg(test)]
 mod tests {
+    use std::{
+        borrow::Cow,
+        sync::atomic::{AtomicU32, Ordering},
+        time::Duration,
+    };
+
+    use futures_util::{StreamExt, TryStreamExt};
     use jaytripper_core::{
         CHARACTER_MOVED_EVENT_TYPE, CHARACTER_MOVED_SCHEMA_VERSION, MovementEvent,
-        MovementEventSource, SYSTEM_SIGNATURES_OBSERVED_EVENT_TYPE,
-        SYSTEM_SIGNATURES_OBSERVED_SCHEMA_VERSION, SignatureEntry, SignatureEventSource,
-        SystemSignaturesObservedEvent, SystemSignaturesObservedPayload, Timestamp,
-        ids::{CharacterId, SolarSystemId},
+        MovementEventSource, STRUCTURE_ENTERED_EVENT_TYPE, SYSTEM_SIGNATURES_BASELINE_EVENT_TYPE,
+        SYSTEM_SIGNATURES_OBSERVED_EVENT_TYPE, SYSTEM_SIGNATURES_OBSERVED_SCHEMA_VERSION,
+        SignatureEntry, SignatureEventSource, StructureEnteredEvent, StructureEnteredPayload,
+        SystemSignaturesBaselinePayload, SystemSignaturesObservedEvent,
+        SystemSignaturesObservedPayload, Timestamp,
+        ids::{CharacterId, SolarSystemId, StructureId},
     };
     use tempfile::tempdir;
 
-    use super::{EventLogStore, EventSource, NewEvent};
+    use super::{
+        BUSY_RETRY_ATTEMPTS, EventLogStore, EventRecord, EventSource, GlobalSeq, NewEvent,
+        SchemaVersionPolicy, StoreOptions, retry_on_busy,
+    };
+    use crate::{StoreError, test_util::temp_store};
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("database is locked")]
+    struct FakeDatabaseError {
+        code: &'static str,
+    }
+
+    impl sqlx::error::DatabaseError for FakeDatabaseError {
+        fn message(&self) -> &str {
+            "database is locked"
+        }
+
+        fn code(&self) -> Option<Cow<'_, str>> {
+            Some(Cow::Borrowed(self.code))
+        }
+
+        fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+            self
+        }
+
+        fn kind(&self) -> sqlx::error::ErrorKind {
+            sqlx::error::ErrorKind::Other
+        }
+    }
+
+    fn database_error(code: &'static str) -> StoreError {
+        StoreError::Database(sqlx::Error::Database(Box::new(FakeDatabaseError { code })))
+    }
+
+    #[tokio::test]
+    async fn retry_on_busy_retries_busy_errors_until_success() {
+        let attempts = AtomicU32::new(0);
+
+        let result = retry_on_busy(|| async {
+            match attempts.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(database_error("5")),
+                1 => Err(database_error("517")),
+                _ => Ok(7),
+            }
+        })
+        .await
+        .expect("third attempt should succeed");
+
+        assert_eq!(result, 7);
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn retry_on_busy_gives_up_and_ignores_other_errors() {
+        let attempts = AtomicU32::new(0);
+        let err = retry_on_busy(|| async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err::<(), _>(database_error("5"))
+        })
+        .await
+        .expect_err("persistent busy should fail");
+        assert!(matches!(err, StoreError::Database(_)));
+        assert_eq!(attempts.load(Ordering::SeqCst), BUSY_RETRY_ATTEMPTS + 1);
+
+        let attempts = AtomicU32::new(0);
+        retry_on_busy(|| async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err::<(), _>(database_error("2067"))
+        })
+        .await
+        .expect_err("constraint errors are not retried");
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
 
     #[tokio::test]
     async fn append_and_read_events_round_trip() {
@@ -453,6 +1124,50 @@ mod tests {
             .await
             .expect("read by stream");
         assert_eq!(stream.len(), 2);
+
+        let stream_since = store
+            .read_events_by_stream_since("character:42", first_seq)
+            .await
+            .expect("read by stream since sequence");
+        assert_eq!(stream_since.len(), 1);
+        assert_eq!(stream_since[0].envelope.event_id, "evt-2");
+
+        let other_stream = store
+            .read_events_by_stream_since("character:43", first_seq)
+            .await
+            .expect("read other stream since sequence");
+        assert!(other_stream.is_empty());
+    }
+
+    #[tokio::test]
+    async fn stream_reads_use_stream_key_global_seq_index() {
+        let (_temp_dir, store) = temp_store().await;
+
+        for query in [
+            "EXPLAIN QUERY PLAN SELECT * FROM event_log \
+             WHERE stream_key = ?1 ORDER BY global_seq ASC",
+            "EXPLAIN QUERY PLAN SELECT * FROM event_log \
+             WHERE stream_key = ?1 AND global_seq > ?2 ORDER BY global_seq ASC",
+        ] {
+            let plan: Vec<(i64, i64, i64, String)> = sqlx::query_as(query)
+                .bind("character:42")
+                .bind(0_i64)
+                .fetch_all(store.pool())
+                .await
+                .expect("explain query plan");
+            let details: Vec<&str> = plan.iter().map(|row| row.3.as_str()).collect();
+
+            assert!(
+                details
+                    .iter()
+                    .any(|detail| detail.contains("idx_event_log_stream_key_global_seq")),
+                "{details:?}"
+            );
+            assert!(
+                !details.iter().any(|detail| detail.contains("TEMP B-TREE")),
+                "{details:?}"
+            );
+        }
     }
 
     #[tokio::test]
@@ -476,6 +1191,335 @@ mod tests {
         assert!(events.is_empty());
     }
 
+    #[tokio::test]
+    async fn database_from_newer_build_reports_migration_version_mismatch() {
+        let temp_dir = tempdir().expect("tempdir");
+        let database_path = temp_dir.path().join("events.sqlite");
+        let store = EventLogStore::connect(&database_path)
+            .await
+            .expect("connect first");
+        sqlx::query(
+            "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time)
+             VALUES (9999, 'from a newer build', TRUE, X'00', 0)",
+        )
+        .execute(store.pool())
+        .await
+        .expect("record unknown migration");
+        store.pool().close().await;
+
+        let err = match EventLogStore::connect(&database_path).await {
+            Ok(_) => panic!("unknown applied migration should fail connect"),
+            Err(err) => err,
+        };
+
+        assert!(matches!(
+            err,
+            StoreError::MigrationVersionMismatch {
+                found: 9999,
+                expected,
+            } if expected == super::latest_migration_version()
+        ));
+    }
+
+    #[tokio::test]
+    async fn edited_applied_migration_reports_checksum_mismatch() {
+        let temp_dir = tempdir().expect("tempdir");
+        let database_path = temp_dir.path().join("events.sqlite");
+        let store = EventLogStore::connect(&database_path)
+            .await
+            .expect("connect first");
+        let first_version = super::MIGRATOR
+            .iter()
+            .map(|migration| migration.version)
+            .min()
+            .expect("at least one migration");
+        sqlx::query("UPDATE _sqlx_migrations SET checksum = X'00' WHERE version = ?")
+            .bind(first_version)
+            .execute(store.pool())
+            .await
+            .expect("edit checksum");
+        store.pool().close().await;
+
+        let err = match EventLogStore::connect(&database_path).await {
+            Ok(_) => panic!("edited migration should fail connect"),
+            Err(err) => err,
+        };
+
+        assert!(matches!(
+            err,
+            StoreError::MigrationChecksumMismatch { version } if version == first_version
+        ));
+    }
+
+    #[tokio::test]
+    async fn stream_ordered_events_yields_log_in_sequence_order() {
+        let (_temp_dir, store) = temp_store().await;
+        let mut appended = Vec::new();
+        for event_id in ["evt-1", "evt-2", "evt-3"] {
+            appended.push(
+                store
+                    .append_event(&new_event(event_id))
+                    .await
+                    .expect("append event"),
+            );
+        }
+
+        let streamed: Vec<EventRecord> = store
+            .stream_ordered_events()
+            .try_collect()
+            .await
+            .expect("stream events");
+
+        assert_eq!(
+            streamed
+                .iter()
+                .map(|record| (record.global_seq, record.envelope.event_id.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (appended[0], "evt-1"),
+                (appended[1], "evt-2"),
+                (appended[2], "evt-3"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn subscribe_new_events_yields_appended_events() {
+        let (_temp_dir, store) = temp_store().await;
+        let seen = store
+            .append_event(&new_event("evt-before"))
+            .await
+            .expect("append earlier event");
+        let mut events = Box::pin(store.subscribe_new_events(seen, Duration::from_millis(5)));
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let appended = store
+            .append_event(&new_event("evt-after"))
+            .await
+            .expect("append followed event");
+
+        let record = tokio::time::timeout(Duration::from_secs(1), events.next())
+            .await
+            .expect("stream should yield promptly")
+            .expect("stream should not end")
+            .expect("read should succeed");
+        assert_eq!(record.global_seq, appended);
+        assert_eq!(record.envelope.event_id, "evt-after");
+    }
+
+    #[tokio::test]
+    async fn append_events_is_all_or_nothing() {
+        let (_temp_dir, store) = temp_store().await;
+
+        let seqs = store
+            .append_events(&[new_event("evt-1"), new_event("evt-2")])
+            .await
+            .expect("append batch");
+        assert_eq!(seqs.len(), 2);
+        assert!(seqs[0] < seqs[1]);
+
+        store
+            .append_events(&[new_event("evt-3"), new_event("evt-1")])
+            .await
+            .expect_err("duplicate event id should fail the batch");
+        let stored = store.read_ordered_events().await.expect("read ordered");
+        assert_eq!(stored.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn schema_version_policy_controls_mismatched_known_versions() {
+        let temp_dir = tempdir().expect("tempdir");
+        let event = |event_type: &str| NewEvent {
+            event_type: event_type.to_owned(),
+            schema_version: 99,
+            ..new_event(&format!("evt-{event_type}"))
+        };
+
+        let (_warn_dir, warn_store) = temp_store().await;
+        warn_store
+            .append_event(&event(CHARACTER_MOVED_EVENT_TYPE))
+            .await
+            .expect("warn policy stores mismatched version");
+
+        let reject_store = EventLogStore::connect_with_options(
+            temp_dir.path().join("events.sqlite"),
+            StoreOptions {
+                schema_version_policy: SchemaVersionPolicy::Reject,
+                ..StoreOptions::default()
+            },
+        )
+        .await
+        .expect("connect store");
+        let err = reject_store
+            .append_event(&event(CHARACTER_MOVED_EVENT_TYPE))
+            .await
+            .expect_err("reject policy refuses mismatched version");
+        assert!(matches!(
+            err,
+            StoreError::UnexpectedSchemaVersion {
+                expected: CHARACTER_MOVED_SCHEMA_VERSION,
+                actual: 99,
+                ..
+            }
+        ));
+        reject_store
+            .append_event(&event("future_event_type"))
+            .await
+            .expect("unknown event types are not checked");
+    }
+
+    #[tokio::test]
+    async fn future_skew_guard_rejects_events_far_ahead_of_recording() {
+        let temp_dir = tempdir().expect("tempdir");
+        let store = EventLogStore::connect_with_options(
+            temp_dir.path().join("events.sqlite"),
+            StoreOptions {
+                max_future_skew: Some(Duration::from_secs(300)),
+                ..StoreOptions::default()
+            },
+        )
+        .await
+        .expect("connect store");
+        let event = |event_id: &str, occurred_at_millis: i64| NewEvent {
+            occurred_at: ts_millis(occurred_at_millis),
+            ..new_event(event_id)
+        };
+
+        let err = store
+            .append_event(&event("evt-future", 1_700_003_600_000))
+            .await
+            .expect_err("event an hour ahead should be rejected");
+        assert!(matches!(err, StoreError::FutureTimestamp { .. }));
+
+        store
+            .append_event(&event("evt-slightly-ahead", 1_700_000_060_000))
+            .await
+            .expect("small skew is tolerated");
+        assert_eq!(store.read_ordered_events().await.expect("read").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn reset_empties_store_and_keeps_it_usable() {
+        let (_temp_dir, store) = temp_store().await;
+        store
+            .append_events(&[new_event("evt-1"), new_event("evt-2")])
+            .await
+            .expect("append batch");
+
+        store.reset().await.expect("reset store");
+        assert!(store.read_ordered_events().await.expect("read").is_empty());
+
+        store
+            .append_event(&new_event("evt-1"))
+            .await
+            .expect("append after reset");
+        assert_eq!(store.read_ordered_events().await.expect("read").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn health_check_reports_pool_liveness() {
+        let (_temp_dir, store) = temp_store().await;
+
+        store.health_check().await.expect("healthy store");
+
+        store.pool().close().await;
+        assert!(store.health_check().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn connect_creates_missing_parent_directories() {
+        let temp_dir = tempdir().expect("tempdir");
+        let database_path = temp_dir.path().join("nested/data/events.sqlite");
+
+        EventLogStore::connect(&database_path)
+            .await
+            .expect("connect with missing parent");
+
+        assert!(database_path.exists());
+    }
+
+    #[tokio::test]
+    async fn connect_reports_unusable_parent_directory() {
+        let temp_dir = tempdir().expect("tempdir");
+        let blocker = temp_dir.path().join("not-a-dir");
+        std::fs::write(&blocker, b"").expect("write blocker file");
+
+        let err = EventLogStore::connect(blocker.join("events.sqlite"))
+            .await
+            .err()
+            .expect("parent is a file");
+
+        assert!(
+            matches!(&err, super::StoreError::DatabaseDirCreate { path, .. } if path == &blocker),
+            "{err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn fresh_store_reports_all_migrations_applied() {
+        let temp_dir = tempdir().expect("tempdir");
+        let database_path = temp_dir.path().join("events.sqlite");
+
+        let store = EventLogStore::connect(&database_path)
+            .await
+            .expect("connect store");
+        let status = store.migration_status().await.expect("migration status");
+
+        assert!(status.is_up_to_date());
+        assert_eq!(
+            status.applied_versions,
+            super::MIGRATOR
+                .iter()
+                .map(|migration| migration.version)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn sync_cursor_persists_across_reconnects() {
+        let temp_dir = tempdir().expect("tempdir");
+        let database_path = temp_dir.path().join("events.sqlite");
+
+        let store = EventLogStore::connect(&database_path)
+            .await
+            .expect("connect store");
+        assert_eq!(
+            store.get_sync_cursor("peer-a").await.expect("get cursor"),
+            None
+        );
+        store
+            .set_sync_cursor("peer-a", GlobalSeq(7))
+            .await
+            .expect("set cursor");
+        store
+            .set_sync_cursor("peer-a", GlobalSeq(12))
+            .await
+            .expect("advance cursor");
+        store
+            .set_sync_cursor("peer-b", GlobalSeq(3))
+            .await
+            .expect("set other cursor");
+        store.pool().close().await;
+
+        let reopened = EventLogStore::connect(&database_path)
+            .await
+            .expect("reconnect store");
+        assert_eq!(
+            reopened
+                .get_sync_cursor("peer-a")
+                .await
+                .expect("get cursor"),
+            Some(GlobalSeq(12))
+        );
+        assert_eq!(
+            reopened
+                .get_sync_cursor("peer-b")
+                .await
+                .expect("get cursor"),
+            Some(GlobalSeq(3))
+        );
+    }
+
     #[tokio::test]
     async fn append_movement_event_uses_expected_envelope_shape() {
         let temp_dir = tempdir().expect("tempdir");
@@ -493,6 +1537,7 @@ mod tests {
                     to_system_id: SolarSystemId(30002510),
                     observed_at: ts_secs(1_700_000_000),
                     source: MovementEventSource::Esi,
+                    reason: None,
                 },
                 ts_millis(1_700_000_000_999),
             )
@@ -566,6 +1611,36 @@ mod tests {
         assert_eq!(payload.entries, entries);
     }
 
+    #[tokio::test]
+    async fn append_structure_entered_event_stores_character_stream_payload() {
+        let (_temp_dir, store) = temp_store().await;
+        let event = StructureEnteredEvent {
+            character_id: CharacterId(42),
+            system_id: SolarSystemId(31000005),
+            from_structure_id: Some(StructureId(1_000_000_000_001)),
+            structure_id: StructureId(1_000_000_000_002),
+            observed_at: ts_secs(1_700_000_000),
+            source: MovementEventSource::Esi,
+        };
+
+        store
+            .append_structure_entered_event_at(&event, ts_millis(1_700_000_000_500))
+            .await
+            .expect("append structure entered event");
+
+        let events = store.read_ordered_events().await.expect("read ordered");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].envelope.event_type, STRUCTURE_ENTERED_EVENT_TYPE);
+        assert_eq!(events[0].envelope.stream_key, "character:42");
+        assert_eq!(
+            events[0].envelope.attribution_character_id,
+            Some(CharacterId(42))
+        );
+        let payload: StructureEnteredPayload =
+            serde_json::from_str(&events[0].envelope.payload_json).expect("decode payload");
+        assert_eq!(payload, event.as_payload());
+    }
+
     #[tokio::test]
     async fn movement_and_signature_events_coexist_in_ordered_stream() {
         let temp_dir = tempdir().expect("tempdir");
@@ -583,6 +1658,7 @@ mod tests {
                     to_system_id: SolarSystemId(30000142),
                     observed_at: ts_secs(1_700_000_000),
                     source: MovementEventSource::Esi,
+                    reason: None,
                 },
                 ts_millis(1_700_000_000_500),
             )
@@ -619,10 +1695,146 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn prune_signature_history_keeps_latest_snapshots_for_system_only() {
+        let temp_dir = tempdir().expect("tempdir");
+        let database_path = temp_dir.path().join("events.sqlite");
+
+        let store = EventLogStore::connect(&database_path)
+            .await
+            .expect("connect store");
+
+        for (system, snapshot_id) in [
+            (31000001, "snap-1"),
+            (31000001, "snap-2"),
+            (31000002, "snap-other"),
+            (31000001, "snap-3"),
+        ] {
+            store
+                .append_system_signatures_observed_event(&SystemSignaturesObservedEvent {
+                    system_id: SolarSystemId(system),
+                    snapshot_id: snapshot_id.to_owned(),
+                    entries: Vec::new(),
+                    observed_at: ts_secs(1_700_000_000),
+                    attribution_character_id: None,
+                    source: SignatureEventSource::Manual,
+                })
+                .await
+                .expect("append signature event");
+        }
+
+        let pruned = store
+            .prune_signature_history(SolarSystemId(31000001), 0)
+            .await
+            .expect("prune history");
+        assert_eq!(pruned, 2);
+
+        let remaining: Vec<String> = store
+            .read_ordered_events()
+            .await
+            .expect("read ordered")
+            .into_iter()
+            .map(|record| {
+                serde_json::from_str::<SystemSignaturesObservedPayload>(
+                    &record.envelope.payload_json,
+                )
+                .expect("deserialize payload")
+                .snapshot_id
+            })
+            .collect();
+        assert_eq!(remaining, vec!["snap-other", "snap-3"]);
+    }
+
+    #[tokio::test]
+    async fn compact_signature_history_deletes_only_through_seq() {
+        let (_temp_dir, store) = temp_store().await;
+        let system_id = SolarSystemId(31000001);
+        let mut seqs = Vec::new();
+        for snapshot_id in ["snap-1", "snap-2", "snap-3"] {
+            seqs.push(
+                store
+                    .append_system_signatures_observed_event(&SystemSignaturesObservedEvent {
+                        system_id,
+                        snapshot_id: snapshot_id.to_owned(),
+                        entries: Vec::new(),
+                        observed_at: ts_secs(1_700_000_000),
+                        attribution_character_id: None,
+                        source: SignatureEventSource::Manual,
+                    })
+                    .await
+                    .expect("append signature event"),
+            );
+        }
+        let baseline = SystemSignaturesBaselinePayload {
+            system_id,
+            snapshot_id: "snap-2".to_owned(),
+            entries: Vec::new(),
+            signatures: Vec::new(),
+        };
+
+        let err = store
+            .compact_signature_history_at(
+                &baseline,
+                seqs[1],
+                ts_secs(1_700_000_000),
+                ts_secs(1_700_000_100),
+            )
+            .await
+            .expect_err("snap-3 is newer than the baseline");
+        assert!(
+            matches!(err, StoreError::CompactionConflict { through_seq, .. } if through_seq == seqs[1].0),
+            "{err:?}"
+        );
+        assert_eq!(
+            store
+                .read_ordered_events()
+                .await
+                .expect("read ordered")
+                .len(),
+            3,
+            "a conflicting compaction must not delete anything"
+        );
+
+        let pruned = store
+            .compact_signature_history_at(
+                &SystemSignaturesBaselinePayload {
+                    snapshot_id: "snap-3".to_owned(),
+                    ..baseline
+                },
+                seqs[2],
+                ts_secs(1_700_000_000),
+                ts_secs(1_700_000_100),
+            )
+            .await
+            .expect("compact through latest");
+        assert_eq!(pruned, 3);
+        let events = store.read_ordered_events().await.expect("read ordered");
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            events[0].envelope.event_type,
+            SYSTEM_SIGNATURES_BASELINE_EVENT_TYPE
+        );
+    }
+
     fn ts_secs(value: i64) -> Timestamp {
         Timestamp::from_epoch_secs(value).expect("valid epoch seconds")
     }
 
+    /// A `character_moved` event for character 42 with fixed timestamps.
+    fn new_event(event_id: &str) -> NewEvent {
+        NewEvent {
+            event_id: event_id.to_owned(),
+            event_type: "character_moved".to_owned(),
+            schema_version: 1,
+            stream_key: "character:42".to_owned(),
+            occurred_at: ts_millis(1_700_000_000_000),
+            recorded_at: ts_millis(1_700_000_000_000),
+            attribution_character_id: Some(CharacterId(42)),
+            source: EventSource::Esi,
+            payload_json: "{\"to_system_id\":30000142}".to_owned(),
+        }
+    }
+
     fn ts_millis(value: i64) -> Timestamp {
         Timestamp::from_epoch_millis(value).expect("valid epoch milliseconds")
     }
@@ -1,5 +1,12 @@
+mod dto;
 mod error;
 mod event_log;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
 
+pub use dto::{EVENT_DTO_FORMAT_VERSION, EventEnvelopeDto, EventRecordDto};
 pub use error::StoreError;
-pub use event_log::{EventEnvelope, EventLogStore, EventRecord, EventSource, GlobalSeq, NewEvent};
+pub use event_log::{
+    EventEnvelope, EventLogStore, EventRecord, EventSource, GlobalSeq, MigrationStatus, NewEvent,
+    SchemaVersionPolicy, StoreOptions,
+};
@@ -0,0 +1,40 @@
+use std::path::PathBuf;
+
+use tempfile::TempDir;
+
+use crate::EventLogStore;
+
+/// Connects a store backed by a fresh temporary directory. Keep the returned
+/// [`TempDir`] alive for as long as the store is in use.
+pub async fn temp_store() -> (TempDir, EventLogStore) {
+    let temp_dir = tempfile::tempdir().expect("tempdir");
+    let store = EventLogStore::connect(temp_store_path(&temp_dir))
+        .await
+        .expect("connect temp store");
+    (temp_dir, store)
+}
+
+/// The database file [`temp_store`] created in `temp_dir`, for tests that
+/// reopen the store through another handle.
+pub fn temp_store_path(temp_dir: &TempDir) -> PathBuf {
+    temp_dir.path().join("events.sqlite")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::temp_store;
+
+    #[tokio::test]
+    async fn temp_store_is_migrated_and_empty() {
+        let (_temp_dir, store) = temp_store().await;
+
+        assert!(
+            store
+                .migration_status()
+                .await
+                .expect("migration status")
+                .is_up_to_date()
+        );
+        assert!(store.read_ordered_events().await.expect("read").is_empty());
+    }
+}
@@ -2,7 +2,7 @@ use clap::{Args, Subcommand};
 use jaytripper_esi::EnsureSessionResult;
 
 use crate::cli::debug::common::{
-    build_auth_service, load_esi_config, print_session_details, required_character_id,
+    CharacterArgs, build_auth_service, load_esi_config, merge_scopes, print_session_details,
     selected_character_id, wait_for_callback,
 };
 
@@ -22,6 +22,9 @@ enum AuthSubcommand {
 
     /// Remove stored keyring session.
     Logout(LogoutCommand),
+
+    /// Ask ESI which character and scopes the stored token grants.
+    Whoami(WhoamiCommand),
 }
 
 impl AuthCommand {
@@ -30,6 +33,7 @@ impl AuthCommand {
             AuthSubcommand::Login(cmd) => cmd.run().await,
             AuthSubcommand::Status(cmd) => cmd.run().await,
             AuthSubcommand::Logout(cmd) => cmd.run().await,
+            AuthSubcommand::Whoami(cmd) => cmd.run().await,
         }
     }
 }
@@ -38,11 +42,16 @@ impl AuthCommand {
 struct LoginCommand {
     #[arg(long)]
     character_id: Option<u64>,
+
+    /// Extra scopes to request for this login, in addition to EVE_SCOPES.
+    #[arg(long, value_delimiter = ',')]
+    scopes: Vec<String>,
 }
 
 impl LoginCommand {
     async fn run(&self) -> anyhow::Result<()> {
-        let config = load_esi_config("jaytripper-tui-auth/0.1")?;
+        let mut config = load_esi_config("jaytripper-tui-auth/0.1")?;
+        config.scopes = merge_scopes(&config.scopes, &self.scopes)?;
         let mut auth = build_auth_service(&config)?;
 
         if let Some(character_id) = selected_character_id(self.character_id) {
@@ -90,13 +99,13 @@ impl LoginCommand {
 
 #[derive(Debug, Args)]
 struct StatusCommand {
-    #[arg(long)]
-    character_id: Option<u64>,
+    #[command(flatten)]
+    character: CharacterArgs,
 }
 
 impl StatusCommand {
     async fn run(&self) -> anyhow::Result<()> {
-        let character_id = required_character_id(self.character_id)?;
+        let character_id = self.character.required()?;
         let config = load_esi_config("jaytripper-tui-auth/0.1")?;
         let auth = build_auth_service(&config)?;
 
@@ -111,13 +120,13 @@ impl StatusCommand {
 
 #[derive(Debug, Args)]
 struct LogoutCommand {
-    #[arg(long)]
-    character_id: Option<u64>,
+    #[command(flatten)]
+    character: CharacterArgs,
 }
 
 impl LogoutCommand {
     async fn run(&self) -> anyhow::Result<()> {
-        let character_id = required_character_id(self.character_id)?;
+        let character_id = self.character.required()?;
         let config = load_esi_config("jaytripper-tui-auth/0.1")?;
         let auth = build_auth_service(&config)?;
 
@@ -126,3 +135,50 @@ impl LogoutCommand {
         Ok(())
     }
 }
+
+#[derive(Debug, Args)]
+struct WhoamiCommand {
+    #[command(flatten)]
+    character: CharacterArgs,
+}
+
+impl WhoamiCommand {
+    async fn run(&self) -> anyhow::Result<()> {
+        let character_id = self.character.required()?;
+        let config = load_esi_config("jaytripper-tui-auth/0.1")?;
+        let mut auth = build_auth_service(&config)?;
+
+        let verified = auth.verify_session(character_id).await?;
+        println!(
+            "ESI reports character {} ({})",
+            verified.character_name, verified.character_id
+        );
+        println!("Granted scopes: {}", verified.scopes.join(" "));
+
+        if verified.character_id != character_id {
+            println!("WARNING: token belongs to a different character than {character_id}.");
+        }
+        if let Some(session) = auth.load_session(character_id)? {
+            let missing: Vec<&str> = session
+                .scopes
+                .iter()
+                .filter(|scope| !verified.scopes.contains(scope))
+                .map(String::as_str)
+                .collect();
+            let extra: Vec<&str> = verified
+                .scopes
+                .iter()
+                .filter(|scope| !session.has_scope(scope))
+                .map(String::as_str)
+                .collect();
+            if !missing.is_empty() {
+                println!("Stored scopes not granted by ESI: {}", missing.join(" "));
+            }
+            if !extra.is_empty() {
+                println!("Granted scopes not in stored session: {}", extra.join(" "));
+            }
+        }
+
+        Ok(())
+    }
+}
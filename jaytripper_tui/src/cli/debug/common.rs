@@ -5,8 +5,12 @@ use std::{
 };
 
 use anyhow::Context;
+use clap::Args;
 use jaytripper_core::{Timestamp, ids::CharacterId};
-use jaytripper_esi::{AuthService, AuthSession, EsiConfig, KeyringTokenStore, RfesiSsoClient};
+use jaytripper_esi::{
+    AuthService, AuthSession, DEFAULT_REQUEST_TIMEOUT, EsiConfig, KeyringTokenStore,
+    RfesiSsoClient, TokenStore,
+};
 use url::Url;
 
 const DEFAULT_SCOPES: &str = "publicData,esi-location.read_location.v1";
@@ -14,20 +18,82 @@ const KEYRING_SERVICE: &str = "jaytripper";
 const KEYRING_ACCOUNT_PREFIX: &str = "esi-session";
 
 pub(crate) fn load_esi_config(default_user_agent: &'static str) -> anyhow::Result<EsiConfig> {
-    Ok(EsiConfig {
-        client_id: required_env("EVE_CLIENT_ID")?,
-        callback_url: required_env("EVE_CALLBACK_URL")?,
-        scopes: scopes_from_env(),
-        user_agent: env::var("JAYTRIPPER_USER_AGENT").unwrap_or_else(|_| default_user_agent.into()),
-    })
+    let config = resolve_esi_config(default_user_agent);
+    for (name, value) in [
+        ("EVE_CLIENT_ID", &config.client_id),
+        ("EVE_CALLBACK_URL", &config.callback_url),
+    ] {
+        if value.is_empty() {
+            anyhow::bail!("missing required env var `{name}`");
+        }
+    }
+    Ok(config)
+}
+
+/// Reads the ESI configuration from the environment without failing; unset
+/// required variables come back empty for [`EsiConfig::validate`] to report.
+pub(crate) fn resolve_esi_config(default_user_agent: &'static str) -> EsiConfig {
+    esi_config_from(|name| env::var(name).ok(), default_user_agent)
+}
+
+pub(crate) fn esi_config_from(
+    lookup: impl Fn(&str) -> Option<String>,
+    default_user_agent: &'static str,
+) -> EsiConfig {
+    let raw_scopes = lookup("EVE_SCOPES").unwrap_or_else(|| DEFAULT_SCOPES.to_owned());
+    EsiConfig {
+        client_id: lookup("EVE_CLIENT_ID").unwrap_or_default(),
+        callback_url: lookup("EVE_CALLBACK_URL").unwrap_or_default(),
+        scopes: raw_scopes
+            .split(',')
+            .map(str::trim)
+            .filter(|scope| !scope.is_empty())
+            .map(ToOwned::to_owned)
+            .collect(),
+        user_agent: lookup("JAYTRIPPER_USER_AGENT").unwrap_or_else(|| default_user_agent.into()),
+        request_timeout: DEFAULT_REQUEST_TIMEOUT,
+        base_url: lookup("JAYTRIPPER_ESI_BASE_URL"),
+    }
+}
+
+/// Unions `extra` into the configured scopes, keeping config order first and
+/// dropping duplicates. Every extra scope must look like an ESI scope name.
+pub(crate) fn merge_scopes(configured: &[String], extra: &[String]) -> anyhow::Result<Vec<String>> {
+    let mut merged = configured.to_vec();
+    for scope in extra {
+        let scope = scope.trim();
+        validate_scope(scope)?;
+        if !merged.iter().any(|existing| existing == scope) {
+            merged.push(scope.to_owned());
+        }
+    }
+    Ok(merged)
+}
+
+pub(crate) fn validate_scope(scope: &str) -> anyhow::Result<()> {
+    let valid = !scope.is_empty()
+        && scope
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '.' | '_' | '-'));
+    if !valid {
+        anyhow::bail!("invalid ESI scope `{scope}`");
+    }
+    Ok(())
 }
 
 pub(crate) fn build_auth_service(
     config: &EsiConfig,
 ) -> anyhow::Result<AuthService<RfesiSsoClient, KeyringTokenStore>> {
     let client = RfesiSsoClient::new(config).context("failed to create ESI SSO client")?;
-    let store = KeyringTokenStore::new(KEYRING_SERVICE, KEYRING_ACCOUNT_PREFIX);
-    Ok(AuthService::new(client, store, config.scopes.clone()))
+    Ok(AuthService::new(
+        client,
+        token_store(),
+        config.scopes.clone(),
+    ))
+}
+
+pub(crate) fn token_store() -> KeyringTokenStore {
+    KeyringTokenStore::new(KEYRING_SERVICE, KEYRING_ACCOUNT_PREFIX)
 }
 
 pub(crate) fn selected_character_id(explicit: Option<u64>) -> Option<CharacterId> {
@@ -44,6 +110,101 @@ pub(crate) fn required_character_id(explicit: Option<u64>) -> anyhow::Result<Cha
         .context("character id is required; provide --character-id or set EVE_CHARACTER_ID")
 }
 
+/// Selects a character by numeric id or by the name on a stored session.
+#[derive(Debug, Args)]
+pub(crate) struct CharacterArgs {
+    #[arg(long)]
+    character_id: Option<u64>,
+
+    /// Resolve the character from the name on a stored session.
+    #[arg(long, conflicts_with = "character_id")]
+    character_name: Option<String>,
+}
+
+impl CharacterArgs {
+    pub(crate) fn required(&self) -> anyhow::Result<CharacterId> {
+        match &self.character_name {
+            Some(name) => resolve_character_name(
+                &known_sessions(&token_store(), selected_character_id(None))?,
+                name,
+            ),
+            None => required_character_id(self.character_id),
+        }
+    }
+}
+
+/// Lists stored sessions, plus the session for `fallback` (`EVE_CHARACTER_ID`)
+/// if the store's index does not know it yet, as with sessions saved before
+/// the index existed. Loading it also adds it to the index.
+pub(crate) fn known_sessions(
+    store: &impl TokenStore,
+    fallback: Option<CharacterId>,
+) -> anyhow::Result<Vec<AuthSession>> {
+    let mut sessions = store.list_sessions()?;
+    if let Some(character_id) = fallback
+        && !sessions
+            .iter()
+            .any(|session| session.character_id == character_id)
+        && let Some(session) = store.load_session(character_id)?
+    {
+        sessions.push(session);
+    }
+    Ok(sessions)
+}
+
+/// Finds the single stored session whose character name matches `name`
+/// (case-insensitively).
+pub(crate) fn resolve_character_name(
+    sessions: &[AuthSession],
+    name: &str,
+) -> anyhow::Result<CharacterId> {
+    let matches: Vec<&AuthSession> = sessions
+        .iter()
+        .filter(|session| {
+            session
+                .character_name
+                .as_deref()
+                .is_some_and(|candidate| candidate.eq_ignore_ascii_case(name.trim()))
+        })
+        .collect();
+
+    match matches.as_slice() {
+        [session] => Ok(session.character_id),
+        [] => {
+            let mut available: Vec<String> = sessions
+                .iter()
+                .map(|session| {
+                    format!(
+                        "{} ({})",
+                        session.character_name.as_deref().unwrap_or("<unknown>"),
+                        session.character_id
+                    )
+                })
+                .collect();
+            available.sort();
+            if available.is_empty() {
+                anyhow::bail!(
+                    "no stored session matches character name `{name}`; no sessions are stored"
+                );
+            }
+            anyhow::bail!(
+                "no stored session matches character name `{name}`; available: {}",
+                available.join(", ")
+            )
+        }
+        many => {
+            let ids: Vec<String> = many
+                .iter()
+                .map(|session| session.character_id.to_string())
+                .collect();
+            anyhow::bail!(
+                "character name `{name}` matches several stored sessions ({}); use --character-id",
+                ids.join(", ")
+            )
+        }
+    }
+}
+
 pub(crate) fn print_session_details(session: &AuthSession) {
     let now = Timestamp::now();
     let valid_for = session
@@ -153,19 +314,6 @@ pub(crate) fn wait_for_callback(callback_url: &str) -> anyhow::Result<(String, S
     }
 }
 
-fn required_env(name: &str) -> anyhow::Result<String> {
-    env::var(name).map_err(|_| anyhow::anyhow!("missing required env var `{name}`"))
-}
-
-fn scopes_from_env() -> Vec<String> {
-    let raw = env::var("EVE_SCOPES").unwrap_or_else(|_| DEFAULT_SCOPES.to_owned());
-    raw.split(',')
-        .map(str::trim)
-        .filter(|scope| !scope.is_empty())
-        .map(ToOwned::to_owned)
-        .collect()
-}
-
 fn read_http_request(stream: &mut TcpStream) -> Result<String, io::Error> {
     let mut buffer = [0_u8; 8192];
     let size = stream.read(&mut buffer)?;
@@ -188,3 +336,149 @@ fn write_http_response(
     stream.flush()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use jaytripper_core::{Timestamp, ids::CharacterId};
+    use jaytripper_esi::{AuthSession, EsiError, EsiResult, TokenStore};
+
+    use super::{known_sessions, merge_scopes, resolve_character_name};
+
+    /// Lists only `indexed`, like a keyring whose `unindexed` session was
+    /// saved before the session index existed.
+    struct PartlyIndexedStore {
+        indexed: Vec<AuthSession>,
+        unindexed: AuthSession,
+    }
+
+    impl TokenStore for PartlyIndexedStore {
+        fn load_session(&self, character_id: CharacterId) -> EsiResult<Option<AuthSession>> {
+            Ok(self
+                .indexed
+                .iter()
+                .chain([&self.unindexed])
+                .find(|session| session.character_id == character_id)
+                .cloned())
+        }
+
+        fn save_session(&self, _session: &AuthSession) -> EsiResult<()> {
+            Err(EsiError::message("read-only test store"))
+        }
+
+        fn clear_session(&self, _character_id: CharacterId) -> EsiResult<()> {
+            Err(EsiError::message("read-only test store"))
+        }
+
+        fn list_sessions(&self) -> EsiResult<Vec<AuthSession>> {
+            Ok(self.indexed.clone())
+        }
+    }
+
+    fn session(character_id: u64, name: &str) -> AuthSession {
+        AuthSession {
+            character_id: CharacterId(character_id),
+            character_name: Some(name.to_owned()),
+            scopes: Vec::new(),
+            access_token: "access".to_owned(),
+            access_expires_at: Timestamp::from_epoch_secs(1_700_000_000).expect("timestamp"),
+            refresh_token: "refresh".to_owned(),
+            updated_at: Timestamp::from_epoch_secs(1_700_000_000).expect("timestamp"),
+        }
+    }
+
+    #[test]
+    fn resolves_character_name_across_stored_sessions() {
+        let sessions = [
+            session(90000001, "Main Pilot"),
+            session(90000002, "Scout Alt"),
+        ];
+
+        assert_eq!(
+            resolve_character_name(&sessions, "scout alt").expect("resolve"),
+            CharacterId(90000002)
+        );
+        assert_eq!(
+            resolve_character_name(&sessions, "Main Pilot").expect("resolve"),
+            CharacterId(90000001)
+        );
+    }
+
+    #[test]
+    fn unknown_character_name_lists_available_names() {
+        let sessions = [
+            session(90000001, "Main Pilot"),
+            session(90000002, "Scout Alt"),
+        ];
+
+        let err = resolve_character_name(&sessions, "Hauler").expect_err("unknown name");
+
+        let message = err.to_string();
+        assert!(message.contains("Main Pilot (90000001)"), "{message}");
+        assert!(message.contains("Scout Alt (90000002)"), "{message}");
+    }
+
+    #[test]
+    fn character_name_finds_unindexed_session_through_fallback_id() {
+        let store = PartlyIndexedStore {
+            indexed: vec![session(90000001, "Main Pilot")],
+            unindexed: session(90000002, "Scout Alt"),
+        };
+
+        let indexed_only = known_sessions(&store, None).expect("list sessions");
+        resolve_character_name(&indexed_only, "Scout Alt").expect_err("not indexed");
+
+        let sessions = known_sessions(&store, Some(CharacterId(90000002))).expect("list sessions");
+        assert_eq!(
+            resolve_character_name(&sessions, "Scout Alt").expect("resolve"),
+            CharacterId(90000002)
+        );
+        assert_eq!(
+            known_sessions(&store, Some(CharacterId(90000001)))
+                .expect("list sessions")
+                .len(),
+            1,
+            "an indexed fallback is not listed twice"
+        );
+    }
+
+    #[test]
+    fn ambiguous_character_name_is_rejected() {
+        let sessions = [session(90000001, "Pilot"), session(90000002, "pilot")];
+
+        let err = resolve_character_name(&sessions, "Pilot").expect_err("ambiguous name");
+
+        assert!(err.to_string().contains("90000001, 90000002"));
+    }
+
+    #[test]
+    fn merge_scopes_unions_without_duplicates() {
+        let configured = vec![
+            "publicData".to_owned(),
+            "esi-location.read_location.v1".to_owned(),
+        ];
+        let extra = vec![
+            "esi-location.read_location.v1".to_owned(),
+            "esi-location.read_ship_type.v1".to_owned(),
+            "esi-location.read_ship_type.v1".to_owned(),
+        ];
+
+        let merged = merge_scopes(&configured, &extra).expect("valid scopes");
+
+        assert_eq!(
+            merged,
+            vec![
+                "publicData",
+                "esi-location.read_location.v1",
+                "esi-location.read_ship_type.v1",
+            ]
+        );
+    }
+
+    #[test]
+    fn merge_scopes_rejects_malformed_scope() {
+        let err = merge_scopes(&[], &["esi-location read".to_owned()])
+            .expect_err("scope with whitespace should be rejected");
+
+        assert!(err.to_string().contains("esi-location read"));
+    }
+}
@@ -0,0 +1,169 @@
+use std::fmt::Write;
+
+use clap::{Args, Subcommand};
+use jaytripper_esi::EsiConfig;
+
+use crate::cli::debug::common::{resolve_esi_config, validate_scope};
+
+#[derive(Debug, Args)]
+pub(crate) struct ConfigCommand {
+    #[command(subcommand)]
+    subcmd: ConfigSubcommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum ConfigSubcommand {
+    /// Resolve the ESI configuration from the environment and validate it.
+    Check(CheckCommand),
+}
+
+impl ConfigCommand {
+    pub(crate) async fn run(&self) -> anyhow::Result<()> {
+        match &self.subcmd {
+            ConfigSubcommand::Check(cmd) => cmd.run(),
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+struct CheckCommand {}
+
+impl CheckCommand {
+    fn run(&self) -> anyhow::Result<()> {
+        run_check(&resolve_esi_config("jaytripper-tui-auth/0.1"))
+    }
+}
+
+/// Prints the [`check_report`] for `config` and fails if any check did.
+fn run_check(config: &EsiConfig) -> anyhow::Result<()> {
+    let (report, valid) = check_report(config);
+    print!("{report}");
+    if !valid {
+        anyhow::bail!("ESI configuration is invalid");
+    }
+    Ok(())
+}
+
+/// Lists every resolved field (none of them are secrets) followed by the scope
+/// grammar and [`EsiConfig::validate`] results. Returns whether both passed.
+fn check_report(config: &EsiConfig) -> (String, bool) {
+    let mut report = String::new();
+    let required = |value: &str| {
+        if value.is_empty() {
+            "<unset>".to_owned()
+        } else {
+            value.to_owned()
+        }
+    };
+    let fields = [
+        ("client_id", required(&config.client_id)),
+        ("callback_url", required(&config.callback_url)),
+        ("scopes", config.scopes.join(",")),
+        ("user_agent", config.user_agent.clone()),
+        ("request_timeout", format!("{:?}", config.request_timeout)),
+        (
+            "base_url",
+            config
+                .base_url
+                .clone()
+                .unwrap_or_else(|| "<default>".to_owned()),
+        ),
+    ];
+    for (field, value) in fields {
+        writeln!(report, "{field}: {value}").expect("write to string");
+    }
+
+    let scope_errors: Vec<String> = config
+        .scopes
+        .iter()
+        .filter_map(|scope| validate_scope(scope).err())
+        .map(|err| err.to_string())
+        .collect();
+    let scopes_valid = scope_errors.is_empty();
+    if scopes_valid {
+        writeln!(report, "scope grammar: pass").expect("write to string");
+    } else {
+        writeln!(report, "scope grammar: fail ({})", scope_errors.join("; "))
+            .expect("write to string");
+    }
+
+    let validated = config.validate();
+    match &validated {
+        Ok(()) => writeln!(report, "validate: pass"),
+        Err(err) => writeln!(report, "validate: fail ({err})"),
+    }
+    .expect("write to string");
+
+    (report, scopes_valid && validated.is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use jaytripper_esi::{DEFAULT_REQUEST_TIMEOUT, EsiConfig};
+
+    use super::{check_report, run_check};
+    use crate::cli::debug::common::esi_config_from;
+
+    fn config() -> EsiConfig {
+        EsiConfig {
+            client_id: "client-id".to_owned(),
+            callback_url: "http://localhost:8080/callback".to_owned(),
+            scopes: vec![
+                "publicData".to_owned(),
+                "esi-location.read_location.v1".to_owned(),
+            ],
+            user_agent: "jaytripper-test/0.1".to_owned(),
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            base_url: None,
+        }
+    }
+
+    #[test]
+    fn valid_config_passes_every_check() {
+        let (report, valid) = check_report(&config());
+
+        assert!(valid, "{report}");
+        assert!(report.contains("client_id: client-id\n"), "{report}");
+        assert!(report.contains("base_url: <default>\n"), "{report}");
+        assert!(
+            report.ends_with("scope grammar: pass\nvalidate: pass\n"),
+            "{report}"
+        );
+    }
+
+    #[test]
+    fn invalid_config_reports_each_failure() {
+        let (report, valid) = check_report(&EsiConfig {
+            client_id: " ".to_owned(),
+            scopes: vec!["esi-location read".to_owned()],
+            ..config()
+        });
+
+        assert!(!valid, "{report}");
+        assert!(
+            report.contains("scope grammar: fail (invalid ESI scope `esi-location read`)"),
+            "{report}"
+        );
+        assert!(
+            report.contains("validate: fail (invalid config: EVE_CLIENT_ID must be set)"),
+            "{report}"
+        );
+    }
+
+    #[test]
+    fn missing_callback_url_fails_the_check() {
+        let unset_callback = esi_config_from(
+            |name| (name == "EVE_CLIENT_ID").then(|| "client-id".to_owned()),
+            "jaytripper-test/0.1",
+        );
+
+        let (report, _) = check_report(&unset_callback);
+        assert!(report.contains("callback_url: <unset>\n"), "{report}");
+        assert!(
+            report.contains("validate: fail (invalid config: EVE_CALLBACK_URL must be set)"),
+            "{report}"
+        );
+        run_check(&unset_callback).expect_err("missing callback url should fail");
+        run_check(&config()).expect("valid config should pass");
+    }
+}
@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 
 use clap::Args;
-use jaytripper_store::{EventLogStore, GlobalSeq};
+use jaytripper_store::{EventLogStore, EventRecord, GlobalSeq};
 
 #[derive(Debug, Args)]
 pub(crate) struct EventsCommand {
@@ -16,40 +16,31 @@ pub(crate) struct EventsCommand {
 
     #[arg(long)]
     limit: Option<usize>,
+
+    /// List the newest events first. With `--limit` and no other filter,
+    /// only the requested events are read from the log.
+    #[arg(long)]
+    newest_first: bool,
 }
 
 impl EventsCommand {
     pub(crate) async fn run(&self) -> anyhow::Result<()> {
         let store = EventLogStore::connect(&self.db).await?;
+        let (records, total) = self.load_records(&store).await?;
 
-        let mut records = if let Some(stream_key) = &self.stream {
-            store.read_events_by_stream(stream_key).await?
-        } else if let Some(since_seq) = self.since {
-            store.read_events_since(GlobalSeq(since_seq)).await?
-        } else {
-            store.read_ordered_events().await?
-        };
-
-        if self.stream.is_some()
-            && let Some(since_seq) = self.since
-        {
-            records.retain(|record| record.global_seq > GlobalSeq(since_seq));
-        }
-
-        let total = records.len();
-        if let Some(limit) = self.limit
-            && records.len() > limit
-        {
-            let keep_from = records.len() - limit;
-            records = records.split_off(keep_from);
+        match total {
+            Some(total) => println!(
+                "Showing {} event(s) from {} (matched {total} before limit)",
+                records.len(),
+                self.db.display()
+            ),
+            None => println!(
+                "Showing {} newest event(s) from {}",
+                records.len(),
+                self.db.display()
+            ),
         }
 
-        println!(
-            "Showing {} event(s) from {} (matched {total} before limit)",
-            records.len(),
-            self.db.display()
-        );
-
         for record in records {
             let attribution = record
                 .envelope
@@ -71,4 +62,88 @@ impl EventsCommand {
 
         Ok(())
     }
+
+    /// Returns the selected records and, when the whole match was read, how
+    /// many matched before the limit.
+    async fn load_records(
+        &self,
+        store: &EventLogStore,
+    ) -> anyhow::Result<(Vec<EventRecord>, Option<usize>)> {
+        if self.newest_first
+            && self.stream.is_none()
+            && self.since.is_none()
+            && let Some(limit) = self.limit
+        {
+            return Ok((store.read_recent_events(limit).await?, None));
+        }
+
+        let mut records = match (&self.stream, self.since) {
+            (Some(stream_key), Some(since_seq)) => {
+                store
+                    .read_events_by_stream_since(stream_key, GlobalSeq(since_seq))
+                    .await?
+            }
+            (Some(stream_key), None) => store.read_events_by_stream(stream_key).await?,
+            (None, Some(since_seq)) => store.read_events_since(GlobalSeq(since_seq)).await?,
+            (None, None) => store.read_ordered_events().await?,
+        };
+
+        let total = records.len();
+        if let Some(limit) = self.limit
+            && records.len() > limit
+        {
+            let keep_from = records.len() - limit;
+            records = records.split_off(keep_from);
+        }
+        if self.newest_first {
+            records.reverse();
+        }
+
+        Ok((records, Some(total)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use jaytripper_core::{
+        MovementEvent, MovementEventSource, Timestamp,
+        ids::{CharacterId, SolarSystemId},
+    };
+    use jaytripper_store::{GlobalSeq, test_util::temp_store};
+
+    use super::EventsCommand;
+
+    #[tokio::test]
+    async fn limit_with_newest_first_returns_newest_events() {
+        let (_temp_dir, store) = temp_store().await;
+        for (offset, system) in [30000142, 30000144, 30000145].into_iter().enumerate() {
+            store
+                .append_movement_event(&MovementEvent {
+                    character_id: CharacterId(42),
+                    from_system_id: None,
+                    to_system_id: SolarSystemId(system),
+                    observed_at: Timestamp::from_epoch_secs(1_700_000_000 + offset as i64)
+                        .expect("timestamp"),
+                    source: MovementEventSource::Esi,
+                    reason: None,
+                })
+                .await
+                .expect("append movement");
+        }
+        let command = EventsCommand {
+            db: PathBuf::new(),
+            since: None,
+            stream: None,
+            limit: Some(2),
+            newest_first: true,
+        };
+
+        let (records, total) = command.load_records(&store).await.expect("load records");
+
+        let seqs: Vec<GlobalSeq> = records.iter().map(|record| record.global_seq).collect();
+        assert_eq!(seqs, vec![GlobalSeq(3), GlobalSeq(2)]);
+        assert_eq!(total, None);
+    }
 }
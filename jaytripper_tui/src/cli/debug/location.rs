@@ -1,7 +1,11 @@
+use std::{fmt::Write as _, path::PathBuf};
+
 use clap::{Args, Subcommand};
+use jaytripper_app::{AppRuntime, CharacterMovement, LocationNameResolver};
+use jaytripper_core::{MovementReason, ids::CharacterId};
 use jaytripper_esi::EsiClient;
 
-use crate::cli::debug::common::{build_auth_service, load_esi_config, required_character_id};
+use crate::cli::debug::common::{CharacterArgs, build_auth_service, load_esi_config};
 
 #[derive(Debug, Args)]
 pub(crate) struct LocationCommand {
@@ -13,25 +17,28 @@ pub(crate) struct LocationCommand {
 enum LocationSubcommand {
     /// Fetch one location sample.
     Once(OnceCommand),
+    /// Print the stored jump log for a character (no ESI call).
+    History(HistoryCommand),
 }
 
 impl LocationCommand {
     pub(crate) async fn run(&self) -> anyhow::Result<()> {
         match &self.subcmd {
             LocationSubcommand::Once(cmd) => cmd.run().await,
+            LocationSubcommand::History(cmd) => cmd.run().await,
         }
     }
 }
 
 #[derive(Debug, Args)]
 struct OnceCommand {
-    #[arg(long)]
-    character_id: Option<u64>,
+    #[command(flatten)]
+    character: CharacterArgs,
 }
 
 impl OnceCommand {
     async fn run(&self) -> anyhow::Result<()> {
-        let character_id = required_character_id(self.character_id)?;
+        let character_id = self.character.required()?;
         let config = load_esi_config("jaytripper-tui-location/0.1")?;
         let auth = build_auth_service(&config)?;
         let client = auth.connect_character(character_id).await?;
@@ -53,7 +60,158 @@ impl OnceCommand {
                 .map(|value| value.to_string())
                 .unwrap_or_else(|| "<none>".to_owned())
         );
+        println!(
+            "Docked at: {}",
+            LocationNameResolver::new()
+                .docked_label(&client, &location)
+                .await
+                .unwrap_or_else(|| "<undocked>".to_owned())
+        );
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+struct HistoryCommand {
+    #[command(flatten)]
+    character: CharacterArgs,
+
+    #[arg(long, default_value = "jaytripper.sqlite")]
+    db: PathBuf,
 
+    /// Only show the most recent N movements.
+    #[arg(long)]
+    limit: Option<usize>,
+
+    /// Print movements as JSON instead of text.
+    #[arg(long)]
+    json: bool,
+}
+
+impl HistoryCommand {
+    async fn run(&self) -> anyhow::Result<()> {
+        let character_id = self.character.required()?;
+        let app = AppRuntime::connect(&self.db).await?;
+        print!(
+            "{}",
+            history_output(&app, character_id, self.limit, self.json).await?
+        );
         Ok(())
     }
 }
+
+async fn history_output(
+    app: &AppRuntime,
+    character_id: CharacterId,
+    limit: Option<usize>,
+    json: bool,
+) -> anyhow::Result<String> {
+    let mut movements = app.character_movement_history(character_id).await?;
+    if let Some(limit) = limit
+        && movements.len() > limit
+    {
+        let keep_from = movements.len() - limit;
+        movements = movements.split_off(keep_from);
+    }
+
+    if json {
+        return Ok(format!("{}\n", serde_json::to_string_pretty(&movements)?));
+    }
+    Ok(render_history(character_id, &movements))
+}
+
+fn render_history(character_id: CharacterId, movements: &[CharacterMovement]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "character {character_id}: {} movement(s)",
+        movements.len()
+    );
+
+    for movement in movements {
+        let _ = writeln!(
+            out,
+            "occurred={} from={} to={} reason={}",
+            movement.occurred_at.as_epoch_secs(),
+            movement
+                .from_system_id
+                .map(|value| value.to_string())
+                .unwrap_or_else(|| "<none>".to_owned()),
+            movement.to_system_id,
+            movement.reason.map(reason_label).unwrap_or("<unknown>"),
+        );
+    }
+
+    out
+}
+
+fn reason_label(reason: MovementReason) -> &'static str {
+    match reason {
+        MovementReason::Login => "login",
+        MovementReason::Gate => "gate",
+        MovementReason::Wormhole => "wormhole",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jaytripper_app::AppRuntime;
+    use jaytripper_core::{
+        MovementEvent, MovementEventSource, MovementReason, Timestamp,
+        ids::{CharacterId, SolarSystemId},
+    };
+    use jaytripper_store::test_util::temp_store;
+
+    use super::history_output;
+
+    #[tokio::test]
+    async fn history_lists_stored_movements_with_limit_and_json() {
+        let (_temp_dir, store) = temp_store().await;
+        for (from, to, observed_at, reason) in [
+            (None, 30000142, 1_700_000_000, Some(MovementReason::Login)),
+            (
+                Some(30000142),
+                30000144,
+                1_700_000_060,
+                Some(MovementReason::Gate),
+            ),
+            (Some(30000144), 31000005, 1_700_000_120, None),
+        ] {
+            store
+                .append_movement_event(&MovementEvent {
+                    character_id: CharacterId(42),
+                    from_system_id: from.map(SolarSystemId),
+                    to_system_id: SolarSystemId(to),
+                    observed_at: ts(observed_at),
+                    source: MovementEventSource::Esi,
+                    reason,
+                })
+                .await
+                .expect("append movement");
+        }
+        let app = AppRuntime::from_store(store).await.expect("app");
+
+        let text = history_output(&app, CharacterId(42), Some(2), false)
+            .await
+            .expect("text history");
+        assert_eq!(
+            text,
+            "character 42: 2 movement(s)\n\
+             occurred=1700000060 from=30000142 to=30000144 reason=gate\n\
+             occurred=1700000120 from=30000144 to=31000005 reason=<unknown>\n"
+        );
+
+        let json = history_output(&app, CharacterId(42), None, true)
+            .await
+            .expect("json history");
+        let rows: Vec<serde_json::Value> = serde_json::from_str(&json).expect("parse json");
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0]["reason"], "login");
+        assert_eq!(rows[2]["to_system_id"], 31000005);
+    }
+
+    fn ts(epoch_secs: i64) -> Timestamp {
+        Timestamp::from_epoch_secs(epoch_secs).expect("valid epoch seconds")
+    }
+}
@@ -0,0 +1,41 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use jaytripper_store::EventLogStore;
+
+#[derive(Debug, Args)]
+pub(crate) struct MigrateCommand {
+    #[arg(long, default_value = "jaytripper.sqlite")]
+    db: PathBuf,
+}
+
+impl MigrateCommand {
+    pub(crate) async fn run(&self) -> anyhow::Result<()> {
+        let store = EventLogStore::connect(&self.db).await?;
+        let status = store.migration_status().await?;
+
+        println!("DB: {}", self.db.display());
+        println!(
+            "applied migrations: {}",
+            format_versions(&status.applied_versions)
+        );
+        println!(
+            "pending migrations: {}",
+            format_versions(&status.pending_versions)
+        );
+
+        Ok(())
+    }
+}
+
+fn format_versions(versions: &[i64]) -> String {
+    if versions.is_empty() {
+        return "<none>".to_owned();
+    }
+
+    versions
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
@@ -1,15 +1,24 @@
 mod auth;
 mod common;
+mod config;
 mod events;
 mod location;
+mod migrate;
+#[cfg(feature = "dev")]
+mod reset;
+mod signatures;
 mod state;
+mod tokens;
 mod track;
 
 use clap::{Args, Subcommand};
 
+#[cfg(feature = "dev")]
+use self::reset::ResetCommand;
 use self::{
-    auth::AuthCommand, events::EventsCommand, location::LocationCommand, state::StateCommand,
-    track::TrackCommand,
+    auth::AuthCommand, config::ConfigCommand, events::EventsCommand, location::LocationCommand,
+    migrate::MigrateCommand, signatures::SignaturesCommand, state::StateCommand,
+    tokens::TokensCommand, track::TrackCommand,
 };
 
 #[derive(Debug, Args)]
@@ -34,6 +43,22 @@ enum DebugSubcommand {
 
     /// Inspect derived in-memory state from replay.
     State(StateCommand),
+
+    /// Apply pending store migrations and report migration status.
+    Migrate(MigrateCommand),
+
+    /// Export or import stored sessions, including tokens.
+    Tokens(TokensCommand),
+
+    /// Delete every stored event, keeping the migrated schema.
+    #[cfg(feature = "dev")]
+    Reset(ResetCommand),
+
+    /// Manage recorded signature state.
+    Signatures(SignaturesCommand),
+
+    /// Inspect the resolved ESI configuration.
+    Config(ConfigCommand),
 }
 
 impl DebugCommand {
@@ -44,6 +69,12 @@ impl DebugCommand {
             DebugSubcommand::Track(cmd) => cmd.run().await,
             DebugSubcommand::Events(cmd) => cmd.run().await,
             DebugSubcommand::State(cmd) => cmd.run().await,
+            DebugSubcommand::Migrate(cmd) => cmd.run().await,
+            DebugSubcommand::Tokens(cmd) => cmd.run().await,
+            #[cfg(feature = "dev")]
+            DebugSubcommand::Reset(cmd) => cmd.run().await,
+            DebugSubcommand::Signatures(cmd) => cmd.run().await,
+            DebugSubcommand::Config(cmd) => cmd.run().await,
         }
     }
 }
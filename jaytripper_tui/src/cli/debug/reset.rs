@@ -0,0 +1,66 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use jaytripper_store::EventLogStore;
+
+#[derive(Debug, Args)]
+pub(crate) struct ResetCommand {
+    #[arg(long, default_value = "jaytripper.sqlite")]
+    db: PathBuf,
+
+    /// Confirm that every stored event should be deleted.
+    #[arg(long)]
+    yes: bool,
+}
+
+impl ResetCommand {
+    pub(crate) async fn run(&self) -> anyhow::Result<()> {
+        require_reset_confirmation(self.yes)?;
+        if !self.db.exists() {
+            anyhow::bail!("no event store at {}", self.db.display());
+        }
+
+        let store = EventLogStore::connect(&self.db).await?;
+        store.reset().await?;
+
+        println!("Deleted all events from {}", self.db.display());
+        Ok(())
+    }
+}
+
+fn require_reset_confirmation(yes: bool) -> anyhow::Result<()> {
+    if !yes {
+        anyhow::bail!("This deletes every stored event. Re-run with --yes to confirm.");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use jaytripper_store::test_util::temp_store;
+
+    use super::{ResetCommand, require_reset_confirmation};
+
+    #[test]
+    fn reset_requires_explicit_confirmation() {
+        let err = require_reset_confirmation(false).expect_err("reset without --yes");
+
+        assert!(err.to_string().contains("--yes"));
+        require_reset_confirmation(true).expect("confirmed reset");
+    }
+
+    #[tokio::test]
+    async fn reset_does_not_create_a_missing_store() {
+        let (temp_dir, _store) = temp_store().await;
+        let db = temp_dir.path().join("missing.sqlite");
+        let cmd = ResetCommand {
+            db: db.clone(),
+            yes: true,
+        };
+
+        let err = cmd.run().await.expect_err("reset of a missing store");
+
+        assert!(err.to_string().contains("no event store"));
+        assert!(!db.exists());
+    }
+}
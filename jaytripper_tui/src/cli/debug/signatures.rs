@@ -0,0 +1,356 @@
+use std::{collections::HashMap, io::Write, path::PathBuf};
+
+use clap::{Args, Subcommand};
+use jaytripper_app::{
+    AppRuntime, SignatureSnapshotRecordContext, SignatureSnapshotRecordOutcome,
+    SystemSignaturesView,
+};
+use jaytripper_core::{ProjectedSignature, ids::SolarSystemId};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, BufReader};
+
+#[derive(Debug, Args)]
+pub(crate) struct SignaturesCommand {
+    #[command(subcommand)]
+    subcmd: SignaturesSubcommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum SignaturesSubcommand {
+    /// Record that a system was scanned and has no signatures.
+    Clear(ClearCommand),
+    /// Record each paste from stdin (ended by an empty line) and print what
+    /// changed, until EOF or Ctrl+C.
+    Watch(WatchCommand),
+}
+
+impl SignaturesCommand {
+    pub(crate) async fn run(&self) -> anyhow::Result<()> {
+        match &self.subcmd {
+            SignaturesSubcommand::Clear(cmd) => cmd.run().await,
+            SignaturesSubcommand::Watch(cmd) => cmd.run().await,
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+struct ClearCommand {
+    #[arg(long, default_value = "jaytripper.sqlite")]
+    db: PathBuf,
+
+    #[arg(long)]
+    system: i32,
+
+    /// Confirm that the system's signatures should be cleared.
+    #[arg(long)]
+    yes: bool,
+}
+
+impl ClearCommand {
+    async fn run(&self) -> anyhow::Result<()> {
+        let system_id = SolarSystemId(self.system);
+        require_clear_confirmation(self.yes, system_id)?;
+
+        let app = AppRuntime::connect(&self.db).await?;
+        print!("{}", clear_output(&app, system_id).await?);
+        Ok(())
+    }
+}
+
+fn require_clear_confirmation(yes: bool, system_id: SolarSystemId) -> anyhow::Result<()> {
+    if !yes {
+        anyhow::bail!(
+            "This records system {system_id} as scanned with no signatures. Re-run with --yes to confirm."
+        );
+    }
+    Ok(())
+}
+
+async fn clear_output(app: &AppRuntime, system_id: SolarSystemId) -> anyhow::Result<String> {
+    app.clear_system_signatures(system_id, None).await?;
+    let remaining = app
+        .system_signatures(system_id)
+        .await
+        .map(|view| view.signatures.len())
+        .unwrap_or_default();
+
+    Ok(format!(
+        "Cleared signatures in system {system_id}; {remaining} signature(s) remain\n"
+    ))
+}
+
+#[derive(Debug, Args)]
+struct WatchCommand {
+    #[arg(long, default_value = "jaytripper.sqlite")]
+    db: PathBuf,
+
+    #[arg(long)]
+    system: i32,
+}
+
+impl WatchCommand {
+    async fn run(&self) -> anyhow::Result<()> {
+        let system_id = SolarSystemId(self.system);
+        let app = AppRuntime::connect(&self.db).await?;
+        println!("Watching system {system_id}; paste signatures and press Enter on an empty line.");
+
+        let stdin = BufReader::new(tokio::io::stdin());
+        let mut stdout = std::io::stdout();
+        tokio::select! {
+            result = watch_pastes(&app, system_id, stdin, &mut stdout) => result,
+            _ = tokio::signal::ctrl_c() => Ok(()),
+        }
+    }
+}
+
+/// Reads pastes separated by empty lines from `input`, recording each one
+/// for `system_id` and writing the resulting diff to `out`. A trailing paste
+/// without an empty line is still recorded at EOF.
+async fn watch_pastes<R, W>(
+    app: &AppRuntime,
+    system_id: SolarSystemId,
+    input: R,
+    out: &mut W,
+) -> anyhow::Result<()>
+where
+    R: AsyncBufRead + Unpin,
+    W: Write,
+{
+    let mut lines = input.lines();
+    let mut paste = String::new();
+
+    loop {
+        let line = lines.next_line().await?;
+        let submit = match &line {
+            Some(line) if line.trim().is_empty() => true,
+            Some(line) => {
+                paste.push_str(line);
+                paste.push('\n');
+                false
+            }
+            None => true,
+        };
+
+        if submit && !paste.is_empty() {
+            let output = record_and_diff(app, system_id, &paste).await?;
+            out.write_all(output.as_bytes())?;
+            out.flush()?;
+            paste.clear();
+        }
+        if line.is_none() {
+            return Ok(());
+        }
+    }
+}
+
+async fn record_and_diff(
+    app: &AppRuntime,
+    system_id: SolarSystemId,
+    paste: &str,
+) -> anyhow::Result<String> {
+    let before = app.system_signatures(system_id).await;
+    let outcome = app
+        .record_signature_snapshot_lenient(
+            SignatureSnapshotRecordContext::Explicit {
+                system_id,
+                attribution_character_id: None,
+            },
+            paste,
+        )
+        .await?;
+
+    let mut output = String::new();
+    match outcome {
+        SignatureSnapshotRecordOutcome::Recorded { .. } => {}
+        SignatureSnapshotRecordOutcome::RecordedWithWarnings { warnings, .. } => {
+            for (line, warning) in warnings {
+                output.push_str(&format!("skipped line {line}: {warning}\n"));
+            }
+        }
+        SignatureSnapshotRecordOutcome::Empty => {
+            return Ok("No signatures in paste; nothing recorded\n".to_owned());
+        }
+        SignatureSnapshotRecordOutcome::AlreadyRecorded { .. } => {
+            return Ok("Same paste as the last one; nothing recorded\n".to_owned());
+        }
+        other => anyhow::bail!("unexpected outcome for system {system_id}: {other:?}"),
+    }
+
+    let after = app.system_signatures(system_id).await;
+    output.push_str(&render_diff(system_id, before.as_ref(), after.as_ref()));
+    Ok(output)
+}
+
+/// Lists signatures that appeared, vanished, or gained scan strength between
+/// two projections of the same system.
+fn render_diff(
+    system_id: SolarSystemId,
+    before: Option<&SystemSignaturesView>,
+    after: Option<&SystemSignaturesView>,
+) -> String {
+    let present = |view: Option<&SystemSignaturesView>| -> HashMap<String, ProjectedSignature> {
+        view.map(|view| {
+            view.signatures
+                .iter()
+                .filter(|signature| !signature.missing_from_latest_snapshot)
+                .map(|signature| (signature.signature_id.clone(), signature.clone()))
+                .collect()
+        })
+        .unwrap_or_default()
+    };
+    let before = present(before);
+    let after = present(after);
+
+    let mut added: Vec<&ProjectedSignature> = after
+        .values()
+        .filter(|signature| !before.contains_key(&signature.signature_id))
+        .collect();
+    let mut removed: Vec<&ProjectedSignature> = before
+        .values()
+        .filter(|signature| !after.contains_key(&signature.signature_id))
+        .collect();
+    let mut progressed: Vec<(&ProjectedSignature, &ProjectedSignature)> = after
+        .values()
+        .filter_map(|signature| {
+            let previous = before.get(&signature.signature_id)?;
+            (signature.latest_scan_percent.unwrap_or(0.0)
+                > previous.latest_scan_percent.unwrap_or(0.0))
+            .then_some((previous, signature))
+        })
+        .collect();
+    added.sort_by(|left, right| left.signature_id.cmp(&right.signature_id));
+    removed.sort_by(|left, right| left.signature_id.cmp(&right.signature_id));
+    progressed.sort_by(|left, right| left.1.signature_id.cmp(&right.1.signature_id));
+
+    let mut output = format!(
+        "System {system_id}: {} added, {} removed, {} progressed\n",
+        added.len(),
+        removed.len(),
+        progressed.len()
+    );
+    for signature in added {
+        output.push_str(&format!("  + {}\n", describe_signature(signature)));
+    }
+    for signature in removed {
+        output.push_str(&format!("  - {}\n", describe_signature(signature)));
+    }
+    for (previous, signature) in progressed {
+        output.push_str(&format!(
+            "  ~ {} {} -> {}\n",
+            describe_signature(signature),
+            format_scan_percent(previous.latest_scan_percent),
+            format_scan_percent(signature.latest_scan_percent)
+        ));
+    }
+    output
+}
+
+fn describe_signature(signature: &ProjectedSignature) -> String {
+    let mut description = format!("{} {}", signature.signature_id, signature.group);
+    if let Some(site_type) = &signature.site_type {
+        description.push_str(&format!(" / {site_type}"));
+    }
+    if let Some(name) = &signature.name {
+        description.push_str(&format!(" ({name})"));
+    }
+    description
+}
+
+fn format_scan_percent(scan_percent: Option<f32>) -> String {
+    scan_percent
+        .map(|value| format!("{value:.1}%"))
+        .unwrap_or_else(|| "?".to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use jaytripper_app::{AppRuntime, SignatureSnapshotRecordContext};
+    use jaytripper_core::{SYSTEM_SIGNATURES_CLEARED_EVENT_TYPE, ids::SolarSystemId};
+    use jaytripper_store::test_util::temp_store;
+
+    use super::{clear_output, require_clear_confirmation, watch_pastes};
+
+    #[tokio::test]
+    async fn watch_prints_delta_for_each_paste() {
+        let (_temp_dir, store) = temp_store().await;
+        let app = AppRuntime::from_store(store).await.expect("app");
+        let input = concat!(
+            "ABC-123\tCosmic Signature\tGas Site\t\t10.0%\n",
+            "DEF-456\tCosmic Signature\t\t\t0.0%\n",
+            "\n",
+            "ABC-123\tCosmic Signature\tGas Site\t\t45.0%\n",
+            "GHI-789\tCosmic Signature\t\t\t5.0%\n",
+            "\n",
+        );
+        let mut out = Vec::new();
+
+        watch_pastes(&app, SolarSystemId(31000005), input.as_bytes(), &mut out)
+            .await
+            .expect("watch pastes");
+
+        let out = String::from_utf8(out).expect("utf-8 output");
+        let (first, second) = out
+            .split_once("System 31000005: 1 added, 1 removed, 1 progressed\n")
+            .expect("second paste should print its delta");
+        assert_eq!(
+            first,
+            concat!(
+                "System 31000005: 2 added, 0 removed, 0 progressed\n",
+                "  + ABC-123 Cosmic Signature / Gas Site\n",
+                "  + DEF-456 Cosmic Signature\n",
+            )
+        );
+        assert_eq!(
+            second,
+            concat!(
+                "  + GHI-789 Cosmic Signature\n",
+                "  - DEF-456 Cosmic Signature\n",
+                "  ~ ABC-123 Cosmic Signature / Gas Site 10.0% -> 45.0%\n",
+            )
+        );
+    }
+
+    #[test]
+    fn clear_requires_explicit_confirmation() {
+        let err = require_clear_confirmation(false, SolarSystemId(31000005))
+            .expect_err("clear without --yes");
+
+        assert!(err.to_string().contains("--yes"));
+        require_clear_confirmation(true, SolarSystemId(31000005)).expect("confirmed clear");
+    }
+
+    #[tokio::test]
+    async fn clear_appends_cleared_event_and_empties_projection() {
+        let (_temp_dir, store) = temp_store().await;
+        let app = AppRuntime::from_store(store.clone()).await.expect("app");
+        let system_id = SolarSystemId(31000005);
+        app.record_signature_snapshot(
+            SignatureSnapshotRecordContext::Explicit {
+                system_id,
+                attribution_character_id: None,
+            },
+            "ABC-123\tCosmic Signature\tGas Site\t\t10.0%\n",
+        )
+        .await
+        .expect("record snapshot");
+
+        let output = clear_output(&app, system_id).await.expect("clear system");
+
+        assert_eq!(
+            output,
+            "Cleared signatures in system 31000005; 0 signature(s) remain\n"
+        );
+        let events = store.read_ordered_events().await.expect("read events");
+        assert_eq!(
+            events.last().expect("cleared event").envelope.event_type,
+            SYSTEM_SIGNATURES_CLEARED_EVENT_TYPE
+        );
+        assert_eq!(
+            app.system_signatures(system_id)
+                .await
+                .expect("system is known")
+                .signatures
+                .len(),
+            0
+        );
+    }
+}
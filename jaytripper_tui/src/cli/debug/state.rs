@@ -1,7 +1,13 @@
-use std::path::PathBuf;
+use std::{
+    fmt::Write as _,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 use clap::{Args, Subcommand};
-use jaytripper_app::AppRuntime;
+use jaytripper_app::{AppRuntime, CharacterLocationView};
+
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
 
 #[derive(Debug, Args)]
 pub(crate) struct StateCommand {
@@ -27,26 +33,106 @@ impl StateCommand {
 struct SnapshotCommand {
     #[arg(long, default_value = "jaytripper.sqlite")]
     db: PathBuf,
+
+    /// Re-render the snapshot until Ctrl+C.
+    #[arg(long)]
+    watch: bool,
+
+    /// Seconds between renders in watch mode.
+    #[arg(long, default_value_t = 2)]
+    interval_secs: u64,
 }
 
 impl SnapshotCommand {
     async fn run(&self) -> anyhow::Result<()> {
         let app = AppRuntime::connect(&self.db).await?;
-        let mut rows = app.character_locations().await;
-        rows.sort_by_key(|row| row.character_id.0);
-
-        println!("DB: {}", self.db.display());
-        println!("characters: {}", rows.len());
-
-        for row in rows {
-            println!(
-                "character={} current_system={} observed_at={}",
-                row.character_id,
-                row.current_system_id,
-                row.last_movement_observed_at.as_epoch_secs(),
-            );
+
+        if !self.watch {
+            print!("{}", self.render(&app).await);
+            return Ok(());
+        }
+
+        let interval = Duration::from_secs(self.interval_secs.max(1));
+        loop {
+            print!("{CLEAR_SCREEN}{}", self.render(&app).await);
+
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => break,
+                _ = tokio::time::sleep(interval) => {
+                    // `connect` replayed the log once; only project what the
+                    // tracker appended since.
+                    app.catch_up().await?;
+                }
+            }
         }
 
         Ok(())
     }
+
+    async fn render(&self, app: &AppRuntime) -> String {
+        render_snapshot(&self.db, app.character_locations().await)
+    }
+}
+
+fn render_snapshot(db: &Path, mut rows: Vec<CharacterLocationView>) -> String {
+    rows.sort_by_key(|row| row.character_id.0);
+
+    let mut out = String::new();
+    let _ = writeln!(out, "DB: {}", db.display());
+    let _ = writeln!(out, "characters: {}", rows.len());
+
+    for row in rows {
+        let _ = writeln!(
+            out,
+            "character={} current_system={} observed_at={}",
+            row.character_id,
+            row.current_system_id,
+            row.last_movement_observed_at.as_epoch_secs(),
+        );
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use jaytripper_app::CharacterLocationView;
+    use jaytripper_core::{
+        Timestamp,
+        ids::{CharacterId, SolarSystemId},
+    };
+
+    use super::render_snapshot;
+
+    #[test]
+    fn render_snapshot_is_stable_and_sorted_by_character() {
+        let rows = vec![
+            CharacterLocationView {
+                character_id: CharacterId(100),
+                current_system_id: SolarSystemId(30002510),
+                last_movement_observed_at: ts(1_700_000_060),
+            },
+            CharacterLocationView {
+                character_id: CharacterId(42),
+                current_system_id: SolarSystemId(30000142),
+                last_movement_observed_at: ts(1_700_000_000),
+            },
+        ];
+
+        let rendered = render_snapshot(Path::new("jaytripper.sqlite"), rows);
+
+        assert_eq!(
+            rendered,
+            "DB: jaytripper.sqlite\n\
+             characters: 2\n\
+             character=42 current_system=30000142 observed_at=1700000000\n\
+             character=100 current_system=30002510 observed_at=1700000060\n"
+        );
+    }
+
+    fn ts(epoch_secs: i64) -> Timestamp {
+        Timestamp::from_epoch_secs(epoch_secs).expect("valid epoch seconds")
+    }
 }
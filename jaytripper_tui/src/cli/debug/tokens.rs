@@ -0,0 +1,169 @@
+use std::{
+    fs,
+    io::{self, Read},
+    path::PathBuf,
+};
+
+use anyhow::Context;
+use clap::{Args, Subcommand};
+use jaytripper_esi::{AuthSession, TokenStore};
+
+use crate::cli::debug::common::{CharacterArgs, token_store};
+
+const EXPORT_WARNING: &str = "WARNING: the exported session contains live access and refresh tokens. \
+Anyone holding them can act as this character; do not paste or store them anywhere shared.";
+
+#[derive(Debug, Args)]
+pub(crate) struct TokensCommand {
+    #[command(subcommand)]
+    subcmd: TokensSubcommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum TokensSubcommand {
+    /// Print a stored session, including tokens, as JSON on stdout.
+    Export(ExportCommand),
+
+    /// Save a session from exported JSON into the keyring.
+    Import(ImportCommand),
+}
+
+impl TokensCommand {
+    pub(crate) async fn run(&self) -> anyhow::Result<()> {
+        match &self.subcmd {
+            TokensSubcommand::Export(cmd) => cmd.run(),
+            TokensSubcommand::Import(cmd) => cmd.run(),
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+struct ExportCommand {
+    #[command(flatten)]
+    character: CharacterArgs,
+
+    /// Confirm that printing unredacted tokens is intended.
+    #[arg(long)]
+    yes: bool,
+}
+
+impl ExportCommand {
+    fn run(&self) -> anyhow::Result<()> {
+        require_export_confirmation(self.yes)?;
+        let character_id = self.character.required()?;
+
+        let session = token_store()
+            .load_session(character_id)?
+            .with_context(|| format!("no session found in keyring for character {character_id}"))?;
+
+        eprintln!("{EXPORT_WARNING}");
+        println!("{}", render_session_export(&session)?);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+struct ImportCommand {
+    /// Read the exported JSON from this file instead of stdin.
+    #[arg(long)]
+    file: Option<PathBuf>,
+}
+
+impl ImportCommand {
+    fn run(&self) -> anyhow::Result<()> {
+        let raw = match &self.file {
+            Some(path) => fs::read_to_string(path)
+                .with_context(|| format!("failed to read {}", path.display()))?,
+            None => {
+                let mut raw = String::new();
+                io::stdin()
+                    .read_to_string(&mut raw)
+                    .context("failed to read session JSON from stdin")?;
+                raw
+            }
+        };
+
+        let session = parse_session_import(&raw)?;
+        token_store().save_session(&session)?;
+        println!(
+            "Imported session for character {} into keyring.",
+            session.character_id
+        );
+        Ok(())
+    }
+}
+
+fn require_export_confirmation(yes: bool) -> anyhow::Result<()> {
+    if !yes {
+        anyhow::bail!("{EXPORT_WARNING}\nRe-run with --yes to print the session.");
+    }
+    Ok(())
+}
+
+/// Serializes the full session. Unlike its `Debug` output, this deliberately
+/// keeps both tokens so the session can be imported elsewhere.
+fn render_session_export(session: &AuthSession) -> anyhow::Result<String> {
+    serde_json::to_string_pretty(session).context("failed to serialize session")
+}
+
+fn parse_session_import(raw: &str) -> anyhow::Result<AuthSession> {
+    let session: AuthSession =
+        serde_json::from_str(raw.trim()).context("input is not an exported session")?;
+    if session.access_token.is_empty() || session.refresh_token.is_empty() {
+        anyhow::bail!("exported session is missing its tokens");
+    }
+    Ok(session)
+}
+
+#[cfg(test)]
+mod tests {
+    use jaytripper_core::{Timestamp, ids::CharacterId};
+    use jaytripper_esi::AuthSession;
+
+    use super::{parse_session_import, render_session_export, require_export_confirmation};
+
+    fn session() -> AuthSession {
+        AuthSession {
+            character_id: CharacterId(90000001),
+            character_name: Some("Pilot".to_owned()),
+            scopes: vec!["esi-location.read_location.v1".to_owned()],
+            access_token: "access-secret".to_owned(),
+            access_expires_at: Timestamp::from_epoch_secs(1_700_000_000).expect("timestamp"),
+            refresh_token: "refresh-secret".to_owned(),
+            updated_at: Timestamp::from_epoch_secs(1_700_000_000).expect("timestamp"),
+        }
+    }
+
+    #[test]
+    fn export_requires_explicit_confirmation() {
+        let err = require_export_confirmation(false).expect_err("export without --yes");
+
+        assert!(err.to_string().contains("--yes"));
+        assert!(require_export_confirmation(true).is_ok());
+    }
+
+    #[test]
+    fn export_is_unredacted_and_round_trips_through_import() {
+        let rendered = render_session_export(&session()).expect("render");
+
+        assert!(rendered.contains("refresh-secret"));
+        assert!(!format!("{:?}", session()).contains("refresh-secret"));
+        assert_eq!(parse_session_import(&rendered).expect("import"), session());
+    }
+
+    #[test]
+    fn import_rejects_non_session_json() {
+        assert!(parse_session_import("{\"character_id\": 1}").is_err());
+        assert!(parse_session_import("not json").is_err());
+    }
+
+    #[test]
+    fn import_rejects_session_without_tokens() {
+        let mut session = session();
+        session.refresh_token.clear();
+        let raw = serde_json::to_string(&session).expect("serialize");
+
+        let err = parse_session_import(&raw).expect_err("missing token");
+        assert!(err.to_string().contains("missing its tokens"));
+    }
+}
@@ -1,11 +1,12 @@
 use std::{path::PathBuf, time::Instant};
 
-use clap::{Args, Subcommand};
+use clap::{Args, Subcommand, ValueEnum};
 use jaytripper_app::AppRuntime;
+use jaytripper_core::ids::SolarSystemId;
 use jaytripper_esi::LocationPollConfig;
 use tokio::{sync::watch, time::Duration};
 
-use crate::cli::debug::common::{build_auth_service, load_esi_config, required_character_id};
+use crate::cli::debug::common::{CharacterArgs, build_auth_service, load_esi_config};
 
 #[derive(Debug, Args)]
 pub(crate) struct TrackCommand {
@@ -29,40 +30,107 @@ impl TrackCommand {
 
 #[derive(Debug, Args)]
 struct RunCommand {
-    #[arg(long)]
-    character_id: Option<u64>,
+    #[command(flatten)]
+    character: CharacterArgs,
 
     #[arg(long, default_value = "jaytripper.sqlite")]
     db: PathBuf,
+
+    /// Polling cadence; faster profiles spend more of the ESI request budget.
+    #[arg(long, value_enum, default_value_t = PollProfile::Balanced)]
+    profile: PollProfile,
+
+    /// Print detected movements without writing them to the database.
+    #[arg(long)]
+    no_persist: bool,
+
+    /// Ring the terminal bell when a character enters this system. Repeatable.
+    #[arg(long = "alert-system", value_name = "SYSTEM_ID")]
+    alert_systems: Vec<i32>,
+
+    /// Keep running when the session needs reauth, checking every SECS
+    /// seconds for a new login (e.g. `debug auth login` in another terminal).
+    #[arg(long, value_name = "SECS")]
+    reauth_retry_secs: Option<u64>,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum PollProfile {
+    Aggressive,
+    Balanced,
+    Conservative,
+}
+
+impl PollProfile {
+    fn config(self) -> LocationPollConfig {
+        match self {
+            Self::Aggressive => LocationPollConfig::aggressive(),
+            Self::Balanced => LocationPollConfig::balanced(),
+            Self::Conservative => LocationPollConfig::conservative(),
+        }
+    }
 }
 
 impl RunCommand {
     async fn run(&self) -> anyhow::Result<()> {
-        let character_id = required_character_id(self.character_id)?;
+        let character_id = self.character.required()?;
         let config = load_esi_config("jaytripper-tui-track/0.1")?;
 
         println!("Tracking character {character_id}.");
-        println!("Persisting events to {}", self.db.display());
+        if self.no_persist {
+            println!("Observation only: movements will not be persisted.");
+        } else {
+            println!("Persisting events to {}", self.db.display());
+        }
 
-        let app = AppRuntime::connect(&self.db).await?;
+        let mut app = AppRuntime::connect(&self.db).await?;
+        if let Some(secs) = self.reauth_retry_secs {
+            app = app.with_reauth_retry(Duration::from_secs(secs));
+        }
         let auth = build_auth_service(&config)?;
         let esi_client = auth.connect_character(character_id).await?;
+        let poll_config = self.profile.config();
+
+        // Watch and subscribe before ingestion starts so the first poll's
+        // alerts and notices are not missed.
+        for system_id in &self.alert_systems {
+            app.watch_system(SolarSystemId(*system_id)).await;
+        }
+        let mut alerts = app.subscribe_alerts();
+        let mut reauth_notices = app.subscribe_reauth_needed();
+        let mut movements = app.subscribe_movements();
 
         let (shutdown_tx, shutdown_rx) = watch::channel(false);
         let app_for_task = app.clone();
+        let no_persist = self.no_persist;
         let mut ingestion_task = tokio::spawn(async move {
-            app_for_task
-                .run_ingestion_until_shutdown(
-                    esi_client,
-                    LocationPollConfig::default(),
-                    shutdown_rx,
-                )
-                .await
+            if no_persist {
+                app_for_task
+                    .observe_ingestion_until_shutdown(
+                        esi_client,
+                        poll_config,
+                        shutdown_rx,
+                        |event| {
+                            println!(
+                                "character {} -> system {} (not persisted)",
+                                event.character_id, event.to_system_id
+                            );
+                        },
+                    )
+                    .await
+            } else {
+                app_for_task
+                    .run_ingestion_until_shutdown(esi_client, poll_config, shutdown_rx)
+                    .await
+            }
         });
 
         println!("Listening for movement updates. Press Ctrl+C to stop.");
 
-        let mut last_system = None;
+        let mut last_system = app.character_current_system(character_id).await;
+        if let Some(current_system_id) = last_system {
+            println!("character {character_id} -> system {current_system_id}");
+        }
         let mut last_wait_log = Instant::now();
         loop {
             tokio::select! {
@@ -85,14 +153,29 @@ impl RunCommand {
                     let _ = shutdown_tx.send(true);
                     break;
                 }
-                _ = tokio::time::sleep(Duration::from_millis(500)) => {
-                    if let Some(current_system_id) = app.character_current_system(character_id).await {
-                        if last_system != Some(current_system_id) {
-                            last_system = Some(current_system_id);
-                            println!("character {character_id} -> system {}", current_system_id);
-                        }
-                        last_wait_log = Instant::now();
-                    } else if last_wait_log.elapsed() >= Duration::from_secs(5) {
+                Ok(()) = movements.changed() => {
+                    let movement = movements.borrow_and_update().clone();
+                    if let Some(event) = movement.filter(|event| event.character_id == character_id)
+                        && last_system != Some(event.to_system_id)
+                    {
+                        last_system = Some(event.to_system_id);
+                        println!("character {character_id} -> system {}", event.to_system_id);
+                    }
+                }
+                Ok(alert) = alerts.recv() => {
+                    println!(
+                        "\x07ALERT: character {} entered watched system {}",
+                        alert.character_id, alert.system_id
+                    );
+                }
+                Ok(notice) = reauth_notices.recv() => {
+                    eprintln!(
+                        "character {} needs reauth ({}); run `debug auth login` to resume",
+                        notice.character_id, notice.reason
+                    );
+                }
+                _ = tokio::time::sleep(Duration::from_millis(500)), if !no_persist && last_system.is_none() => {
+                    if last_wait_log.elapsed() >= Duration::from_secs(5) {
                         eprintln!("waiting for first movement event for character {character_id}...");
                         last_wait_log = Instant::now();
                     }